@@ -1,16 +1,17 @@
 use clap::{Parser, Subcommand, ValueEnum};
-use chrono::{Datelike, NaiveDate, Timelike};
+use chrono::{Datelike, Timelike};
 use std::path::PathBuf;
 use std::process::ExitCode;
+use std::sync::Arc;
 use tracing::{info, error, warn};
 use tracing_subscriber::EnvFilter;
 
-use bqdrift::{QueryLoader, QueryValidator, Runner, CheckStatus, Severity, InvariantChecker, resolve_invariants_def};
-use bqdrift::{DriftDetector, DriftState, decode_sql, format_sql_diff, has_changes, ImmutabilityChecker, ImmutabilityViolation, SourceAuditor, SourceStatus, AuditTableRow};
+use bqdrift::{QueryLoader, QueryValidator, DependencyGraph, GraphFormat, Runner, BackfillOptions, MigrationTracker, StateStore, LocalStateStore, CheckStatus, Severity, InvariantChecker, resolve_invariants_def};
+use bqdrift::{DriftDetector, DriftState, decompress_from_base64, format_sql_diff, has_changes, ImmutabilityChecker, ImmutabilityViolation, SourceAuditor, SourceStatus, AuditTableRow, SchemaChecker, SchemaCheckReport, SchemaCheckTableRow};
 use tabled::{Table, settings::Style};
-use bqdrift::executor::BqClient;
+use bqdrift::executor::{BqClient, ClientConfig, CostEstimate, Credentials, RetryPolicy, cascade_plan, format_bytes, load_credential_profiles, parse_byte_size};
 use bqdrift::error::{BqDriftError, BigQueryError};
-use bqdrift::executor::PartitionWriteStats;
+use bqdrift::executor::{PartitionWriteStats, RunReport};
 use bqdrift::schema::{PartitionKey, PartitionType};
 
 #[derive(Parser)]
@@ -29,6 +30,59 @@ struct Cli {
     #[arg(short, long, env = "GCP_PROJECT_ID")]
     project: Option<String>,
 
+    /// Location of created datasets and of jobs (e.g. EU, us-east1), unless a
+    /// query sets destination.location. BigQuery's default (US) when unset
+    #[arg(long, global = true, env = "BQDRIFT_LOCATION")]
+    location: Option<String>,
+
+    /// Authenticate with this service account JSON key file instead of
+    /// application default credentials
+    #[arg(long, global = true, env = "BQDRIFT_SERVICE_ACCOUNT_KEY", conflicts_with_all = ["authorized_user", "workload_identity"])]
+    service_account_key: Option<PathBuf>,
+
+    /// Authenticate with this authorized_user JSON file, as written by
+    /// `gcloud auth application-default login`
+    #[arg(long, global = true, env = "BQDRIFT_AUTHORIZED_USER", conflicts_with = "workload_identity")]
+    authorized_user: Option<PathBuf>,
+
+    /// Authenticate as the workload's service account through the GKE metadata server
+    #[arg(long, global = true, env = "BQDRIFT_WORKLOAD_IDENTITY")]
+    workload_identity: bool,
+
+    /// Impersonate this service account, using --authorized-user or gcloud's
+    /// user credentials to mint its tokens
+    #[arg(long, global = true, env = "BQDRIFT_IMPERSONATE_SERVICE_ACCOUNT", conflicts_with_all = ["service_account_key", "workload_identity"])]
+    impersonate_service_account: Option<String>,
+
+    /// Send requests to this BigQuery API root instead of Google's, e.g. an
+    /// emulator at http://localhost:9050
+    #[arg(long, global = true, env = "BQDRIFT_ENDPOINT")]
+    endpoint: Option<String>,
+
+    /// Send no credentials; for emulators reached through --endpoint
+    #[arg(long, global = true, env = "BQDRIFT_NO_AUTH", conflicts_with_all = ["service_account_key", "authorized_user", "workload_identity", "impersonate_service_account"])]
+    no_auth: bool,
+
+    /// Track partition state in a local JSONL file (e.g. .bqdrift/state.jsonl)
+    /// instead of the BigQuery tracking dataset
+    #[arg(long, global = true, env = "BQDRIFT_STATE_FILE")]
+    state_file: Option<PathBuf>,
+
+    /// Retries for BigQuery calls that fail with a transient error
+    /// (rate limits, timeouts, dropped connections), with exponential backoff
+    #[arg(long, global = true, default_value = "3", env = "BQDRIFT_MAX_RETRIES")]
+    max_retries: u32,
+
+    /// Byte budget (e.g. 500GB, 2TB). Sets maximumBytesBilled on every job, and
+    /// run, backfill and sync refuse to start when their dry-run estimate exceeds it
+    #[arg(long, global = true, env = "BQDRIFT_MAX_BYTES_BILLED", value_parser = parse_byte_budget)]
+    max_bytes_billed: Option<i64>,
+
+    /// Cancel BigQuery jobs still running after this many seconds. Ctrl-C or
+    /// SIGTERM cancels running jobs too; cancelled partitions are recorded as CANCELLED
+    #[arg(long, global = true, env = "BQDRIFT_JOB_TIMEOUT")]
+    job_timeout: Option<u64>,
+
     /// Enable verbose output
     #[arg(short, long)]
     verbose: bool,
@@ -52,6 +106,11 @@ struct Cli {
     /// Maximum allowed idle timeout in seconds (server mode only)
     #[arg(long, default_value = "3600", requires = "repl")]
     max_idle_timeout: u64,
+
+    /// YAML file of named credentials that sessions can select with
+    /// session_create's `credentials` parameter (server mode only)
+    #[arg(long, env = "BQDRIFT_CREDENTIAL_PROFILES", requires = "repl")]
+    credential_profiles: Option<PathBuf>,
 }
 
 #[derive(Subcommand)]
@@ -84,6 +143,10 @@ enum Commands {
         #[arg(long)]
         skip_invariants: bool,
 
+        /// Estimate bytes processed with dry-run jobs instead of executing
+        #[arg(long, conflicts_with_all = ["dry_run", "scratch"])]
+        estimate: bool,
+
         /// Scratch project for testing (writes to scratch instead of production)
         #[arg(long, env = "BQDRIFT_SCRATCH_PROJECT")]
         scratch: Option<String>,
@@ -91,6 +154,10 @@ enum Commands {
         /// TTL for scratch tables in hours (default: auto based on partition type)
         #[arg(long)]
         scratch_ttl: Option<u32>,
+
+        /// Dataset for tracking table
+        #[arg(long, default_value = "bqdrift")]
+        tracking_dataset: String,
     },
 
     /// Backfill a query for a date range
@@ -110,9 +177,29 @@ enum Commands {
         #[arg(long)]
         dry_run: bool,
 
+        /// Estimate bytes processed with dry-run jobs instead of executing
+        #[arg(long, conflicts_with = "dry_run")]
+        estimate: bool,
+
         /// Skip invariant checks
         #[arg(long)]
         skip_invariants: bool,
+
+        /// Number of partitions to write concurrently
+        #[arg(long, default_value = "1")]
+        concurrency: usize,
+
+        /// Stop starting new partitions after this many failures; the rest are reported as skipped
+        #[arg(long)]
+        max_failures: Option<usize>,
+
+        /// Also backfill every query downstream of this one, in dependency order
+        #[arg(long)]
+        cascade: bool,
+
+        /// Dataset for tracking table
+        #[arg(long, default_value = "bqdrift")]
+        tracking_dataset: String,
     },
 
     /// Run invariant checks only (no query execution)
@@ -152,11 +239,11 @@ enum Commands {
 
     /// Detect and sync drifted partitions
     Sync {
-        /// Date range start (defaults to 30 days ago, e.g., 2024-01-15)
+        /// Start partition (defaults to 30 days ago, e.g., 2024-01-15, 2024-01-15T10, 2024-01, 2024)
         #[arg(short, long)]
         from: Option<String>,
 
-        /// Date range end (defaults to today, e.g., 2024-01-15)
+        /// End partition (defaults to today, e.g., 2024-01-15, 2024-01-15T10, 2024-01, 2024)
         #[arg(short, long)]
         to: Option<String>,
 
@@ -164,6 +251,10 @@ enum Commands {
         #[arg(long)]
         dry_run: bool,
 
+        /// Estimate bytes processed with dry-run jobs instead of executing
+        #[arg(long, conflicts_with = "dry_run")]
+        estimate: bool,
+
         /// Skip invariant checks when syncing
         #[arg(long)]
         skip_invariants: bool,
@@ -200,6 +291,39 @@ enum Commands {
         tracking_dataset: String,
     },
 
+    /// Show the query dependency graph
+    Graph {
+        /// Focus on this query (name or dataset.table)
+        query: Option<String>,
+
+        /// With a query: show only what it reads from
+        #[arg(long, requires = "query")]
+        upstream: bool,
+
+        /// With a query: show only what reads from it
+        #[arg(long, requires = "query")]
+        downstream: bool,
+
+        /// Use the versions in effect for this partition date instead of the latest
+        #[arg(short, long)]
+        date: Option<chrono::NaiveDate>,
+
+        /// Output format
+        #[arg(short, long, default_value = "ascii")]
+        format: GraphOutput,
+    },
+
+    /// Compare live destination tables with their expected schema,
+    /// partitioning and clustering
+    SchemaCheck {
+        /// Query name (checks all if not specified)
+        query: Option<String>,
+
+        /// Output format: table, yaml, json
+        #[arg(short, long, default_value = "table")]
+        output: OutputFormat,
+    },
+
     /// Manage scratch tables
     Scratch {
         #[command(subcommand)]
@@ -238,6 +362,25 @@ enum OutputFormat {
     Json,
 }
 
+#[derive(Debug, Clone, Copy, ValueEnum)]
+enum GraphOutput {
+    Ascii,
+    Dot,
+    Mermaid,
+    Json,
+}
+
+impl From<GraphOutput> for GraphFormat {
+    fn from(output: GraphOutput) -> Self {
+        match output {
+            GraphOutput::Ascii => GraphFormat::Ascii,
+            GraphOutput::Dot => GraphFormat::Dot,
+            GraphOutput::Mermaid => GraphFormat::Mermaid,
+            GraphOutput::Json => GraphFormat::Json,
+        }
+    }
+}
+
 #[tokio::main]
 async fn main() -> ExitCode {
     let cli = Cli::parse();
@@ -283,6 +426,10 @@ fn print_bq_error(err: &BigQueryError) {
     eprintln!();
 }
 
+fn parse_byte_budget(s: &str) -> Result<i64, String> {
+    parse_byte_size(s).map_err(|e| e.to_string())
+}
+
 fn parse_partition_key(s: &str, partition_type: &PartitionType) -> Result<PartitionKey, Box<dyn std::error::Error>> {
     PartitionKey::parse(s, partition_type)
         .map_err(|e| e.into())
@@ -302,11 +449,43 @@ fn default_partition_key(partition_type: &PartitionType) -> PartitionKey {
     }
 }
 
+fn client_config(cli: &Cli) -> ClientConfig {
+    ClientConfig::default()
+        .with_retry(RetryPolicy::default().with_max_attempts(cli.max_retries + 1))
+        .with_maximum_bytes_billed(cli.max_bytes_billed)
+        .with_job_timeout(cli.job_timeout.map(std::time::Duration::from_secs))
+        .with_location(cli.location.clone())
+        .with_credentials(credentials(cli))
+        .with_endpoint(cli.endpoint.clone())
+}
+
+fn credentials(cli: &Cli) -> Credentials {
+    if cli.no_auth {
+        return Credentials::NoAuth;
+    }
+
+    if let Some(service_account) = &cli.impersonate_service_account {
+        return Credentials::Impersonated {
+            service_account: service_account.clone(),
+            user_credentials: cli.authorized_user.clone(),
+        };
+    }
+
+    match (&cli.service_account_key, &cli.authorized_user) {
+        (Some(path), _) => Credentials::ServiceAccountKey { path: path.clone() },
+        (None, Some(path)) => Credentials::AuthorizedUser { path: path.clone() },
+        (None, None) if cli.workload_identity => Credentials::WorkloadIdentity,
+        (None, None) => Credentials::ApplicationDefault,
+    }
+}
+
 async fn run(cli: Cli) -> Result<(), Box<dyn std::error::Error>> {
     if cli.repl {
         return run_repl(cli).await;
     }
 
+    let client_config = client_config(&cli);
+    client_config.cancel.cancel_on_shutdown();
     let command = cli.command.ok_or("No command specified. Use --help for usage or --repl for interactive mode.")?;
 
     let loader = QueryLoader::new();
@@ -320,19 +499,22 @@ async fn run(cli: Cli) -> Result<(), Box<dyn std::error::Error>> {
             cmd_list(&loader, &cli.queries, detailed)?;
         }
 
-        Commands::Run { query, partition, dry_run, skip_invariants, scratch, scratch_ttl } => {
+        Commands::Run { query, partition, dry_run, skip_invariants, estimate, scratch, scratch_ttl, tracking_dataset } => {
             let project = cli.project.ok_or("Project ID required (--project or GCP_PROJECT_ID)")?;
-            cmd_run(&loader, &cli.queries, &project, query, partition, dry_run, skip_invariants, scratch, scratch_ttl).await?;
+            cmd_run(&loader, &cli.queries, &project, &client_config, query, partition, dry_run, skip_invariants, estimate, scratch, scratch_ttl, &tracking_dataset, cli.state_file.as_ref()).await?;
         }
 
-        Commands::Backfill { query, from, to, dry_run, skip_invariants } => {
+        Commands::Backfill { query, from, to, dry_run, estimate, skip_invariants, concurrency, max_failures, cascade, tracking_dataset } => {
             let project = cli.project.ok_or("Project ID required (--project or GCP_PROJECT_ID)")?;
-            cmd_backfill(&loader, &cli.queries, &project, &query, from, to, dry_run, skip_invariants).await?;
+            let options = BackfillOptions::default()
+                .with_concurrency(concurrency)
+                .with_max_failures(max_failures);
+            cmd_backfill(&loader, &cli.queries, &project, &client_config, &query, from, to, dry_run, estimate, skip_invariants, cascade, &options, &tracking_dataset, cli.state_file.as_ref()).await?;
         }
 
         Commands::Check { query, partition, before, after } => {
             let project = cli.project.ok_or("Project ID required (--project or GCP_PROJECT_ID)")?;
-            cmd_check(&loader, &cli.queries, &project, &query, partition, before, after).await?;
+            cmd_check(&loader, &cli.queries, &project, &client_config, &query, partition, before, after).await?;
         }
 
         Commands::Show { query, version } => {
@@ -340,31 +522,50 @@ async fn run(cli: Cli) -> Result<(), Box<dyn std::error::Error>> {
         }
 
         Commands::Init { dataset } => {
-            let project = cli.project.ok_or("Project ID required (--project or GCP_PROJECT_ID)")?;
-            cmd_init(&project, &dataset).await?;
+            match &cli.state_file {
+                Some(path) => cmd_init_local(path).await?,
+                None => {
+                    let project = cli.project.ok_or("Project ID required (--project or GCP_PROJECT_ID)")?;
+                    cmd_init(&project, &client_config, &dataset).await?;
+                }
+            }
         }
 
-        Commands::Sync { from, to, dry_run, skip_invariants: _, tracking_dataset, allow_source_mutation } => {
+        Commands::Sync { from, to, dry_run, estimate, skip_invariants, tracking_dataset, allow_source_mutation } => {
             let project = if dry_run {
                 cli.project.unwrap_or_default()
             } else {
                 cli.project.ok_or("Project ID required (--project or GCP_PROJECT_ID)")?
             };
-            cmd_sync(&loader, &cli.queries, &project, from, to, dry_run, &tracking_dataset, allow_source_mutation).await?;
+            cmd_sync(&loader, &cli.queries, &project, &client_config, from, to, dry_run, estimate, skip_invariants, &tracking_dataset, cli.state_file.as_ref(), allow_source_mutation).await?;
+        }
+
+        Commands::Audit { query, modified_only, diff, output, tracking_dataset } => {
+            let project = if cli.state_file.is_some() {
+                cli.project.unwrap_or_default()
+            } else {
+                cli.project.ok_or("Project ID required (--project or GCP_PROJECT_ID)")?
+            };
+            cmd_audit(&loader, &cli.queries, &project, &client_config, query, modified_only, diff, output, &tracking_dataset, cli.state_file.as_ref()).await?;
+        }
+
+        Commands::Graph { query, upstream, downstream, date, format } => {
+            cmd_graph(&loader, &cli.queries, query, upstream, downstream, date, format.into())?;
         }
 
-        Commands::Audit { query, modified_only, diff, output, tracking_dataset: _ } => {
-            cmd_audit(&loader, &cli.queries, query, modified_only, diff, output)?;
+        Commands::SchemaCheck { query, output } => {
+            let project = cli.project.ok_or("Project ID required (--project or GCP_PROJECT_ID)")?;
+            cmd_schema_check(&loader, &cli.queries, &project, &client_config, query, output).await?;
         }
 
         Commands::Scratch { action } => {
             match action {
                 ScratchAction::List { project } => {
-                    cmd_scratch_list(&project).await?;
+                    cmd_scratch_list(&project, &client_config).await?;
                 }
                 ScratchAction::Promote { query, partition, scratch_project } => {
                     let project = cli.project.ok_or("Project ID required (--project or GCP_PROJECT_ID)")?;
-                    cmd_scratch_promote(&loader, &cli.queries, &project, &scratch_project, &query, &partition).await?;
+                    cmd_scratch_promote(&loader, &cli.queries, &project, &client_config, &scratch_project, &query, &partition).await?;
                 }
             }
         }
@@ -450,7 +651,7 @@ fn cmd_list(loader: &QueryLoader, queries_path: &PathBuf, detailed: bool) -> Res
     for query in &queries {
         if detailed {
             println!("{}", query.name);
-            println!("  destination: {}.{}", query.destination.dataset, query.destination.table);
+            println!("  destination: {}", query.destination.table_ref());
 
             if let Some(desc) = &query.description {
                 println!("  description: {}", desc);
@@ -473,11 +674,10 @@ fn cmd_list(loader: &QueryLoader, queries_path: &PathBuf, detailed: bool) -> Res
             println!();
         } else {
             let latest = query.latest_version().map(|v| v.version).unwrap_or(0);
-            println!("{:<30} v{:<3} {}.{}",
+            println!("{:<30} v{:<3} {}",
                 query.name,
                 latest,
-                query.destination.dataset,
-                query.destination.table
+                query.destination.table_ref()
             );
         }
     }
@@ -489,12 +689,16 @@ async fn cmd_run(
     loader: &QueryLoader,
     queries_path: &PathBuf,
     project: &str,
+    client_config: &ClientConfig,
     query_name: Option<String>,
     partition: Option<String>,
     dry_run: bool,
     skip_invariants: bool,
+    estimate: bool,
     scratch: Option<String>,
     scratch_ttl: Option<u32>,
+    tracking_dataset: &str,
+    state_file: Option<&PathBuf>,
 ) -> Result<(), Box<dyn std::error::Error>> {
     use bqdrift::executor::{ScratchConfig, ScratchWriter};
 
@@ -522,7 +726,7 @@ async fn cmd_run(
 
             info!("Dry run for partition: {}", partition_key);
             println!("Query: {}", query.name);
-            println!("Destination: {}.{}", query.destination.dataset, query.destination.table);
+            println!("Destination: {}", query.destination.table_ref());
             println!("Partition type: {:?}", partition_type);
 
             let date_for_version = partition_key.to_naive_date();
@@ -566,7 +770,7 @@ async fn cmd_run(
         info!("Running in scratch mode");
         info!("Scratch project: {}", scratch_project);
 
-        let scratch_client = BqClient::new(&scratch_project).await?;
+        let scratch_client = BqClient::connect(&scratch_project, client_config.clone()).await?;
         let mut config = ScratchConfig::new(scratch_project.clone());
         if let Some(ttl) = scratch_ttl {
             config = config.with_ttl(ttl);
@@ -583,6 +787,12 @@ async fn cmd_run(
         println!("  Destination: {}", stats.scratch_table);
         println!("  Partition: {}", stats.partition_key);
         println!("  Expires: {}", stats.expiration.format("%Y-%m-%dT%H:%M:%SZ"));
+        if let Some(rows) = stats.rows_written {
+            println!("  Rows written: {}", rows);
+        }
+        if let Some(job_id) = &stats.job_id {
+            println!("  Job: {}", job_id);
+        }
 
         if !skip_invariants {
             if let Some(report) = &stats.invariant_report {
@@ -607,8 +817,18 @@ async fn cmd_run(
                 None => default_partition_key(partition_type),
             };
 
-            let client = BqClient::new(project).await?;
-            let runner = Runner::new(client, queries);
+            let client = BqClient::connect(project, client_config.clone()).await?;
+
+            if estimate {
+                let plan = Runner::new(client, queries).estimate_query_partition(&name, partition_key).await?;
+                return print_estimate(&plan, client_config.maximum_bytes_billed);
+            }
+
+            let runner = tracked_runner(loader, queries_path, client, queries, tracking_dataset, state_file).await?;
+
+            if let Some(budget) = client_config.maximum_bytes_billed {
+                enforce_budget(&runner.estimate_query_partition(&name, partition_key.clone()).await?, budget)?;
+            }
 
             info!("Running query '{}' for partition {}", name, partition_key);
             let stats = runner.run_query_partition(&name, partition_key).await?;
@@ -620,8 +840,18 @@ async fn cmd_run(
                 None => default_partition_key(&PartitionType::Day),
             };
 
-            let client = BqClient::new(project).await?;
-            let runner = Runner::new(client, queries);
+            let client = BqClient::connect(project, client_config.clone()).await?;
+
+            if estimate {
+                let plan = Runner::new(client, queries).estimate_for_partition(partition_key).await?;
+                return print_estimate(&plan, client_config.maximum_bytes_billed);
+            }
+
+            let runner = tracked_runner(loader, queries_path, client, queries, tracking_dataset, state_file).await?;
+
+            if let Some(budget) = client_config.maximum_bytes_billed {
+                enforce_budget(&runner.estimate_for_partition(partition_key.clone()).await?, budget)?;
+            }
 
             info!("Running all queries for partition {}", partition_key);
             let report = runner.run_for_partition(partition_key).await?;
@@ -634,10 +864,80 @@ async fn cmd_run(
                 eprintln!("\x1b[31m✗\x1b[0m {} ({}): {}", failure.query_name, failure.partition_key, failure.error);
             }
 
-            println!("\n{} succeeded, {} failed", report.stats.len(), report.failures.len());
+            for skip in &report.skipped {
+                eprintln!("\x1b[33m○\x1b[0m {} ({}): skipped, {}", skip.query_name, skip.partition_key, skip.reason);
+            }
+
+            print_cancelled(&report);
+            println!("\n{} succeeded, {} failed, {} skipped{}", report.stats.len(), report.failures.len(), report.skipped.len(), cancelled_summary(&report));
+            check_cancelled(&report)?;
+        }
+    }
+
+    Ok(())
+}
+
+async fn tracked_runner(
+    loader: &QueryLoader,
+    queries_path: &PathBuf,
+    client: BqClient,
+    queries: Vec<bqdrift::QueryDef>,
+    tracking_dataset: &str,
+    state_file: Option<&PathBuf>,
+) -> Result<Runner, Box<dyn std::error::Error>> {
+    let yaml_contents = loader.load_yaml_contents(queries_path)?;
+    let store = state_store(Some(&client), tracking_dataset, state_file)
+        .ok_or("Project ID required (--project or GCP_PROJECT_ID)")?;
+    store.ensure().await?;
+
+    Ok(Runner::new(client, queries).with_state_store(store, yaml_contents))
+}
+
+/// The local state file when `--state-file` is set, otherwise the tracking
+/// dataset in BigQuery. `None` when neither is available.
+fn state_store(
+    client: Option<&BqClient>,
+    tracking_dataset: &str,
+    state_file: Option<&PathBuf>,
+) -> Option<Arc<dyn StateStore>> {
+    match (state_file, client) {
+        (Some(path), _) => Some(Arc::new(LocalStateStore::new(path))),
+        (None, Some(client)) => Some(Arc::new(MigrationTracker::new(client.clone(), tracking_dataset))),
+        (None, None) => None,
+    }
+}
+
+/// Print a dry-run estimate, then fail if it exceeds `--max-bytes-billed`.
+fn print_estimate(plan: &CostEstimate, budget: Option<i64>) -> Result<(), Box<dyn std::error::Error>> {
+    for partition in &plan.partitions {
+        println!("  {} {}: {}", partition.query_name, partition.partition_key, format_bytes(partition.bytes_processed));
+    }
+
+    for failure in &plan.failures {
+        eprintln!("\x1b[31m✗\x1b[0m {} ({}): {}", failure.query_name, failure.partition_key, failure.error);
+    }
+
+    let totals = plan.query_totals();
+    if totals.len() > 1 {
+        println!("\nBy query:");
+        for (query_name, bytes) in &totals {
+            println!("  {}: {}", query_name, format_bytes(*bytes));
         }
     }
 
+    println!("\nEstimated {} processed across {} partitions", format_bytes(plan.total_bytes()), plan.partitions.len());
+    if let Some(budget) = budget {
+        println!("Budget: {}", format_bytes(budget));
+    }
+
+    plan.check_budget(budget)?;
+    Ok(())
+}
+
+/// Refuse to start writing when the dry-run estimate of the plan is over budget.
+fn enforce_budget(plan: &CostEstimate, budget: i64) -> Result<(), Box<dyn std::error::Error>> {
+    plan.check_budget(Some(budget))?;
+    info!("Estimated {} processed, within the {} budget", format_bytes(plan.total_bytes()), format_bytes(budget));
     Ok(())
 }
 
@@ -676,6 +976,14 @@ fn print_scratch_invariants(report: &bqdrift::invariant::InvariantReport) {
 fn print_stats(stats: &PartitionWriteStats, skip_invariants: bool) {
     println!("✓ {} v{} completed for {}", stats.query_name, stats.version, stats.partition_key);
 
+    let summary = stats.job_summary();
+    if !summary.is_empty() {
+        println!("  {}", summary);
+    }
+    if let Some(error) = &stats.tracking_error {
+        println!("  ⚠ state not recorded: {}", error);
+    }
+
     if !skip_invariants {
         if let Some(report) = &stats.invariant_report {
             let mut passed = 0;
@@ -723,11 +1031,17 @@ async fn cmd_backfill(
     loader: &QueryLoader,
     queries_path: &PathBuf,
     project: &str,
+    client_config: &ClientConfig,
     query_name: &str,
     from: String,
     to: String,
     dry_run: bool,
+    estimate: bool,
     skip_invariants: bool,
+    cascade: bool,
+    options: &BackfillOptions,
+    tracking_dataset: &str,
+    state_file: Option<&PathBuf>,
 ) -> Result<(), Box<dyn std::error::Error>> {
     let queries = loader.load_dir(queries_path)?;
 
@@ -739,8 +1053,34 @@ async fn cmd_backfill(
     let from_key = parse_partition_key(&from, partition_type)?;
     let to_key = parse_partition_key(&to, partition_type)?;
 
+    let steps = if cascade {
+        Some(cascade_plan(&queries, query_name, &from_key, &to_key, None)?)
+    } else {
+        None
+    };
+
     info!("Backfilling '{}' from {} to {}", query_name, from_key, to_key);
 
+    if let Some(steps) = &steps {
+        let total: usize = steps.iter().map(|step| step.partitions.len()).sum();
+        println!("Backfilling {} queries, {} partitions:\n", steps.len(), total);
+
+        for step in steps {
+            let query = queries.iter().find(|q| q.name == step.query_name)
+                .ok_or_else(|| format!("Query '{}' not found", step.query_name))?;
+            for key in &step.partitions {
+                if let Some(version) = query.get_version_for_partition(key) {
+                    println!("{} {}: v{} ({})", step.query_name, key, version.version, version.source);
+                }
+            }
+        }
+
+        if dry_run {
+            return Ok(());
+        }
+        println!();
+    }
+
     if dry_run {
         let mut current = from_key.clone();
         while current <= to_key {
@@ -759,10 +1099,54 @@ async fn cmd_backfill(
         info!("Running with invariants skipped");
     }
 
-    let client = BqClient::new(project).await?;
-    let runner = Runner::new(client, queries);
+    let client = BqClient::connect(project, client_config.clone()).await?;
+
+    if estimate {
+        let runner = Runner::new(client, queries);
+        let plan = if cascade {
+            runner.estimate_cascade(query_name, from_key, to_key, None, options).await?
+        } else {
+            runner.estimate_backfill(query_name, from_key, to_key, None, options).await?
+        };
+        return print_estimate(&plan, client_config.maximum_bytes_billed);
+    }
 
-    let report = runner.backfill_partitions(query_name, from_key, to_key, None).await?;
+    let runner = tracked_runner(loader, queries_path, client, queries, tracking_dataset, state_file).await?;
+
+    if let Some(budget) = client_config.maximum_bytes_billed {
+        let plan = if cascade {
+            runner.estimate_cascade(query_name, from_key.clone(), to_key.clone(), None, options).await?
+        } else {
+            runner.estimate_backfill(query_name, from_key.clone(), to_key.clone(), None, options).await?
+        };
+        enforce_budget(&plan, budget)?;
+    }
+
+    if options.concurrency > 1 {
+        info!("Writing up to {} partitions concurrently", options.concurrency);
+    }
+
+    if steps.is_some() {
+        let report = runner.backfill_cascade(query_name, from_key, to_key, None, options).await?;
+
+        for stats in &report.stats {
+            print_stats(stats, skip_invariants);
+        }
+
+        for failure in &report.failures {
+            eprintln!("\x1b[31m✗\x1b[0m {} ({}): {}", failure.query_name, failure.partition_key, failure.error);
+        }
+
+        for skip in &report.skipped {
+            eprintln!("\x1b[33m○\x1b[0m {} ({}): skipped, {}", skip.query_name, skip.partition_key, skip.reason);
+        }
+
+        print_cancelled(&report);
+        println!("\n{} succeeded, {} failed, {} skipped{}", report.stats.len(), report.failures.len(), report.skipped.len(), cancelled_summary(&report));
+        return check_cancelled(&report);
+    }
+
+    let report = runner.backfill_partitions_with(query_name, from_key, to_key, None, options).await?;
 
     for stats in &report.stats {
         print_stats(stats, skip_invariants);
@@ -772,15 +1156,20 @@ async fn cmd_backfill(
         eprintln!("\x1b[31m✗\x1b[0m {}: {}", failure.partition_key, failure.error);
     }
 
-    println!("\n{} succeeded, {} failed", report.stats.len(), report.failures.len());
+    for skip in &report.skipped {
+        eprintln!("\x1b[33m○\x1b[0m {}: skipped, {}", skip.partition_key, skip.reason);
+    }
 
-    Ok(())
+    print_cancelled(&report);
+    println!("\n{} succeeded, {} failed, {} skipped{}", report.stats.len(), report.failures.len(), report.skipped.len(), cancelled_summary(&report));
+    check_cancelled(&report)
 }
 
 async fn cmd_check(
     loader: &QueryLoader,
     queries_path: &PathBuf,
     project: &str,
+    client_config: &ClientConfig,
     query_name: &str,
     partition: Option<String>,
     run_before: bool,
@@ -806,8 +1195,8 @@ async fn cmd_check(
 
     let run_all = !run_before && !run_after;
 
-    let client = BqClient::new(project).await?;
-    let checker = InvariantChecker::new(&client, &query.destination, date_for_version);
+    let client = BqClient::connect(project, client_config.clone()).await?;
+    let checker = InvariantChecker::new(&client, &query.destination, &partition_key);
 
     let mut total_passed = 0;
     let mut total_failed = 0;
@@ -901,7 +1290,7 @@ fn cmd_show(
         .ok_or_else(|| format!("Query '{}' not found", query_name))?;
 
     println!("Name: {}", query.name);
-    println!("Destination: {}.{}", query.destination.dataset, query.destination.table);
+    println!("Destination: {}", query.destination.table_ref());
 
     if let Some(desc) = &query.description {
         println!("Description: {}", desc);
@@ -972,15 +1361,24 @@ fn cmd_show(
     Ok(())
 }
 
-async fn cmd_init(project: &str, dataset: &str) -> Result<(), Box<dyn std::error::Error>> {
+async fn cmd_init(project: &str, client_config: &ClientConfig, dataset: &str) -> Result<(), Box<dyn std::error::Error>> {
     info!("Initializing tracking table in {}.{}", project, dataset);
 
-    let client = BqClient::new(project).await?;
-    let tracker = bqdrift::MigrationTracker::new(client, dataset);
+    let client = BqClient::connect(project, client_config.clone()).await?;
+    let tracker = MigrationTracker::new(client, dataset);
 
     tracker.ensure_tracking_table().await?;
 
-    println!("✓ Tracking table created: {}._bqdrift_query_runs", dataset);
+    println!("✓ Tracking tables created: {}._bqdrift_query_runs, {}._bqdrift_state", dataset, dataset);
+
+    Ok(())
+}
+
+async fn cmd_init_local(path: &PathBuf) -> Result<(), Box<dyn std::error::Error>> {
+    let store = LocalStateStore::new(path);
+    store.ensure().await?;
+
+    println!("✓ State file ready: {}", store.location());
 
     Ok(())
 }
@@ -988,33 +1386,45 @@ async fn cmd_init(project: &str, dataset: &str) -> Result<(), Box<dyn std::error
 async fn cmd_sync(
     loader: &QueryLoader,
     queries_path: &PathBuf,
-    _project: &str,
+    project: &str,
+    client_config: &ClientConfig,
     from: Option<String>,
     to: Option<String>,
     dry_run: bool,
-    _tracking_dataset: &str,
+    estimate: bool,
+    skip_invariants: bool,
+    tracking_dataset: &str,
+    state_file: Option<&PathBuf>,
     allow_source_mutation: bool,
 ) -> Result<(), Box<dyn std::error::Error>> {
     let queries = loader.load_dir(queries_path)?;
     let yaml_contents = loader.load_yaml_contents(queries_path)?;
 
     let today = chrono::Utc::now().date_naive();
-    let from = match from {
-        Some(s) => NaiveDate::parse_from_str(&s, "%Y-%m-%d")
-            .map_err(|_| format!("Invalid date format: '{}'. Expected YYYY-MM-DD", s))?,
-        None => today - chrono::Duration::days(30),
-    };
-    let to = match to {
-        Some(s) => NaiveDate::parse_from_str(&s, "%Y-%m-%d")
-            .map_err(|_| format!("Invalid date format: '{}'. Expected YYYY-MM-DD", s))?,
-        None => today,
-    };
+    let from = from.unwrap_or_else(|| (today - chrono::Duration::days(30)).to_string());
+    let to = to.unwrap_or_else(|| today.to_string());
 
     info!("Detecting drift from {} to {}", from, to);
 
-    // TODO: Fetch stored states from BigQuery tracking table
-    // For now, we pass empty states (no immutability check possible without stored states)
-    let stored_states = vec![];
+    let client = if project.is_empty() {
+        None
+    } else {
+        Some(BqClient::connect(project, client_config.clone()).await?)
+    };
+
+    let stored_states = match state_store(client.as_ref(), tracking_dataset, state_file) {
+        Some(store) => {
+            info!("Reading partition state from {}", store.location());
+            match PartitionKey::date_bounds(&from, &to) {
+                Some((first, last)) => store.get_latest_states_for_date_range(first, last).await?,
+                None => store.get_latest_states().await?,
+            }
+        }
+        None => {
+            warn!("No project set, treating every partition as never run");
+            vec![]
+        }
+    };
 
     if !allow_source_mutation && !stored_states.is_empty() {
         let immutability_checker = ImmutabilityChecker::new(&queries);
@@ -1027,7 +1437,7 @@ async fn cmd_sync(
     }
 
     let detector = DriftDetector::new(queries.clone(), yaml_contents);
-    let report = detector.detect(&stored_states, from, to)?;
+    let report = detector.detect_range(&stored_states, &from, &to)?;
 
     let drifted: Vec<_> = report.needs_rerun();
 
@@ -1079,11 +1489,14 @@ async fn cmd_sync(
                     DriftState::Current => "current",
                 };
 
-                println!("  {} [{}] v{}", partition.partition_key, state_str, partition.current_version);
+                match &partition.caused_by {
+                    Some(upstream) => println!("  {} [{}] v{} (caused by {})", partition.partition_key, state_str, partition.current_version, upstream),
+                    None => println!("  {} [{}] v{}", partition.partition_key, state_str, partition.current_version),
+                }
 
                 if partition.state == DriftState::SqlChanged {
                     if let (Some(executed_b64), Some(current_sql)) = (&partition.executed_sql_b64, &partition.current_sql) {
-                        if let Some(executed_sql) = decode_sql(executed_b64) {
+                        if let Some(executed_sql) = decompress_from_base64(executed_b64) {
                             if has_changes(&executed_sql, current_sql) {
                                 println!();
                                 println!("{}", format_sql_diff(&executed_sql, current_sql));
@@ -1097,11 +1510,64 @@ async fn cmd_sync(
         }
 
         println!("Run without --dry-run to execute {} drifted partitions", drifted.len());
+        return Ok(());
+    }
+
+    if skip_invariants {
+        info!("Running with invariants skipped");
+    }
+
+    let client = client.ok_or("Project ID required (--project or GCP_PROJECT_ID)")?;
+
+    if estimate {
+        println!();
+        let plan = Runner::new(client, queries).estimate_sync(&drifted).await?;
+        return print_estimate(&plan, client_config.maximum_bytes_billed);
+    }
+
+    let runner = tracked_runner(loader, queries_path, client, queries, tracking_dataset, state_file).await?;
+
+    if let Some(budget) = client_config.maximum_bytes_billed {
+        enforce_budget(&runner.estimate_sync(&drifted).await?, budget)?;
+    }
+
+    println!("\nSyncing {} drifted partitions...\n", drifted.len());
+    let sync_report = runner.sync_partitions(&drifted, skip_invariants).await?;
+
+    for stats in &sync_report.stats {
+        print_stats(stats, skip_invariants);
+    }
+
+    for failure in &sync_report.failures {
+        eprintln!("\x1b[31m✗\x1b[0m {} ({}): {}", failure.query_name, failure.partition_key, failure.error);
+    }
+
+    print_cancelled(&sync_report);
+    println!("\n{} succeeded, {} failed{}", sync_report.stats.len(), sync_report.failures.len(), cancelled_summary(&sync_report));
+    check_cancelled(&sync_report)
+}
+
+fn print_cancelled(report: &RunReport) {
+    for cancelled in &report.cancelled {
+        eprintln!("\x1b[33m⊘\x1b[0m {} ({}): {}", cancelled.query_name, cancelled.partition_key, cancelled.error);
+    }
+}
+
+fn cancelled_summary(report: &RunReport) -> String {
+    if report.cancelled.is_empty() {
+        String::new()
     } else {
-        println!("\nSync execution not yet implemented. Use --dry-run to preview changes.");
+        format!(", {} cancelled", report.cancelled.len())
     }
+}
 
-    Ok(())
+/// An interrupted run exits with an error so scripts don't mistake it for a complete one.
+fn check_cancelled(report: &RunReport) -> Result<(), Box<dyn std::error::Error>> {
+    if report.cancelled.is_empty() {
+        Ok(())
+    } else {
+        Err(format!("{} partitions were cancelled", report.cancelled.len()).into())
+    }
 }
 
 fn print_immutability_violations(violations: &[ImmutabilityViolation]) {
@@ -1118,8 +1584,8 @@ fn print_immutability_violations(violations: &[ImmutabilityViolation]) {
         eprintln!("\x1b[1mAffected partitions:\x1b[0m {} partitions", violation.affected_partitions.len());
 
         if violation.affected_partitions.len() <= 5 {
-            for date in &violation.affected_partitions {
-                eprintln!("  - {}", date);
+            for partition in &violation.affected_partitions {
+                eprintln!("  - {}", partition);
             }
         } else {
             let first = violation.affected_partitions.first().unwrap();
@@ -1141,13 +1607,17 @@ fn print_immutability_violations(violations: &[ImmutabilityViolation]) {
     eprintln!();
 }
 
-fn cmd_audit(
+async fn cmd_audit(
     loader: &QueryLoader,
     queries_path: &PathBuf,
+    project: &str,
+    client_config: &ClientConfig,
     query_filter: Option<String>,
     modified_only: bool,
     show_diff: bool,
     output: OutputFormat,
+    tracking_dataset: &str,
+    state_file: Option<&PathBuf>,
 ) -> Result<(), Box<dyn std::error::Error>> {
     let queries = loader.load_dir(queries_path)?;
 
@@ -1166,9 +1636,13 @@ fn cmd_audit(
 
     info!("Auditing {} queries", queries_to_audit.len());
 
-    // TODO: Fetch stored states from BigQuery tracking table
-    // For now, we pass empty states (demonstration mode)
-    let stored_states = vec![];
+    let client = match state_file {
+        Some(_) => None,
+        None => Some(BqClient::connect(project, client_config.clone()).await?),
+    };
+    let store = state_store(client.as_ref(), tracking_dataset, state_file)
+        .ok_or("Project ID required (--project or GCP_PROJECT_ID)")?;
+    let stored_states = store.get_latest_states().await?;
 
     let auditor = SourceAuditor::new(&queries_to_audit);
     let report = auditor.audit(&stored_states);
@@ -1237,10 +1711,104 @@ fn cmd_audit(
     Ok(())
 }
 
-async fn cmd_scratch_list(project: &str) -> Result<(), Box<dyn std::error::Error>> {
+fn cmd_graph(
+    loader: &QueryLoader,
+    queries_path: &PathBuf,
+    query: Option<String>,
+    upstream: bool,
+    downstream: bool,
+    date: Option<chrono::NaiveDate>,
+    format: GraphFormat,
+) -> Result<(), Box<dyn std::error::Error>> {
+    let queries = loader.load_dir(queries_path)?;
+    let mut graph = DependencyGraph::build(&queries, date);
+
+    if let Some(name) = query {
+        // Neither flag means both directions.
+        let both = !upstream && !downstream;
+        graph = graph
+            .focus(&name, upstream || both, downstream || both)
+            .ok_or_else(|| format!("Query '{}' not found", name))?;
+    }
+
+    print!("{}", graph.render(format));
+    Ok(())
+}
+
+async fn cmd_schema_check(
+    loader: &QueryLoader,
+    queries_path: &PathBuf,
+    project: &str,
+    client_config: &ClientConfig,
+    query_filter: Option<String>,
+    output: OutputFormat,
+) -> Result<(), Box<dyn std::error::Error>> {
+    let queries = loader.load_dir(queries_path)?;
+
+    let queries_to_check: Vec<_> = match &query_filter {
+        Some(name) => queries.iter().filter(|q| &q.name == name).cloned().collect(),
+        None => queries,
+    };
+
+    if queries_to_check.is_empty() {
+        if let Some(name) = query_filter {
+            return Err(format!("Query '{}' not found", name).into());
+        }
+        println!("No queries found in {}", queries_path.display());
+        return Ok(());
+    }
+
+    info!("Checking {} destination tables", queries_to_check.len());
+
+    let client = BqClient::connect(project, client_config.clone()).await?;
+    let mut report = SchemaCheckReport::default();
+
+    for query in &queries_to_check {
+        let live = client
+            .clone()
+            .for_destination(&query.destination)
+            .get_table_definition(&query.destination.dataset, &query.destination.table)
+            .await?;
+        report.entries.push(SchemaChecker::check(query, live.as_ref()));
+    }
+
+    match output {
+        OutputFormat::Yaml => {
+            let yaml = serde_yaml::to_string(&report.entries)?;
+            println!("{}", yaml);
+        }
+        OutputFormat::Json => {
+            let json = serde_json::to_string_pretty(&report.entries)?;
+            println!("{}", json);
+        }
+        OutputFormat::Table => {
+            println!("\nSchema Check Report\n");
+
+            let rows: Vec<SchemaCheckTableRow> = report.entries.iter().flat_map(SchemaCheckTableRow::rows).collect();
+            let mut table = Table::new(rows);
+            table.with(Style::markdown());
+            println!("{}", table);
+
+            println!("\nSummary:");
+            println!("  ✓ {} in sync", report.in_sync_tables());
+            println!("  ✗ {} mismatched", report.mismatched_tables());
+        }
+    }
+
+    if report.has_mismatches() {
+        return Err(format!(
+            "{} destination table(s) differ from their definitions",
+            report.mismatched_tables()
+        ).into());
+    }
+
+    Ok(())
+}
+
+async fn cmd_scratch_list(project: &str, client_config: &ClientConfig) -> Result<(), Box<dyn std::error::Error>> {
     use bqdrift::executor::{ScratchConfig, ScratchWriter};
 
-    let client = BqClient::new(project).await?;
+    let client = BqClient::connect(project, client_config.clone()).await?;
     let config = ScratchConfig::new(project.to_string());
     let writer = ScratchWriter::new(client, config);
 
@@ -1262,6 +1830,7 @@ async fn cmd_scratch_promote(
     loader: &QueryLoader,
     queries_path: &PathBuf,
     production_project: &str,
+    client_config: &ClientConfig,
     scratch_project: &str,
     query_name: &str,
     partition_str: &str,
@@ -1281,8 +1850,8 @@ async fn cmd_scratch_promote(
     info!("  Scratch project: {}", scratch_project);
     info!("  Production project: {}", production_project);
 
-    let scratch_client = BqClient::new(scratch_project).await?;
-    let production_client = BqClient::new(production_project).await?;
+    let scratch_client = BqClient::connect(scratch_project, client_config.clone()).await?;
+    let production_client = BqClient::connect(production_project, client_config.clone()).await?;
 
     let config = ScratchConfig::new(scratch_project.to_string());
     let scratch_writer = ScratchWriter::new(scratch_client, config);
@@ -1300,18 +1869,29 @@ async fn cmd_scratch_promote(
 async fn run_repl(cli: Cli) -> Result<(), Box<dyn std::error::Error>> {
     use bqdrift::repl::{ReplSession, InteractiveRepl, AsyncJsonRpcServer, ServerConfig};
 
+    let client_config = client_config(&cli);
     let is_tty = atty::is(atty::Stream::Stdin);
     let force_server = cli.server;
 
     if is_tty && !force_server {
-        let session = ReplSession::new(cli.project, cli.queries);
+        let session = ReplSession::new(cli.project, cli.queries)
+            .with_state_file(cli.state_file)
+            .with_client_config(client_config);
         let mut repl = InteractiveRepl::new(session)?;
         repl.run().await?;
     } else {
+        let profiles = match &cli.credential_profiles {
+            Some(path) => load_credential_profiles(path)?,
+            None => Default::default(),
+        };
+
         let config = ServerConfig::new(cli.project, cli.queries)
             .with_max_sessions(cli.max_sessions)
             .with_idle_timeout(cli.idle_timeout)
-            .with_max_idle_timeout(cli.max_idle_timeout);
+            .with_max_idle_timeout(cli.max_idle_timeout)
+            .with_state_file(cli.state_file)
+            .with_client_config(client_config)
+            .with_credential_profiles(profiles);
         AsyncJsonRpcServer::run(config).await?;
     }
 
@@ -321,8 +321,8 @@ impl<'a> SourceAuditor<'a> {
 #[cfg(test)]
 mod tests {
     use super::*;
-    use crate::dsl::{VersionDef, Destination, ResolvedRevision};
-    use crate::schema::{Schema, PartitionConfig};
+    use crate::dsl::{VersionDef, Destination, ResolvedRevision, WriteStrategy};
+    use crate::schema::{Schema, PartitionConfig, PartitionKey};
     use crate::invariant::InvariantsDef;
     use crate::drift::checksum::compress_to_base64;
     use crate::drift::state::ExecutionStatus;
@@ -333,10 +333,13 @@ mod tests {
         QueryDef {
             name: name.to_string(),
             destination: Destination {
+                project: None,
                 dataset: "test_dataset".to_string(),
                 table: "test_table".to_string(),
                 partition: PartitionConfig::day("date"),
                 cluster: None,
+                write_strategy: WriteStrategy::Merge,
+                location: None,
             },
             description: None,
             owner: None,
@@ -358,6 +361,7 @@ mod tests {
             schema: Schema::default(),
             dependencies: HashSet::new(),
             invariants: InvariantsDef::default(),
+            job: Default::default(),
         }
     }
 
@@ -381,6 +385,7 @@ mod tests {
             schema: Schema::default(),
             dependencies: HashSet::new(),
             invariants: InvariantsDef::default(),
+            job: Default::default(),
         }
     }
 
@@ -393,6 +398,7 @@ mod tests {
     ) -> PartitionState {
         PartitionState {
             query_name: query_name.to_string(),
+            partition_key: PartitionKey::Day(partition_date),
             partition_date,
             version,
             sql_revision: revision,
@@ -401,11 +407,16 @@ mod tests {
             schema_checksum: "schema".to_string(),
             yaml_checksum: "yaml".to_string(),
             executed_sql_b64: Some(compress_to_base64(executed_sql)),
+            executed_yaml_b64: None,
             upstream_states: HashMap::new(),
             executed_at: Utc::now(),
             execution_time_ms: Some(100),
             rows_written: Some(1000),
             bytes_processed: Some(10000),
+            bytes_billed: None,
+            slot_ms: None,
+            job_id: None,
+            write_strategy: None,
             status: ExecutionStatus::Success,
         }
     }
@@ -1,10 +1,10 @@
-use chrono::NaiveDate;
+use chrono::{DateTime, NaiveDate, Utc};
 use std::collections::HashMap;
-use crate::error::Result;
-use crate::dsl::QueryDef;
-use crate::schema::PartitionKey;
+use crate::error::{BqDriftError, Result};
+use crate::dsl::{QueryDef, VersionDef};
+use crate::schema::{PartitionConfig, PartitionKey, PartitionType};
 use super::checksum::Checksums;
-use super::state::{PartitionState, DriftState, DriftReport, PartitionDrift};
+use super::state::{PartitionState, DriftState, DriftReport, PartitionDrift, ExecutionStatus};
 
 pub struct DriftDetector {
     queries: HashMap<String, QueryDef>,
@@ -22,41 +22,86 @@ impl DriftDetector {
         stored_states: &[PartitionState],
         from: NaiveDate,
         to: NaiveDate,
+    ) -> Result<DriftReport> {
+        self.detect_range(stored_states, &from.to_string(), &to.to_string())
+    }
+
+    /// Detect drift for every partition between `from` and `to`, walking each
+    /// query's own partition type. Bounds accept any partition format; RANGE
+    /// queries fall back to their configured `start`/`end` when the bounds are
+    /// not integers, and are skipped when neither is available.
+    pub fn detect_range(
+        &self,
+        stored_states: &[PartitionState],
+        from: &str,
+        to: &str,
     ) -> Result<DriftReport> {
         let mut report = DriftReport::new();
 
-        let stored_map: HashMap<(String, NaiveDate), &PartitionState> = stored_states
+        let stored_map: HashMap<(String, PartitionKey), &PartitionState> = stored_states
             .iter()
-            .map(|s| ((s.query_name.clone(), s.partition_date), s))
+            .map(|s| ((s.query_name.clone(), s.partition_key.clone()), s))
             .collect();
+        let latest_success = Self::latest_successful_runs(stored_states);
 
         for (query_name, query) in &self.queries {
             let yaml_content = self.yaml_contents.get(query_name).map(|s| s.as_str()).unwrap_or("");
 
-            let mut current = from;
-            while current <= to {
+            let Some((first, last)) = Self::partition_bounds(&query.destination.partition, from, to)? else {
+                continue;
+            };
+            let interval = query.destination.partition.interval.unwrap_or(1).max(1);
+
+            let mut current = first;
+            while current <= last {
                 let drift = self.detect_partition(
                     query,
-                    current,
-                    stored_map.get(&(query_name.clone(), current)),
+                    &current,
+                    stored_map.get(&(query_name.clone(), current.clone())),
                     yaml_content,
+                    &latest_success,
                 );
                 report.add(drift);
-                current = current.succ_opt().unwrap_or(current);
+
+                let next = current.next_by(interval);
+                if next == current {
+                    break;
+                }
+                current = next;
             }
         }
 
         Ok(report)
     }
 
+    fn partition_bounds(
+        partition: &PartitionConfig,
+        from: &str,
+        to: &str,
+    ) -> Result<Option<(PartitionKey, PartitionKey)>> {
+        let partition_type = &partition.partition_type;
+        let first = PartitionKey::parse_bound(from, partition_type, false);
+        let last = PartitionKey::parse_bound(to, partition_type, true);
+
+        match (first, last) {
+            (Ok(first), Ok(last)) => Ok(Some((first, last))),
+            _ if *partition_type == PartitionType::Range => Ok(partition
+                .start
+                .zip(partition.end)
+                .map(|(start, end)| (PartitionKey::Range(start), PartitionKey::Range(end - 1)))),
+            (Err(e), _) | (_, Err(e)) => Err(BqDriftError::Partition(e)),
+        }
+    }
+
     fn detect_partition(
         &self,
         query: &QueryDef,
-        partition_date: NaiveDate,
+        partition_key: &PartitionKey,
         stored: Option<&&PartitionState>,
         yaml_content: &str,
+        latest_success: &HashMap<String, Vec<(PartitionKey, DateTime<Utc>)>>,
     ) -> PartitionDrift {
-        let version = query.get_version_for_date(partition_date);
+        let version = query.get_version_for_partition(partition_key);
         let current_sql = version.map(|v| v.get_sql_for_date(chrono::Utc::now().date_naive()).to_string());
 
         let (state, executed_version, caused_by, executed_sql_b64) = match (version, stored) {
@@ -65,7 +110,7 @@ impl DriftDetector {
             (Some(_), None) => (DriftState::NeverRun, None, None, None),
 
             (Some(v), Some(stored)) => {
-                if stored.status == super::state::ExecutionStatus::Failed {
+                if stored.status != super::state::ExecutionStatus::Success {
                     (DriftState::Failed, Some(stored.version), None, stored.executed_sql_b64.clone())
                 } else {
                     let current_checksums = Checksums::from_version(
@@ -80,8 +125,9 @@ impl DriftDetector {
                         (DriftState::SqlChanged, Some(stored.version), None, stored.executed_sql_b64.clone())
                     } else if v.version != stored.version {
                         (DriftState::VersionUpgraded, Some(stored.version), None, stored.executed_sql_b64.clone())
+                    } else if let Some(upstream) = self.upstream_change(query, v, stored, latest_success) {
+                        (DriftState::UpstreamChanged, Some(stored.version), Some(upstream), stored.executed_sql_b64.clone())
                     } else {
-                        // TODO: Check upstream_changed
                         (DriftState::Current, Some(stored.version), None, stored.executed_sql_b64.clone())
                     }
                 }
@@ -90,7 +136,7 @@ impl DriftDetector {
 
         PartitionDrift {
             query_name: query.name.clone(),
-            partition_key: PartitionKey::Day(partition_date),
+            partition_key: partition_key.clone(),
             state,
             current_version: version.map(|v| v.version).unwrap_or(0),
             executed_version,
@@ -104,33 +150,77 @@ impl DriftDetector {
     /// Returns the name of the upstream query that changed, if any
     pub fn detect_upstream_changed(
         &self,
-        _query: &QueryDef,
+        query: &QueryDef,
         stored: &PartitionState,
         all_states: &[PartitionState],
     ) -> Option<String> {
-        // Check each upstream dependency recorded in the state
-        for (upstream_name, recorded_time) in &stored.upstream_states {
-            // Find the latest execution of the upstream query for this partition date
-            let upstream_latest = all_states
+        let version = query.get_version_for_partition(&stored.partition_key)?;
+        let latest_success = Self::latest_successful_runs(all_states);
+        self.upstream_change(query, version, stored, &latest_success)
+    }
+
+    /// Compares the upstream execution times recorded with `stored` against the
+    /// latest successful runs of the managed queries this version reads from.
+    /// Only upstream partitions overlapping the stored one are considered, so
+    /// rerunning one upstream hour does not flag every downstream hour of the
+    /// day. Upstreams missing from the recorded map count as changed when they
+    /// ran after the partition itself.
+    fn upstream_change(
+        &self,
+        query: &QueryDef,
+        version: &VersionDef,
+        stored: &PartitionState,
+        latest_success: &HashMap<String, Vec<(PartitionKey, DateTime<Utc>)>>,
+    ) -> Option<String> {
+        let dependencies = version
+            .get_revision_for_date(chrono::Utc::now().date_naive())
+            .map(|r| &r.dependencies)
+            .unwrap_or(&version.dependencies);
+
+        let mut upstream_names: Vec<&str> = self.queries
+            .values()
+            .filter(|q| q.name != query.name)
+            .filter(|q| dependencies.iter().any(|dep| q.produces(dep)))
+            .map(|q| q.name.as_str())
+            .collect();
+        upstream_names.sort();
+
+        upstream_names.into_iter().find_map(|upstream_name| {
+            let latest = latest_success
+                .get(upstream_name)?
                 .iter()
-                .filter(|s| &s.query_name == upstream_name && s.partition_date == stored.partition_date)
-                .max_by_key(|s| s.executed_at);
+                .filter(|(key, _)| key.overlaps(&stored.partition_key))
+                .map(|(_, executed_at)| executed_at)
+                .max()?;
+            let seen = stored.upstream_states.get(upstream_name).unwrap_or(&stored.executed_at);
+            (latest > seen).then(|| upstream_name.to_string())
+        })
+    }
 
-            if let Some(upstream) = upstream_latest {
-                // If upstream ran after we recorded it, we're stale
-                if upstream.executed_at > *recorded_time {
-                    return Some(upstream_name.clone());
-                }
+    /// Latest successful run of each partition, grouped by query.
+    fn latest_successful_runs(states: &[PartitionState]) -> HashMap<String, Vec<(PartitionKey, DateTime<Utc>)>> {
+        let mut by_partition: HashMap<(&str, &PartitionKey), DateTime<Utc>> = HashMap::new();
+        for state in states.iter().filter(|s| s.status == ExecutionStatus::Success) {
+            let entry = by_partition
+                .entry((state.query_name.as_str(), &state.partition_key))
+                .or_insert(state.executed_at);
+            if state.executed_at > *entry {
+                *entry = state.executed_at;
             }
         }
-        None
+
+        let mut latest: HashMap<String, Vec<(PartitionKey, DateTime<Utc>)>> = HashMap::new();
+        for ((query_name, key), executed_at) in by_partition {
+            latest.entry(query_name.to_string()).or_default().push((key.clone(), executed_at));
+        }
+        latest
     }
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
-    use crate::dsl::{VersionDef, Destination};
+    use crate::dsl::{VersionDef, Destination, WriteStrategy};
     use crate::schema::{Schema, PartitionConfig};
     use crate::drift::checksum::{Checksums, compress_to_base64};
     use crate::invariant::InvariantsDef;
@@ -141,10 +231,13 @@ mod tests {
         QueryDef {
             name: name.to_string(),
             destination: Destination {
+                project: None,
                 dataset: "test_dataset".to_string(),
                 table: "test_table".to_string(),
                 partition: PartitionConfig::day("date"),
                 cluster: None,
+                write_strategy: WriteStrategy::Merge,
+                location: None,
             },
             description: None,
             owner: None,
@@ -160,6 +253,7 @@ mod tests {
                 schema: Schema::default(),
                 dependencies: HashSet::new(),
                 invariants: InvariantsDef::default(),
+                job: Default::default(),
             }],
             cluster: None,
         }
@@ -174,6 +268,7 @@ mod tests {
         let checksums = Checksums::compute(sql_content, &Schema::default(), yaml_content);
         PartitionState {
             query_name: query_name.to_string(),
+            partition_key: PartitionKey::Day(partition_date),
             partition_date,
             version: 1,
             sql_revision: None,
@@ -182,11 +277,16 @@ mod tests {
             schema_checksum: checksums.schema,
             yaml_checksum: checksums.yaml,
             executed_sql_b64: Some(compress_to_base64(sql_content)),
+            executed_yaml_b64: None,
             upstream_states: HashMap::new(),
             executed_at: Utc::now(),
             execution_time_ms: Some(100),
             rows_written: Some(1000),
             bytes_processed: Some(10000),
+            bytes_billed: None,
+            slot_ms: None,
+            job_id: None,
+            write_strategy: None,
             status: super::super::state::ExecutionStatus::Success,
         }
     }
@@ -337,4 +437,215 @@ mod tests {
             assert!(drift.current_sql.is_some());
         }
     }
+
+    fn upstream_and_downstream() -> (QueryDef, QueryDef, HashMap<String, String>) {
+        let mut upstream = create_test_query("upstream", "SELECT * FROM raw.events");
+        upstream.destination.table = "upstream_table".to_string();
+
+        let mut downstream = create_test_query("downstream", "SELECT * FROM test_dataset.upstream_table");
+        downstream.versions[0].dependencies = HashSet::from(["test_dataset.upstream_table".to_string()]);
+
+        let yaml_contents = HashMap::from([
+            ("upstream".to_string(), "name: upstream".to_string()),
+            ("downstream".to_string(), "name: downstream".to_string()),
+        ]);
+
+        (upstream, downstream, yaml_contents)
+    }
+
+    #[test]
+    fn test_detect_upstream_changed_after_recorded_run() {
+        let (upstream, downstream, yaml_contents) = upstream_and_downstream();
+        let date = NaiveDate::from_ymd_opt(2024, 1, 15).unwrap();
+        let recorded_at = Utc::now() - chrono::Duration::hours(2);
+
+        let mut upstream_state = create_stored_state("upstream", date, "SELECT * FROM raw.events", "name: upstream");
+        upstream_state.executed_at = Utc::now();
+
+        let mut downstream_state = create_stored_state(
+            "downstream", date, "SELECT * FROM test_dataset.upstream_table", "name: downstream",
+        );
+        downstream_state.executed_at = recorded_at + chrono::Duration::minutes(5);
+        downstream_state.upstream_states = HashMap::from([("upstream".to_string(), recorded_at)]);
+
+        let detector = DriftDetector::new(vec![upstream, downstream], yaml_contents);
+        let report = detector.detect(&[upstream_state, downstream_state], date, date).unwrap();
+
+        let drift = report.partitions.iter().find(|p| p.query_name == "downstream").unwrap();
+        assert_eq!(drift.state, DriftState::UpstreamChanged);
+        assert_eq!(drift.caused_by.as_deref(), Some("upstream"));
+
+        let upstream_drift = report.partitions.iter().find(|p| p.query_name == "upstream").unwrap();
+        assert_eq!(upstream_drift.state, DriftState::Current);
+    }
+
+    #[test]
+    fn test_detect_upstream_unchanged_is_current() {
+        let (upstream, downstream, yaml_contents) = upstream_and_downstream();
+        let date = NaiveDate::from_ymd_opt(2024, 1, 15).unwrap();
+        let upstream_ran_at = Utc::now() - chrono::Duration::hours(2);
+
+        let mut upstream_state = create_stored_state("upstream", date, "SELECT * FROM raw.events", "name: upstream");
+        upstream_state.executed_at = upstream_ran_at;
+
+        let mut downstream_state = create_stored_state(
+            "downstream", date, "SELECT * FROM test_dataset.upstream_table", "name: downstream",
+        );
+        downstream_state.upstream_states = HashMap::from([("upstream".to_string(), upstream_ran_at)]);
+
+        let detector = DriftDetector::new(vec![upstream, downstream], yaml_contents);
+        let report = detector.detect(&[upstream_state, downstream_state], date, date).unwrap();
+
+        let drift = report.partitions.iter().find(|p| p.query_name == "downstream").unwrap();
+        assert_eq!(drift.state, DriftState::Current);
+        assert!(drift.caused_by.is_none());
+    }
+
+    #[test]
+    fn test_detect_upstream_ignores_failed_upstream_runs() {
+        let (upstream, downstream, yaml_contents) = upstream_and_downstream();
+        let date = NaiveDate::from_ymd_opt(2024, 1, 15).unwrap();
+
+        let downstream_state = create_stored_state(
+            "downstream", date, "SELECT * FROM test_dataset.upstream_table", "name: downstream",
+        );
+
+        let mut upstream_state = create_stored_state("upstream", date, "SELECT * FROM raw.events", "name: upstream");
+        upstream_state.executed_at = downstream_state.executed_at + chrono::Duration::minutes(10);
+        upstream_state.status = super::super::state::ExecutionStatus::Failed;
+
+        let detector = DriftDetector::new(vec![upstream.clone(), downstream.clone()], yaml_contents);
+        assert!(detector
+            .detect_upstream_changed(&downstream, &downstream_state, &[upstream_state])
+            .is_none());
+    }
+
+    #[test]
+    fn test_detect_hourly_partitions() {
+        let sql = "SELECT * FROM source";
+        let yaml = "name: test_query";
+        let mut query = create_test_query("test_query", sql);
+        query.destination.partition = PartitionConfig::hour("ts");
+        let yaml_contents = HashMap::from([("test_query".to_string(), yaml.to_string())]);
+        let detector = DriftDetector::new(vec![query], yaml_contents);
+
+        let hour = PartitionKey::parse("2024-01-15T10", &PartitionType::Hour).unwrap();
+        let mut stored = create_stored_state("test_query", hour.to_naive_date(), sql, yaml);
+        stored.partition_key = hour.clone();
+
+        let report = detector.detect_range(&[stored], "2024-01-15", "2024-01-15").unwrap();
+
+        assert_eq!(report.partitions.len(), 24);
+        for drift in &report.partitions {
+            let expected = if drift.partition_key == hour { DriftState::Current } else { DriftState::NeverRun };
+            assert_eq!(drift.state, expected);
+        }
+    }
+
+    #[test]
+    fn test_detect_monthly_partitions() {
+        let mut query = create_test_query("test_query", "SELECT * FROM source");
+        query.destination.partition = PartitionConfig::month("month");
+        let detector = DriftDetector::new(vec![query], HashMap::new());
+
+        let report = detector.detect_range(&[], "2024-01-15", "2024-03-01").unwrap();
+
+        let keys: Vec<String> = report.partitions.iter().map(|p| p.partition_key.to_string()).collect();
+        assert_eq!(keys, vec!["2024-01", "2024-02", "2024-03"]);
+    }
+
+    #[test]
+    fn test_detect_range_partitions_use_configured_bounds() {
+        let mut query = create_test_query("test_query", "SELECT * FROM source");
+        query.destination.partition = PartitionConfig::range("bucket", 0, 30, 10);
+        let detector = DriftDetector::new(vec![query], HashMap::new());
+
+        let report = detector.detect_range(&[], "2024-01-01", "2024-01-31").unwrap();
+        let keys: Vec<&PartitionKey> = report.partitions.iter().map(|p| &p.partition_key).collect();
+        assert_eq!(keys, vec![&PartitionKey::Range(0), &PartitionKey::Range(10), &PartitionKey::Range(20)]);
+
+        let report = detector.detect_range(&[], "10", "10").unwrap();
+        assert_eq!(report.partitions.len(), 1);
+        assert_eq!(report.partitions[0].partition_key, PartitionKey::Range(10));
+    }
+
+    fn upstream_change_states(
+        upstream_keys: [(&PartitionKey, DateTime<Utc>); 2],
+        downstream_keys: [&PartitionKey; 2],
+        recorded_at: DateTime<Utc>,
+    ) -> Vec<PartitionState> {
+        let mut states = Vec::new();
+        for (key, executed_at) in upstream_keys {
+            let mut state = create_stored_state("upstream", key.to_naive_date(), "SELECT * FROM raw.events", "name: upstream");
+            state.partition_key = key.clone();
+            state.executed_at = executed_at;
+            states.push(state);
+        }
+        for key in downstream_keys {
+            let mut state = create_stored_state(
+                "downstream", key.to_naive_date(), "SELECT * FROM test_dataset.upstream_table", "name: downstream",
+            );
+            state.partition_key = key.clone();
+            state.executed_at = recorded_at + chrono::Duration::minutes(5);
+            state.upstream_states = HashMap::from([("upstream".to_string(), recorded_at)]);
+            states.push(state);
+        }
+        states
+    }
+
+    #[test]
+    fn test_detect_upstream_changed_only_in_overlapping_hour() {
+        let (mut upstream, mut downstream, yaml_contents) = upstream_and_downstream();
+        upstream.destination.partition = PartitionConfig::hour("ts");
+        downstream.destination.partition = PartitionConfig::hour("ts");
+
+        let ten = PartitionKey::parse("2024-01-15T10", &PartitionType::Hour).unwrap();
+        let eleven = PartitionKey::parse("2024-01-15T11", &PartitionType::Hour).unwrap();
+        let recorded_at = Utc::now() - chrono::Duration::hours(2);
+        let states = upstream_change_states([(&ten, Utc::now()), (&eleven, recorded_at)], [&ten, &eleven], recorded_at);
+
+        let detector = DriftDetector::new(vec![upstream, downstream], yaml_contents);
+        let report = detector.detect_range(&states, "2024-01-15", "2024-01-15").unwrap();
+
+        let state_of = |key: &PartitionKey| {
+            report.partitions.iter()
+                .find(|p| p.query_name == "downstream" && &p.partition_key == key)
+                .map(|p| p.state)
+                .unwrap()
+        };
+        assert_eq!(state_of(&ten), DriftState::UpstreamChanged);
+        assert_eq!(state_of(&eleven), DriftState::Current);
+    }
+
+    #[test]
+    fn test_detect_upstream_changed_only_in_same_range_bucket() {
+        let (mut upstream, mut downstream, yaml_contents) = upstream_and_downstream();
+        upstream.destination.partition = PartitionConfig::range("bucket", 0, 30, 10);
+        downstream.destination.partition = PartitionConfig::range("bucket", 0, 30, 10);
+
+        let zero = PartitionKey::Range(0);
+        let ten = PartitionKey::Range(10);
+        let recorded_at = Utc::now() - chrono::Duration::hours(2);
+        let states = upstream_change_states([(&zero, Utc::now()), (&ten, recorded_at)], [&zero, &ten], recorded_at);
+
+        let detector = DriftDetector::new(vec![upstream, downstream], yaml_contents);
+        let report = detector.detect_range(&states, "0", "10").unwrap();
+
+        let state_of = |key: &PartitionKey| {
+            report.partitions.iter()
+                .find(|p| p.query_name == "downstream" && &p.partition_key == key)
+                .map(|p| p.state)
+                .unwrap()
+        };
+        assert_eq!(state_of(&zero), DriftState::UpstreamChanged);
+        assert_eq!(state_of(&ten), DriftState::Current);
+    }
+
+    #[test]
+    fn test_detect_invalid_bound_is_error() {
+        let query = create_test_query("test_query", "SELECT * FROM source");
+        let detector = DriftDetector::new(vec![query], HashMap::new());
+
+        assert!(detector.detect_range(&[], "last week", "2024-01-31").is_err());
+    }
 }
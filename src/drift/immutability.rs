@@ -1,5 +1,6 @@
 use std::collections::HashMap;
 use crate::dsl::QueryDef;
+use crate::schema::PartitionKey;
 use super::state::PartitionState;
 use super::checksum::decompress_from_base64;
 
@@ -9,7 +10,7 @@ pub struct ImmutabilityViolation {
     pub version: u32,
     pub revision: Option<u32>,
     pub source: String,
-    pub affected_partitions: Vec<chrono::NaiveDate>,
+    pub affected_partitions: Vec<PartitionKey>,
     pub stored_sql: String,
     pub current_sql: String,
 }
@@ -134,10 +135,11 @@ impl<'a> ImmutabilityChecker<'a> {
             };
 
             if stored_sql != current_sql {
-                let affected_partitions: Vec<_> = version_states
+                let mut affected_partitions: Vec<_> = version_states
                     .iter()
-                    .map(|s| s.partition_date)
+                    .map(|s| s.partition_key.clone())
                     .collect();
+                affected_partitions.sort();
 
                 violations.push(ImmutabilityViolation {
                     query_name: query.name.clone(),
@@ -158,7 +160,7 @@ impl<'a> ImmutabilityChecker<'a> {
 #[cfg(test)]
 mod tests {
     use super::*;
-    use crate::dsl::{VersionDef, Destination, ResolvedRevision};
+    use crate::dsl::{VersionDef, Destination, ResolvedRevision, WriteStrategy};
     use crate::schema::{Schema, PartitionConfig};
     use crate::invariant::InvariantsDef;
     use crate::drift::checksum::compress_to_base64;
@@ -170,10 +172,13 @@ mod tests {
         QueryDef {
             name: name.to_string(),
             destination: Destination {
+                project: None,
                 dataset: "test_dataset".to_string(),
                 table: "test_table".to_string(),
                 partition: PartitionConfig::day("date"),
                 cluster: None,
+                write_strategy: WriteStrategy::Merge,
+                location: None,
             },
             description: None,
             owner: None,
@@ -195,6 +200,7 @@ mod tests {
             schema: Schema::default(),
             dependencies: HashSet::new(),
             invariants: InvariantsDef::default(),
+            job: Default::default(),
         }
     }
 
@@ -218,6 +224,7 @@ mod tests {
             schema: Schema::default(),
             dependencies: HashSet::new(),
             invariants: InvariantsDef::default(),
+            job: Default::default(),
         }
     }
 
@@ -230,6 +237,7 @@ mod tests {
     ) -> PartitionState {
         PartitionState {
             query_name: query_name.to_string(),
+            partition_key: PartitionKey::Day(partition_date),
             partition_date,
             version,
             sql_revision: revision,
@@ -238,11 +246,16 @@ mod tests {
             schema_checksum: "schema".to_string(),
             yaml_checksum: "yaml".to_string(),
             executed_sql_b64: Some(compress_to_base64(executed_sql)),
+            executed_yaml_b64: None,
             upstream_states: HashMap::new(),
             executed_at: Utc::now(),
             execution_time_ms: Some(100),
             rows_written: Some(1000),
             bytes_processed: Some(10000),
+            bytes_billed: None,
+            slot_ms: None,
+            job_id: None,
+            write_strategy: None,
             status: ExecutionStatus::Success,
         }
     }
@@ -3,9 +3,11 @@ mod state;
 mod detector;
 mod immutability;
 mod audit;
+mod schema_check;
 
 pub use checksum::{Checksums, ExecutionArtifact, compress_to_base64, decompress_from_base64};
 pub use state::{PartitionState, PartitionDrift, DriftState, DriftReport, ExecutionStatus};
 pub use detector::DriftDetector;
 pub use immutability::{ImmutabilityChecker, ImmutabilityReport, ImmutabilityViolation};
 pub use audit::{SourceAuditor, SourceAuditReport, SourceAuditEntry, SourceStatus, AuditTableRow};
+pub use schema_check::{SchemaChecker, SchemaCheckReport, SchemaCheckEntry, SchemaMismatch, SchemaCheckTableRow};
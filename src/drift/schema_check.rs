@@ -0,0 +1,364 @@
+use serde::Serialize;
+use tabled::Tabled;
+use crate::dsl::QueryDef;
+use crate::schema::{column_type, BqType, Field, FieldMode, PartitionConfig, PartitionType, TableDefinition};
+
+/// One way a live table differs from the definition bqdrift expects.
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct SchemaMismatch {
+    /// What differs, e.g. `field payload.id`, `partitioning` or `clustering`.
+    pub subject: String,
+    pub expected: String,
+    pub actual: String,
+}
+
+/// The result of comparing one query's destination table with the latest version.
+#[derive(Debug, Clone, Serialize)]
+pub struct SchemaCheckEntry {
+    pub query_name: String,
+    pub table: String,
+    pub version: u32,
+    pub mismatches: Vec<SchemaMismatch>,
+}
+
+impl SchemaCheckEntry {
+    pub fn is_in_sync(&self) -> bool {
+        self.mismatches.is_empty()
+    }
+}
+
+#[derive(Debug, Clone, Default, Serialize)]
+pub struct SchemaCheckReport {
+    pub entries: Vec<SchemaCheckEntry>,
+}
+
+impl SchemaCheckReport {
+    pub fn has_mismatches(&self) -> bool {
+        self.entries.iter().any(|e| !e.is_in_sync())
+    }
+
+    pub fn mismatched_tables(&self) -> usize {
+        self.entries.iter().filter(|e| !e.is_in_sync()).count()
+    }
+
+    pub fn in_sync_tables(&self) -> usize {
+        self.entries.iter().filter(|e| e.is_in_sync()).count()
+    }
+}
+
+#[derive(Debug, Clone, Tabled)]
+pub struct SchemaCheckTableRow {
+    #[tabled(rename = "Query")]
+    pub query: String,
+    #[tabled(rename = "Table")]
+    pub table: String,
+    #[tabled(rename = "Subject")]
+    pub subject: String,
+    #[tabled(rename = "Expected")]
+    pub expected: String,
+    #[tabled(rename = "Actual")]
+    pub actual: String,
+}
+
+impl SchemaCheckTableRow {
+    /// One row per mismatch, or a single row for a table that is in sync.
+    pub fn rows(entry: &SchemaCheckEntry) -> Vec<Self> {
+        if entry.is_in_sync() {
+            return vec![Self {
+                query: entry.query_name.clone(),
+                table: entry.table.clone(),
+                subject: "-".to_string(),
+                expected: format!("✓ in sync with v{}", entry.version),
+                actual: "-".to_string(),
+            }];
+        }
+
+        entry.mismatches.iter().map(|m| Self {
+            query: entry.query_name.clone(),
+            table: entry.table.clone(),
+            subject: m.subject.clone(),
+            expected: m.expected.clone(),
+            actual: m.actual.clone(),
+        }).collect()
+    }
+}
+
+/// Compares destination tables as BigQuery reports them with the schema,
+/// partitioning and clustering of each query's latest version.
+pub struct SchemaChecker;
+
+impl SchemaChecker {
+    /// Compare `query`'s destination with `live`, which is `None` when the
+    /// table doesn't exist.
+    pub fn check(query: &QueryDef, live: Option<&TableDefinition>) -> SchemaCheckEntry {
+        let table = query.destination.table_ref();
+        let mut mismatches = Vec::new();
+
+        let version = match query.latest_version() {
+            Some(latest) => {
+                match live {
+                    None => mismatches.push(SchemaMismatch {
+                        subject: "table".to_string(),
+                        expected: "exists".to_string(),
+                        actual: "missing".to_string(),
+                    }),
+                    Some(live) => {
+                        compare_fields(&latest.schema.fields, &live.schema.fields, None, &mut mismatches);
+                        compare_partitioning(&query.destination.partition, live.partition.as_ref(), &mut mismatches);
+
+                        let expected = query.cluster.as_ref().map(|c| c.fields.clone()).unwrap_or_default();
+                        if expected != live.cluster_fields {
+                            mismatches.push(SchemaMismatch {
+                                subject: "clustering".to_string(),
+                                expected: describe_clustering(&expected),
+                                actual: describe_clustering(&live.cluster_fields),
+                            });
+                        }
+                    }
+                }
+                latest.version
+            }
+            None => 0,
+        };
+
+        SchemaCheckEntry {
+            query_name: query.name.clone(),
+            table,
+            version,
+            mismatches,
+        }
+    }
+}
+
+fn compare_fields(expected: &[Field], actual: &[Field], parent: Option<&str>, mismatches: &mut Vec<SchemaMismatch>) {
+    for field in expected {
+        let path = match parent {
+            Some(parent) => format!("{}.{}", parent, field.name),
+            None => field.name.clone(),
+        };
+        let subject = format!("field {}", path);
+
+        let Some(live) = actual.iter().find(|f| f.name == field.name) else {
+            mismatches.push(SchemaMismatch {
+                subject,
+                expected: describe_field(field),
+                actual: "missing".to_string(),
+            });
+            continue;
+        };
+
+        if live.field_type != field.field_type || live.mode != field.mode {
+            mismatches.push(SchemaMismatch {
+                subject: subject.clone(),
+                expected: describe_field(field),
+                actual: describe_field(live),
+            });
+        }
+
+        if live.description != field.description {
+            mismatches.push(SchemaMismatch {
+                subject: format!("{} description", subject),
+                expected: describe_description(&field.description),
+                actual: describe_description(&live.description),
+            });
+        }
+
+        if field.field_type == BqType::Record && live.field_type == BqType::Record {
+            compare_fields(
+                field.fields.as_deref().unwrap_or_default(),
+                live.fields.as_deref().unwrap_or_default(),
+                Some(&path),
+                mismatches,
+            );
+        }
+    }
+
+    for live in actual {
+        if !expected.iter().any(|f| f.name == live.name) {
+            let path = match parent {
+                Some(parent) => format!("{}.{}", parent, live.name),
+                None => live.name.clone(),
+            };
+            mismatches.push(SchemaMismatch {
+                subject: format!("field {}", path),
+                expected: "not defined".to_string(),
+                actual: describe_field(live),
+            });
+        }
+    }
+}
+
+fn compare_partitioning(expected: &PartitionConfig, actual: Option<&PartitionConfig>, mismatches: &mut Vec<SchemaMismatch>) {
+    let expected = describe_partitioning(Some(expected));
+    let actual = describe_partitioning(actual);
+
+    if expected != actual {
+        mismatches.push(SchemaMismatch {
+            subject: "partitioning".to_string(),
+            expected,
+            actual,
+        });
+    }
+}
+
+fn describe_field(field: &Field) -> String {
+    let mode = match field.mode {
+        FieldMode::Nullable => "NULLABLE",
+        FieldMode::Required => "REQUIRED",
+        FieldMode::Repeated => "REPEATED",
+    };
+    format!("{} {}", column_type(field), mode)
+}
+
+fn describe_description(description: &Option<String>) -> String {
+    match description {
+        Some(d) => format!("\"{}\"", d),
+        None => "none".to_string(),
+    }
+}
+
+fn describe_partitioning(config: Option<&PartitionConfig>) -> String {
+    let Some(config) = config else {
+        return "none".to_string();
+    };
+
+    let granularity = |t: &PartitionType| match t {
+        PartitionType::Hour => "HOUR",
+        PartitionType::Month => "MONTH",
+        PartitionType::Year => "YEAR",
+        _ => "DAY",
+    };
+    let field = config.field.as_deref().unwrap_or("_PARTITIONTIME");
+
+    match config.partition_type {
+        PartitionType::Range => format!(
+            "RANGE on {} [{}, {}) every {}",
+            field,
+            config.start.unwrap_or(0),
+            config.end.unwrap_or(0),
+            config.interval.unwrap_or(0)
+        ),
+        PartitionType::IngestionTime => format!(
+            "{} on _PARTITIONTIME",
+            granularity(config.granularity.as_ref().unwrap_or(&PartitionType::Day))
+        ),
+        ref t => format!("{} on {}", granularity(t), field),
+    }
+}
+
+fn describe_clustering(fields: &[String]) -> String {
+    if fields.is_empty() {
+        "none".to_string()
+    } else {
+        fields.join(", ")
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::dsl::test_queries::create_test_query;
+    use crate::schema::{ClusterConfig, Schema};
+
+    fn query(schema: Schema) -> QueryDef {
+        let mut query = create_test_query("daily_stats", &[]);
+        query.destination.cluster = Some(vec!["region".to_string()]);
+        query.cluster = Some(ClusterConfig::from_fields(["region"]).unwrap());
+        query.versions[0].schema = schema;
+        query
+    }
+
+    fn expected_schema() -> Schema {
+        Schema::from_fields(vec![
+            Field::new("date", BqType::Date).required(),
+            Field::new("region", BqType::String).with_description("Sales region"),
+            Field::new("geo", BqType::Record).with_fields(vec![Field::new("lat", BqType::Float64)]),
+        ])
+    }
+
+    fn live_table(schema: Schema) -> TableDefinition {
+        TableDefinition {
+            schema,
+            partition: Some(PartitionConfig::day("date")),
+            cluster_fields: vec!["region".to_string()],
+        }
+    }
+
+    #[test]
+    fn test_matching_table_is_in_sync() {
+        let entry = SchemaChecker::check(&query(expected_schema()), Some(&live_table(expected_schema())));
+
+        assert!(entry.is_in_sync(), "{:?}", entry.mismatches);
+        assert_eq!(entry.table, "analytics.daily_stats");
+        assert_eq!(entry.version, 1);
+    }
+
+    #[test]
+    fn test_missing_table() {
+        let entry = SchemaChecker::check(&query(expected_schema()), None);
+
+        assert_eq!(entry.mismatches.len(), 1);
+        assert_eq!(entry.mismatches[0].subject, "table");
+        assert_eq!(entry.mismatches[0].actual, "missing");
+    }
+
+    #[test]
+    fn test_field_mismatches() {
+        let live = Schema::from_fields(vec![
+            Field::new("date", BqType::Date),
+            Field::new("region", BqType::String),
+            Field::new("geo", BqType::Record).with_fields(vec![Field::new("lat", BqType::String)]),
+            Field::new("added_in_console", BqType::Int64),
+        ]);
+
+        let entry = SchemaChecker::check(&query(expected_schema()), Some(&live_table(live)));
+
+        assert_eq!(entry.mismatches, vec![
+            SchemaMismatch {
+                subject: "field date".to_string(),
+                expected: "DATE REQUIRED".to_string(),
+                actual: "DATE NULLABLE".to_string(),
+            },
+            SchemaMismatch {
+                subject: "field region description".to_string(),
+                expected: "\"Sales region\"".to_string(),
+                actual: "none".to_string(),
+            },
+            SchemaMismatch {
+                subject: "field geo.lat".to_string(),
+                expected: "FLOAT64 NULLABLE".to_string(),
+                actual: "STRING NULLABLE".to_string(),
+            },
+            SchemaMismatch {
+                subject: "field added_in_console".to_string(),
+                expected: "not defined".to_string(),
+                actual: "INT64 NULLABLE".to_string(),
+            },
+        ]);
+    }
+
+    #[test]
+    fn test_field_types_use_column_type_names() {
+        let tags = Field::new("tags", BqType::String).repeated();
+        let geo = Field::new("geo", BqType::Record).with_fields(vec![Field::new("lat", BqType::Float64)]);
+
+        assert_eq!(describe_field(&tags), "ARRAY<STRING> REPEATED");
+        assert_eq!(describe_field(&geo), "STRUCT<lat FLOAT64> NULLABLE");
+        assert_eq!(describe_field(&Field::new("amount", BqType::Bignumeric)), "BIGNUMERIC NULLABLE");
+    }
+
+    #[test]
+    fn test_partitioning_and_clustering_mismatches() {
+        let mut live = live_table(expected_schema());
+        live.partition = Some(PartitionConfig::ingestion_time(PartitionType::Day));
+        live.cluster_fields = vec![];
+
+        let entry = SchemaChecker::check(&query(expected_schema()), Some(&live));
+
+        assert_eq!(entry.mismatches.len(), 2);
+        assert_eq!(entry.mismatches[0].expected, "DAY on date");
+        assert_eq!(entry.mismatches[0].actual, "DAY on _PARTITIONTIME");
+        assert_eq!(entry.mismatches[1].subject, "clustering");
+        assert_eq!(entry.mismatches[1].actual, "none");
+    }
+}
@@ -1,11 +1,13 @@
 use chrono::{DateTime, NaiveDate, Utc};
 use serde::{Deserialize, Serialize};
 use std::collections::HashMap;
+use crate::dsl::WriteStrategy;
 use crate::schema::PartitionKey;
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct PartitionState {
     pub query_name: String,
+    pub partition_key: PartitionKey,
     pub partition_date: NaiveDate,
     pub version: u32,
     pub sql_revision: Option<u32>,
@@ -14,17 +16,27 @@ pub struct PartitionState {
     pub schema_checksum: String,
     pub yaml_checksum: String,
     pub executed_sql_b64: Option<String>,
+    #[serde(default)]
+    pub executed_yaml_b64: Option<String>,
     pub upstream_states: HashMap<String, DateTime<Utc>>,
     pub executed_at: DateTime<Utc>,
     pub execution_time_ms: Option<i64>,
     pub rows_written: Option<i64>,
     pub bytes_processed: Option<i64>,
+    #[serde(default)]
+    pub bytes_billed: Option<i64>,
+    #[serde(default)]
+    pub slot_ms: Option<i64>,
+    #[serde(default)]
+    pub job_id: Option<String>,
+    #[serde(default)]
+    pub write_strategy: Option<WriteStrategy>,
     pub status: ExecutionStatus,
 }
 
 impl PartitionState {
     pub fn partition_key(&self) -> PartitionKey {
-        PartitionKey::Day(self.partition_date)
+        self.partition_key.clone()
     }
 }
 
@@ -33,6 +45,8 @@ impl PartitionState {
 pub enum ExecutionStatus {
     Success,
     Failed,
+    /// The write job was cancelled by an interrupt or a job timeout.
+    Cancelled,
 }
 
 #[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
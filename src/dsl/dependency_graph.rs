@@ -0,0 +1,431 @@
+use std::collections::{HashSet, VecDeque};
+use std::fmt::Write;
+use chrono::{NaiveDate, Utc};
+use serde::Serialize;
+use super::parser::QueryDef;
+
+/// How `DependencyGraph::render` prints the graph.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum GraphFormat {
+    Ascii,
+    Dot,
+    Mermaid,
+    Json,
+}
+
+impl std::str::FromStr for GraphFormat {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.to_lowercase().as_str() {
+            "ascii" | "tree" => Ok(GraphFormat::Ascii),
+            "dot" | "graphviz" => Ok(GraphFormat::Dot),
+            "mermaid" => Ok(GraphFormat::Mermaid),
+            "json" => Ok(GraphFormat::Json),
+            other => Err(format!("Unknown graph format '{}': expected ascii, dot, mermaid or json", other)),
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct GraphNode {
+    /// `dataset.table` of a query's destination, or the table reference as
+    /// written in SQL for an external source.
+    pub id: String,
+    /// The query writing this table; `None` for external sources.
+    pub query: Option<String>,
+    pub version: Option<u32>,
+    pub revision: Option<u32>,
+    /// Read by a query but not produced by any bqdrift query.
+    pub external: bool,
+}
+
+impl GraphNode {
+    fn label(&self) -> String {
+        match (self.version, self.revision) {
+            _ if self.external => format!("{} [external]", self.id),
+            (Some(v), Some(r)) => format!("{} (v{}.r{})", self.id, v, r),
+            (Some(v), None) => format!("{} (v{})", self.id, v),
+            (None, _) => self.id.clone(),
+        }
+    }
+}
+
+/// An edge from a table to a query that reads it.
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct GraphEdge {
+    pub from: String,
+    pub to: String,
+}
+
+/// The table-level dependency graph of a set of queries, for display.
+/// Nodes are destination tables (labelled with the version in effect) and
+/// the external tables they read.
+#[derive(Debug, Clone, Default, Serialize)]
+pub struct DependencyGraph {
+    pub nodes: Vec<GraphNode>,
+    pub edges: Vec<GraphEdge>,
+}
+
+impl DependencyGraph {
+    /// Build the graph from each query's dependencies. With `date`, the
+    /// version in effect for that partition date is used (queries with no
+    /// version yet are still shown, unversioned); otherwise the latest version.
+    /// Revisions are resolved as of today, as `run` does.
+    pub fn build(queries: &[QueryDef], date: Option<NaiveDate>) -> Self {
+        let today = Utc::now().date_naive();
+        let mut graph = Self::default();
+
+        let versions: Vec<_> = queries
+            .iter()
+            .map(|q| match date {
+                Some(date) => q.get_version_for_date(date),
+                None => q.latest_version(),
+            })
+            .collect();
+
+        for (query, version) in queries.iter().zip(&versions) {
+            graph.nodes.push(GraphNode {
+                id: destination_id(query),
+                query: Some(query.name.clone()),
+                version: version.map(|v| v.version),
+                revision: version.and_then(|v| v.get_revision_for_date(today)).map(|r| r.revision),
+                external: false,
+            });
+        }
+
+        let mut externals: Vec<String> = Vec::new();
+
+        for (query, version) in queries.iter().zip(&versions) {
+            let Some(version) = version else {
+                continue;
+            };
+            let dependencies = version
+                .get_revision_for_date(today)
+                .map(|r| &r.dependencies)
+                .unwrap_or(&version.dependencies);
+
+            let dependencies: HashSet<String> = dependencies
+                .iter()
+                .filter(|dep| !query.produces(dep))
+                .cloned()
+                .collect();
+
+            for upstream in query.upstream_queries(&dependencies, queries) {
+                push_edge(&mut graph, destination_id(upstream), destination_id(query));
+            }
+
+            let mut sources: Vec<String> = dependencies
+                .iter()
+                .filter(|dep| !queries.iter().any(|q| q.produces(dep)))
+                .map(|dep| dep.replace('`', ""))
+                .collect();
+            sources.sort();
+
+            for id in sources {
+                if !externals.contains(&id) {
+                    externals.push(id.clone());
+                }
+                push_edge(&mut graph, id, destination_id(query));
+            }
+        }
+
+        externals.sort();
+        graph.nodes.extend(externals.into_iter().map(|id| GraphNode {
+            id,
+            query: None,
+            version: None,
+            revision: None,
+            external: true,
+        }));
+
+        graph
+    }
+
+    /// The subgraph around `query` (a query name or `dataset.table`): what it
+    /// reads from with `upstream`, what reads from it with `downstream`.
+    /// Returns `None` if no node matches.
+    pub fn focus(&self, query: &str, upstream: bool, downstream: bool) -> Option<Self> {
+        let start = self.nodes.iter().find(|n| n.query.as_deref() == Some(query) || n.id == query)?;
+
+        let mut keep: HashSet<&str> = HashSet::from([start.id.as_str()]);
+        if upstream {
+            keep.extend(self.reachable(&start.id, |e| (&e.to, &e.from)));
+        }
+        if downstream {
+            keep.extend(self.reachable(&start.id, |e| (&e.from, &e.to)));
+        }
+
+        Some(Self {
+            nodes: self.nodes.iter().filter(|n| keep.contains(n.id.as_str())).cloned().collect(),
+            edges: self
+                .edges
+                .iter()
+                .filter(|e| keep.contains(e.from.as_str()) && keep.contains(e.to.as_str()))
+                .cloned()
+                .collect(),
+        })
+    }
+
+    /// Nodes reachable from `start`, following edges oriented by `direction`
+    /// (which returns an edge's (tail, head)).
+    fn reachable<'a>(&'a self, start: &str, direction: impl Fn(&'a GraphEdge) -> (&'a String, &'a String)) -> Vec<&'a str> {
+        let mut seen: HashSet<&str> = HashSet::new();
+        let mut queue: VecDeque<String> = VecDeque::from([start.to_string()]);
+
+        while let Some(current) = queue.pop_front() {
+            for edge in &self.edges {
+                let (tail, head) = direction(edge);
+                if *tail == current && seen.insert(head.as_str()) {
+                    queue.push_back(head.clone());
+                }
+            }
+        }
+
+        seen.into_iter().collect()
+    }
+
+    pub fn render(&self, format: GraphFormat) -> String {
+        match format {
+            GraphFormat::Ascii => self.to_ascii(),
+            GraphFormat::Dot => self.to_dot(),
+            GraphFormat::Mermaid => self.to_mermaid(),
+            GraphFormat::Json => serde_json::to_string_pretty(self).unwrap_or_default(),
+        }
+    }
+
+    /// A tree per root (a node nothing in the graph feeds). A node read by
+    /// several queries appears under each of them.
+    pub fn to_ascii(&self) -> String {
+        let mut out = String::new();
+        let roots: Vec<&GraphNode> = self
+            .nodes
+            .iter()
+            .filter(|n| !self.edges.iter().any(|e| e.to == n.id))
+            .collect();
+
+        for (i, root) in roots.iter().enumerate() {
+            if i > 0 {
+                out.push('\n');
+            }
+            out.push_str(&root.label());
+            out.push('\n');
+            self.write_children(&mut out, &root.id, "", &mut vec![root.id.clone()]);
+        }
+
+        if roots.is_empty() && !self.nodes.is_empty() {
+            out.push_str("(every query is part of a dependency cycle)\n");
+        }
+
+        out
+    }
+
+    fn write_children(&self, out: &mut String, id: &str, prefix: &str, path: &mut Vec<String>) {
+        let children: Vec<&GraphNode> = self
+            .edges
+            .iter()
+            .filter(|e| e.from == id)
+            .filter_map(|e| self.nodes.iter().find(|n| n.id == e.to))
+            .collect();
+
+        for (i, child) in children.iter().enumerate() {
+            let last = i + 1 == children.len();
+            let (branch, indent) = if last { ("└── ", "    ") } else { ("├── ", "│   ") };
+
+            if path.contains(&child.id) {
+                let _ = writeln!(out, "{}{}{} (cycle)", prefix, branch, child.label());
+                continue;
+            }
+
+            let _ = writeln!(out, "{}{}{}", prefix, branch, child.label());
+            path.push(child.id.clone());
+            self.write_children(out, &child.id, &format!("{}{}", prefix, indent), path);
+            path.pop();
+        }
+    }
+
+    /// Graphviz DOT; external sources are dashed ellipses.
+    pub fn to_dot(&self) -> String {
+        let mut out = String::from("digraph bqdrift {\n    rankdir=LR;\n    node [shape=box];\n\n");
+
+        for node in &self.nodes {
+            let style = if node.external { ", shape=ellipse, style=dashed" } else { "" };
+            let _ = writeln!(out, "    \"{}\" [label=\"{}\"{}];", dot_escape(&node.id), dot_escape(&node.label()), style);
+        }
+
+        if !self.edges.is_empty() {
+            out.push('\n');
+        }
+        for edge in &self.edges {
+            let _ = writeln!(out, "    \"{}\" -> \"{}\";", dot_escape(&edge.from), dot_escape(&edge.to));
+        }
+
+        out.push_str("}\n");
+        out
+    }
+
+    /// A Mermaid flowchart; external sources are drawn as dashed stadiums.
+    pub fn to_mermaid(&self) -> String {
+        let mut out = String::from("graph LR\n");
+        let node_ref = |id: &str| self.nodes.iter().position(|n| n.id == id).map(|i| format!("n{}", i));
+
+        for (i, node) in self.nodes.iter().enumerate() {
+            let label = node.label().replace('"', "#quot;");
+            if node.external {
+                let _ = writeln!(out, "    n{}([\"{}\"])", i, label);
+            } else {
+                let _ = writeln!(out, "    n{}[\"{}\"]", i, label);
+            }
+        }
+
+        for edge in &self.edges {
+            if let (Some(from), Some(to)) = (node_ref(&edge.from), node_ref(&edge.to)) {
+                let _ = writeln!(out, "    {} --> {}", from, to);
+            }
+        }
+
+        let external: Vec<String> = self
+            .nodes
+            .iter()
+            .enumerate()
+            .filter(|(_, n)| n.external)
+            .map(|(i, _)| format!("n{}", i))
+            .collect();
+        if !external.is_empty() {
+            out.push_str("    classDef external stroke-dasharray: 5 5\n");
+            let _ = writeln!(out, "    class {} external", external.join(","));
+        }
+
+        out
+    }
+}
+
+fn push_edge(graph: &mut DependencyGraph, from: String, to: String) {
+    let edge = GraphEdge { from, to };
+    if !graph.edges.contains(&edge) {
+        graph.edges.push(edge);
+    }
+}
+
+fn destination_id(query: &QueryDef) -> String {
+    query.destination.table_ref()
+}
+
+fn dot_escape(s: &str) -> String {
+    s.replace('\\', "\\\\").replace('"', "\\\"")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::dsl::test_queries::{create_test_query_with_versions, create_test_version};
+
+    fn jan(day: u32) -> NaiveDate {
+        NaiveDate::from_ymd_opt(2024, 1, day).unwrap()
+    }
+
+    fn queries() -> Vec<QueryDef> {
+        vec![
+            create_test_query_with_versions("daily", vec![create_test_version(1, jan(1), &["raw.events"])]),
+            create_test_query_with_versions("revenue", vec![create_test_version(1, jan(1), &["`raw.orders`"])]),
+            create_test_query_with_versions("weekly", vec![
+                create_test_version(1, jan(1), &["analytics.daily"]),
+                create_test_version(2, jan(15), &["analytics.daily", "analytics.revenue"]),
+            ]),
+        ]
+    }
+
+    #[test]
+    fn test_build_marks_external_sources() {
+        let graph = DependencyGraph::build(&queries(), None);
+
+        let ids: Vec<&str> = graph.nodes.iter().map(|n| n.id.as_str()).collect();
+        assert_eq!(ids, vec!["analytics.daily", "analytics.revenue", "analytics.weekly", "raw.events", "raw.orders"]);
+        assert!(graph.nodes[3].external && graph.nodes[4].external);
+        assert_eq!(graph.nodes[2].version, Some(2));
+        assert_eq!(graph.edges.len(), 4);
+        assert!(graph.edges.contains(&GraphEdge { from: "analytics.revenue".into(), to: "analytics.weekly".into() }));
+    }
+
+    #[test]
+    fn test_build_for_date_uses_version_in_effect() {
+        let graph = DependencyGraph::build(&queries(), Some(jan(10)));
+
+        assert_eq!(graph.nodes[2].version, Some(1));
+        assert!(!graph.edges.iter().any(|e| e.from == "analytics.revenue"));
+    }
+
+    #[test]
+    fn test_build_links_every_producer_of_a_table() {
+        let mut queries = queries();
+        queries[0].destination.project = Some("eu-project".to_string());
+        let mut us_daily = create_test_query_with_versions("us_daily", vec![create_test_version(1, jan(1), &["raw.events"])]);
+        us_daily.destination.project = Some("us-project".to_string());
+        us_daily.destination.table = "daily".to_string();
+        queries.push(us_daily);
+
+        let graph = DependencyGraph::build(&queries, None);
+
+        let into_weekly: Vec<&str> = graph
+            .edges
+            .iter()
+            .filter(|e| e.to == "analytics.weekly")
+            .map(|e| e.from.as_str())
+            .collect();
+        assert_eq!(into_weekly, vec!["eu-project.analytics.daily", "analytics.revenue", "us-project.analytics.daily"]);
+    }
+
+    #[test]
+    fn test_focus() {
+        let graph = DependencyGraph::build(&queries(), None);
+
+        let upstream = graph.focus("weekly", true, false).unwrap();
+        assert_eq!(upstream.nodes.len(), 5);
+
+        let downstream = graph.focus("daily", false, true).unwrap();
+        let ids: Vec<&str> = downstream.nodes.iter().map(|n| n.id.as_str()).collect();
+        assert_eq!(ids, vec!["analytics.daily", "analytics.weekly"]);
+        assert_eq!(downstream.edges.len(), 1);
+
+        assert!(graph.focus("missing", true, true).is_none());
+    }
+
+    #[test]
+    fn test_ascii_tree() {
+        let graph = DependencyGraph::build(&queries(), None);
+
+        assert_eq!(
+            graph.to_ascii(),
+            "raw.events [external]\n\
+             └── analytics.daily (v1)\n\
+             \x20   └── analytics.weekly (v2)\n\
+             \n\
+             raw.orders [external]\n\
+             └── analytics.revenue (v1)\n\
+             \x20   └── analytics.weekly (v2)\n"
+        );
+    }
+
+    #[test]
+    fn test_dot_and_mermaid() {
+        let graph = DependencyGraph::build(&queries(), None);
+
+        let dot = graph.to_dot();
+        assert!(dot.starts_with("digraph bqdrift {"));
+        assert!(dot.contains("\"raw.events\" [label=\"raw.events [external]\", shape=ellipse, style=dashed];"));
+        assert!(dot.contains("\"analytics.daily\" -> \"analytics.weekly\";"));
+
+        let mermaid = graph.to_mermaid();
+        assert!(mermaid.starts_with("graph LR\n"));
+        assert!(mermaid.contains("n3([\"raw.events [external]\"])"));
+        assert!(mermaid.contains("n0 --> n2"));
+        assert!(mermaid.contains("class n3,n4 external"));
+    }
+
+    #[test]
+    fn test_parse_format() {
+        assert_eq!("DOT".parse::<GraphFormat>(), Ok(GraphFormat::Dot));
+        assert_eq!("mermaid".parse::<GraphFormat>(), Ok(GraphFormat::Mermaid));
+        assert!("svg".parse::<GraphFormat>().is_err());
+    }
+}
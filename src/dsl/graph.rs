@@ -0,0 +1,237 @@
+use std::collections::{HashSet, VecDeque};
+use crate::error::{BqDriftError, Result};
+use crate::schema::PartitionKey;
+use super::parser::QueryDef;
+
+/// Queries as a dependency graph. A query is upstream of every other query
+/// whose SQL reads its destination table, in any version or revision (`new`)
+/// or in the versions that write given partitions (`for_partitions`).
+/// Nodes are indexes into the query slice the graph was built from.
+#[derive(Debug, Clone)]
+pub struct QueryGraph {
+    names: Vec<String>,
+    upstream: Vec<Vec<usize>>,
+    downstream: Vec<Vec<usize>>,
+}
+
+impl QueryGraph {
+    pub fn new(queries: &[QueryDef]) -> Self {
+        Self::from_dependencies(queries, QueryDef::all_dependencies)
+    }
+
+    /// The graph of the versions that write `partition_keys`, each at the
+    /// revision in effect today (see `QueryDef::dependencies_for_partition`).
+    /// Dependencies of versions that no longer apply to these partitions add
+    /// no edges, so they can't form a cycle with the current ones.
+    pub fn for_partitions(queries: &[QueryDef], partition_keys: &[PartitionKey]) -> Self {
+        Self::from_dependencies(queries, |q| {
+            partition_keys
+                .iter()
+                .filter_map(|key| q.dependencies_for_partition(key))
+                .flatten()
+                .cloned()
+                .collect()
+        })
+    }
+
+    fn from_dependencies(queries: &[QueryDef], dependencies: impl Fn(&QueryDef) -> HashSet<String>) -> Self {
+        let upstream: Vec<Vec<usize>> = queries
+            .iter()
+            .map(|q| {
+                q.upstream_queries(&dependencies(q), queries)
+                    .iter()
+                    .filter_map(|u| queries.iter().position(|other| other.name == u.name))
+                    .collect()
+            })
+            .collect();
+
+        let mut downstream = vec![Vec::new(); queries.len()];
+        for (i, ups) in upstream.iter().enumerate() {
+            for &u in ups {
+                downstream[u].push(i);
+            }
+        }
+
+        Self {
+            names: queries.iter().map(|q| q.name.clone()).collect(),
+            upstream,
+            downstream,
+        }
+    }
+
+    pub fn len(&self) -> usize {
+        self.names.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.names.is_empty()
+    }
+
+    pub fn name(&self, node: usize) -> &str {
+        &self.names[node]
+    }
+
+    pub fn index_of(&self, name: &str) -> Option<usize> {
+        self.names.iter().position(|n| n == name)
+    }
+
+    /// Queries `node` reads from.
+    pub fn upstream(&self, node: usize) -> &[usize] {
+        &self.upstream[node]
+    }
+
+    /// Queries that read from `node`.
+    pub fn downstream(&self, node: usize) -> &[usize] {
+        &self.downstream[node]
+    }
+
+    /// Every query that reads from `node`, directly or through other queries,
+    /// nearest first.
+    pub fn descendants(&self, node: usize) -> Vec<usize> {
+        let mut seen = vec![false; self.len()];
+        let mut queue: VecDeque<usize> = self.downstream[node].iter().copied().collect();
+        let mut descendants = Vec::new();
+        seen[node] = true;
+
+        while let Some(next) = queue.pop_front() {
+            if seen[next] {
+                continue;
+            }
+            seen[next] = true;
+            descendants.push(next);
+            queue.extend(self.downstream[next].iter().copied());
+        }
+
+        descendants
+    }
+
+    /// All nodes, each after the queries it reads from. Queries with no
+    /// ordering between them keep load order. Fails if the graph has a cycle.
+    pub fn topological_order(&self) -> Result<Vec<usize>> {
+        let mut placed = vec![false; self.len()];
+        let mut ordered = Vec::with_capacity(self.len());
+
+        loop {
+            let ready: Vec<usize> = (0..self.len())
+                .filter(|&i| !placed[i] && self.upstream[i].iter().all(|&u| placed[u]))
+                .collect();
+
+            if ready.is_empty() {
+                break;
+            }
+
+            for i in ready {
+                placed[i] = true;
+                ordered.push(i);
+            }
+        }
+
+        if ordered.len() < self.len() {
+            let cycle = self.find_cycle(&placed).unwrap_or_default();
+            let path: Vec<&str> = cycle.iter().map(|&i| self.name(i)).collect();
+            return Err(BqDriftError::DependencyCycle(path.join(" -> ")));
+        }
+
+        Ok(ordered)
+    }
+
+    /// A cycle among the nodes not yet `placed`, as a path whose last node
+    /// repeats the first. Every unplaced node has an unplaced upstream, so
+    /// walking upstream from any of them must revisit a node.
+    fn find_cycle(&self, placed: &[bool]) -> Option<Vec<usize>> {
+        let start = (0..self.len()).find(|&i| !placed[i])?;
+        let mut path = vec![start];
+
+        loop {
+            let current = *path.last()?;
+            let next = *self.upstream[current].iter().find(|&&u| !placed[u])?;
+
+            if let Some(pos) = path.iter().position(|&n| n == next) {
+                // The walk went upstream; reverse it so edges read upstream -> downstream.
+                let mut cycle: Vec<usize> = path[pos..].to_vec();
+                cycle.push(next);
+                cycle.reverse();
+                return Some(cycle);
+            }
+            path.push(next);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::dsl::test_queries::{create_test_query, create_test_query_with_versions, create_test_version};
+    use chrono::NaiveDate;
+
+    fn names(graph: &QueryGraph, nodes: &[usize]) -> Vec<String> {
+        nodes.iter().map(|&i| graph.name(i).to_string()).collect()
+    }
+
+    #[test]
+    fn test_edges_follow_destination_tables() {
+        let queries = vec![
+            create_test_query("daily", &["raw.events"]),
+            create_test_query("weekly", &["analytics.daily"]),
+            create_test_query("report", &["analytics.weekly", "analytics.daily"]),
+        ];
+        let graph = QueryGraph::new(&queries);
+
+        assert!(graph.upstream(0).is_empty());
+        assert_eq!(names(&graph, graph.downstream(0)), vec!["weekly", "report"]);
+        assert_eq!(names(&graph, graph.upstream(2)), vec!["daily", "weekly"]);
+        assert_eq!(names(&graph, &graph.descendants(0)), vec!["weekly", "report"]);
+    }
+
+    #[test]
+    fn test_topological_order() {
+        let queries = vec![
+            create_test_query("report", &["analytics.weekly"]),
+            create_test_query("weekly", &["analytics.daily"]),
+            create_test_query("daily", &[]),
+            create_test_query("standalone", &[]),
+        ];
+        let graph = QueryGraph::new(&queries);
+
+        let order = graph.topological_order().unwrap();
+        assert_eq!(names(&graph, &order), vec!["daily", "standalone", "weekly", "report"]);
+    }
+
+    #[test]
+    fn test_cycle_is_an_error() {
+        let queries = vec![
+            create_test_query("c", &[]),
+            create_test_query("a", &["analytics.b"]),
+            create_test_query("b", &["analytics.a"]),
+        ];
+        let graph = QueryGraph::new(&queries);
+
+        let err = graph.topological_order().unwrap_err();
+        assert!(matches!(err, BqDriftError::DependencyCycle(_)));
+        assert_eq!(err.to_string(), "Dependency cycle: a -> b -> a");
+    }
+
+    #[test]
+    fn test_partition_graph_uses_versions_in_effect() {
+        let jan = NaiveDate::from_ymd_opt(2024, 1, 1).unwrap();
+        let feb = NaiveDate::from_ymd_opt(2024, 2, 1).unwrap();
+        let queries = vec![
+            create_test_query_with_versions("a", vec![
+                create_test_version(1, jan, &["analytics.b"]),
+                create_test_version(2, feb, &[]),
+            ]),
+            create_test_query_with_versions("b", vec![
+                create_test_version(1, jan, &[]),
+                create_test_version(2, feb, &["analytics.a"]),
+            ]),
+        ];
+
+        assert!(QueryGraph::new(&queries).topological_order().is_err());
+
+        let january = QueryGraph::for_partitions(&queries, &[PartitionKey::Day(jan.succ_opt().unwrap())]);
+        assert_eq!(names(&january, &january.topological_order().unwrap()), vec!["b", "a"]);
+
+        let february = QueryGraph::for_partitions(&queries, &[PartitionKey::Day(feb.succ_opt().unwrap())]);
+        assert_eq!(names(&february, &february.topological_order().unwrap()), vec!["a", "b"]);
+    }
+}
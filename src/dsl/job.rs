@@ -0,0 +1,171 @@
+use serde::{Deserialize, Deserializer, Serialize};
+use std::collections::BTreeMap;
+use std::time::Duration;
+use crate::executor::parse_byte_size;
+use crate::schema::PartitionKey;
+
+/// BigQuery job settings from a `job:` block, applied to every job bqdrift
+/// submits for the query. A version's block overrides the query's field by
+/// field; labels from both are kept, the version's winning on conflicts.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct JobConfig {
+    #[serde(default)]
+    pub labels: BTreeMap<String, String>,
+    #[serde(default)]
+    pub priority: Option<JobPriority>,
+    /// Written as a byte count or a size such as `500GB`.
+    #[serde(default, deserialize_with = "deserialize_byte_size")]
+    pub maximum_bytes_billed: Option<i64>,
+    /// Seconds a job may run before it is cancelled.
+    #[serde(default)]
+    pub timeout: Option<u64>,
+    /// Reservation the jobs run in, as
+    /// `projects/{project}/locations/{location}/reservations/{name}`, or
+    /// `none` for on-demand billing.
+    #[serde(default)]
+    pub reservation: Option<String>,
+}
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "UPPERCASE")]
+pub enum JobPriority {
+    #[default]
+    Interactive,
+    /// Queued until idle slots are available; doesn't count towards the
+    /// concurrent interactive query limit.
+    Batch,
+}
+
+impl JobPriority {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            JobPriority::Interactive => "INTERACTIVE",
+            JobPriority::Batch => "BATCH",
+        }
+    }
+}
+
+impl std::fmt::Display for JobPriority {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str(self.as_str())
+    }
+}
+
+impl JobConfig {
+    /// `overrides` applied on top of `self`.
+    pub fn merged(&self, overrides: &JobConfig) -> JobConfig {
+        let mut labels = self.labels.clone();
+        labels.extend(overrides.labels.iter().map(|(k, v)| (k.clone(), v.clone())));
+
+        JobConfig {
+            labels,
+            priority: overrides.priority.or(self.priority),
+            maximum_bytes_billed: overrides.maximum_bytes_billed.or(self.maximum_bytes_billed),
+            timeout: overrides.timeout.or(self.timeout),
+            reservation: overrides.reservation.clone().or_else(|| self.reservation.clone()),
+        }
+    }
+
+    /// The settings for writing one partition, with the `bqdrift_query`,
+    /// `bqdrift_version` and `bqdrift_partition` labels added.
+    pub fn for_partition(&self, query_name: &str, version: u32, partition_key: &PartitionKey) -> JobConfig {
+        let mut config = self.clone();
+        config.labels.insert("bqdrift_query".to_string(), label_value(query_name));
+        config.labels.insert("bqdrift_version".to_string(), version.to_string());
+        config.labels.insert("bqdrift_partition".to_string(), label_value(&partition_key.to_string()));
+        config
+    }
+
+    pub fn timeout(&self) -> Option<Duration> {
+        self.timeout.map(Duration::from_secs)
+    }
+}
+
+/// BigQuery label keys start with a lowercase letter and, like values, hold
+/// at most 63 lowercase letters, digits, underscores and dashes.
+pub fn is_valid_label_key(key: &str) -> bool {
+    key.starts_with(|c: char| c.is_ascii_lowercase()) && is_valid_label_value(key)
+}
+
+pub fn is_valid_label_value(value: &str) -> bool {
+    value.len() <= 63
+        && value.chars().all(|c| c.is_ascii_lowercase() || c.is_ascii_digit() || c == '_' || c == '-')
+}
+
+/// `value` lowercased, with characters labels can't hold replaced by `_`,
+/// cut to 63 characters.
+pub fn label_value(value: &str) -> String {
+    value
+        .chars()
+        .map(|c| c.to_ascii_lowercase())
+        .map(|c| if c.is_ascii_lowercase() || c.is_ascii_digit() || c == '-' { c } else { '_' })
+        .take(63)
+        .collect()
+}
+
+fn deserialize_byte_size<'de, D>(deserializer: D) -> std::result::Result<Option<i64>, D::Error>
+where
+    D: Deserializer<'de>,
+{
+    #[derive(Deserialize)]
+    #[serde(untagged)]
+    enum ByteSize {
+        Bytes(i64),
+        Size(String),
+    }
+
+    match Option::<ByteSize>::deserialize(deserializer)? {
+        None => Ok(None),
+        Some(ByteSize::Bytes(bytes)) => Ok(Some(bytes)),
+        Some(ByteSize::Size(size)) => parse_byte_size(&size)
+            .map(Some)
+            .map_err(serde::de::Error::custom),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::NaiveDate;
+
+    #[test]
+    fn test_parse_job_block() {
+        let yaml = r#"
+labels:
+  team: finance
+priority: BATCH
+maximum_bytes_billed: 500GB
+timeout: 1800
+"#;
+        let config: JobConfig = serde_yaml::from_str(yaml).unwrap();
+
+        assert_eq!(config.labels.get("team").map(String::as_str), Some("finance"));
+        assert_eq!(config.priority, Some(JobPriority::Batch));
+        assert_eq!(config.maximum_bytes_billed, Some(500 * (1 << 30)));
+        assert_eq!(config.timeout(), Some(Duration::from_secs(1800)));
+        assert_eq!(config.reservation, None);
+    }
+
+    #[test]
+    fn test_version_overrides_query() {
+        let query: JobConfig = serde_yaml::from_str("{labels: {team: finance, tier: gold}, priority: BATCH, timeout: 60}").unwrap();
+        let version: JobConfig = serde_yaml::from_str("{labels: {tier: silver}, priority: INTERACTIVE}").unwrap();
+
+        let merged = query.merged(&version);
+        assert_eq!(merged.labels.get("team").map(String::as_str), Some("finance"));
+        assert_eq!(merged.labels.get("tier").map(String::as_str), Some("silver"));
+        assert_eq!(merged.priority, Some(JobPriority::Interactive));
+        assert_eq!(merged.timeout, Some(60));
+    }
+
+    #[test]
+    fn test_partition_labels() {
+        let key = PartitionKey::Hour(NaiveDate::from_ymd_opt(2024, 3, 1).unwrap().and_hms_opt(7, 0, 0).unwrap());
+        let config = JobConfig::default().for_partition("Daily.Stats", 2, &key);
+
+        assert_eq!(config.labels["bqdrift_query"], "daily_stats");
+        assert_eq!(config.labels["bqdrift_version"], "2");
+        assert_eq!(config.labels["bqdrift_partition"], "2024-03-01t07");
+        assert!(config.labels.iter().all(|(k, v)| is_valid_label_key(k) && is_valid_label_value(v)));
+    }
+}
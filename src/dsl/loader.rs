@@ -77,6 +77,8 @@ impl QueryLoader {
         let mut resolved_invariants: HashMap<u32, InvariantsDef> = HashMap::new();
         let mut versions: Vec<VersionDef> = Vec::new();
 
+        let query_job = raw.job.clone().unwrap_or_default();
+
         let mut sorted_versions = raw.versions.clone();
         sorted_versions.sort_by_key(|v| v.version);
 
@@ -110,6 +112,10 @@ impl QueryLoader {
                 schema,
                 dependencies,
                 invariants,
+                job: match &raw_version.job {
+                    Some(job) => query_job.merged(job),
+                    None => query_job.clone(),
+                },
             });
         }
 
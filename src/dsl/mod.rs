@@ -4,10 +4,18 @@ mod loader;
 mod validator;
 mod dependencies;
 mod preprocessor;
+mod graph;
+mod dependency_graph;
+mod job;
+#[cfg(test)]
+pub(crate) mod test_queries;
 
-pub use parser::{QueryDef, VersionDef, Revision, ResolvedRevision, Destination, RawQueryDef, SchemaRef};
+pub use parser::{QueryDef, VersionDef, Revision, ResolvedRevision, Destination, WriteStrategy, RawQueryDef, SchemaRef};
 pub use resolver::VariableResolver;
 pub use loader::QueryLoader;
 pub use validator::{QueryValidator, ValidationResult, ValidationError, ValidationWarning};
 pub use dependencies::SqlDependencies;
 pub use preprocessor::YamlPreprocessor;
+pub use graph::QueryGraph;
+pub use dependency_graph::{DependencyGraph, GraphNode, GraphEdge, GraphFormat};
+pub use job::{JobConfig, JobPriority};
@@ -1,8 +1,9 @@
 use chrono::NaiveDate;
 use serde::{Deserialize, Serialize};
 use std::collections::HashSet;
-use crate::schema::{Field, PartitionConfig, ClusterConfig, Schema};
+use crate::schema::{Field, PartitionConfig, PartitionKey, ClusterConfig, Schema};
 use crate::invariant::{InvariantsRef, InvariantsDef};
+use super::job::JobConfig;
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct RawQueryDef {
@@ -14,6 +15,8 @@ pub struct RawQueryDef {
     pub owner: Option<String>,
     #[serde(default)]
     pub tags: Vec<String>,
+    #[serde(default)]
+    pub job: Option<JobConfig>,
     pub versions: Vec<RawVersionDef>,
 }
 
@@ -31,6 +34,9 @@ pub struct RawVersionDef {
     pub schema: SchemaRef,
     #[serde(default)]
     pub invariants: Option<InvariantsRef>,
+    /// Overrides the query's `job` settings for this version.
+    #[serde(default)]
+    pub job: Option<JobConfig>,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -65,11 +71,82 @@ pub struct Revision {
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct Destination {
+    /// Project holding the table; the client's project when unset.
+    #[serde(default)]
+    pub project: Option<String>,
     pub dataset: String,
     pub table: String,
     pub partition: PartitionConfig,
     #[serde(default)]
     pub cluster: Option<Vec<String>>,
+    #[serde(default)]
+    pub write_strategy: WriteStrategy,
+    /// Location of the dataset, e.g. `EU`, and of every job writing or
+    /// checking it. Overrides the client's location.
+    #[serde(default)]
+    pub location: Option<String>,
+}
+
+impl Destination {
+    /// `project` when set, otherwise `default_project`.
+    pub fn project_or<'a>(&'a self, default_project: &'a str) -> &'a str {
+        self.project.as_deref().unwrap_or(default_project)
+    }
+
+    /// `dataset.table`, or `project.dataset.table` when the destination
+    /// names a project.
+    pub fn table_ref(&self) -> String {
+        match &self.project {
+            Some(project) => format!("{}.{}.{}", project, self.dataset, self.table),
+            None => format!("{}.{}", self.dataset, self.table),
+        }
+    }
+}
+
+/// How a partition's rows are replaced when it is written.
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq, Default)]
+#[serde(rename_all = "snake_case")]
+pub enum WriteStrategy {
+    /// A single MERGE that deletes the partition's rows and inserts the new ones.
+    #[default]
+    Merge,
+    /// DELETE of the partition and INSERT of the new rows in one transaction.
+    TruncatePartition,
+    /// A query job writing to the partition decorator with `WRITE_TRUNCATE`.
+    InsertOverwriteViaJob,
+    /// INSERT without removing existing rows. Rerunning a partition duplicates it.
+    Append,
+}
+
+impl WriteStrategy {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            WriteStrategy::Merge => "merge",
+            WriteStrategy::TruncatePartition => "truncate_partition",
+            WriteStrategy::InsertOverwriteViaJob => "insert_overwrite_via_job",
+            WriteStrategy::Append => "append",
+        }
+    }
+}
+
+impl std::fmt::Display for WriteStrategy {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str(self.as_str())
+    }
+}
+
+impl std::str::FromStr for WriteStrategy {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "merge" => Ok(WriteStrategy::Merge),
+            "truncate_partition" => Ok(WriteStrategy::TruncatePartition),
+            "insert_overwrite_via_job" => Ok(WriteStrategy::InsertOverwriteViaJob),
+            "append" => Ok(WriteStrategy::Append),
+            other => Err(format!("Unknown write strategy '{}'", other)),
+        }
+    }
 }
 
 #[derive(Debug, Clone)]
@@ -95,6 +172,8 @@ pub struct VersionDef {
     pub schema: Schema,
     pub dependencies: HashSet<String>,
     pub invariants: InvariantsDef,
+    /// The query's `job` settings with this version's overrides applied.
+    pub job: JobConfig,
 }
 
 #[derive(Debug, Clone)]
@@ -109,13 +188,15 @@ pub struct ResolvedRevision {
 }
 
 impl VersionDef {
-    pub fn get_sql_for_date(&self, execution_date: NaiveDate) -> &str {
-        let applicable_revision = self.revisions
+    pub fn get_revision_for_date(&self, execution_date: NaiveDate) -> Option<&ResolvedRevision> {
+        self.revisions
             .iter()
             .filter(|r| r.effective_from <= execution_date)
-            .max_by_key(|r| r.effective_from);
+            .max_by_key(|r| r.effective_from)
+    }
 
-        match applicable_revision {
+    pub fn get_sql_for_date(&self, execution_date: NaiveDate) -> &str {
+        match self.get_revision_for_date(execution_date) {
             Some(rev) => &rev.sql_content,
             None => &self.sql_content,
         }
@@ -130,7 +211,63 @@ impl QueryDef {
             .max_by_key(|v| v.effective_from)
     }
 
+    /// Version that applies to a partition. Integer-range partitions carry no
+    /// date, so the version in effect today is used for them.
+    pub fn get_version_for_partition(&self, partition_key: &PartitionKey) -> Option<&VersionDef> {
+        match partition_key {
+            PartitionKey::Range(_) => self.get_version_for_date(chrono::Utc::now().date_naive()),
+            _ => self.get_version_for_date(partition_key.to_naive_date()),
+        }
+    }
+
     pub fn latest_version(&self) -> Option<&VersionDef> {
         self.versions.iter().max_by_key(|v| v.version)
     }
+
+    /// Whether a table reference extracted from SQL points at this query's destination.
+    /// Accepts `dataset.table` and `project.dataset.table`, with or without backticks.
+    /// When the destination names a project, a three-part reference must use it.
+    pub fn produces(&self, table_ref: &str) -> bool {
+        let table_ref = table_ref.replace('`', "");
+        let destination = format!("{}.{}", self.destination.dataset, self.destination.table);
+
+        if table_ref == destination {
+            return true;
+        }
+
+        match table_ref.strip_suffix(&format!(".{}", destination)) {
+            Some(project) => self.destination.project.as_deref().is_none_or(|p| p == project),
+            None => false,
+        }
+    }
+
+    /// Table references read by any version or revision of this query.
+    pub fn all_dependencies(&self) -> HashSet<String> {
+        self.versions
+            .iter()
+            .flat_map(|v| {
+                v.dependencies
+                    .iter()
+                    .chain(v.revisions.iter().flat_map(|r| r.dependencies.iter()))
+            })
+            .cloned()
+            .collect()
+    }
+
+    /// Table references read when `partition_key` is written today: those of
+    /// the version in effect for the partition, at its current revision.
+    pub fn dependencies_for_partition(&self, partition_key: &PartitionKey) -> Option<&HashSet<String>> {
+        let version = self.get_version_for_partition(partition_key)?;
+        let revision = version.get_revision_for_date(chrono::Utc::now().date_naive());
+        Some(revision.map(|r| &r.dependencies).unwrap_or(&version.dependencies))
+    }
+
+    /// Other queries whose destination tables appear in the given dependency set.
+    pub fn upstream_queries<'a>(&self, dependencies: &HashSet<String>, queries: &'a [QueryDef]) -> Vec<&'a QueryDef> {
+        queries
+            .iter()
+            .filter(|q| q.name != self.name)
+            .filter(|q| dependencies.iter().any(|dep| q.produces(dep)))
+            .collect()
+    }
 }
@@ -0,0 +1,52 @@
+//! Query definitions shared by unit tests.
+
+use std::collections::HashSet;
+use chrono::NaiveDate;
+use crate::invariant::InvariantsDef;
+use crate::schema::{PartitionConfig, Schema};
+use super::{Destination, QueryDef, VersionDef, WriteStrategy};
+
+/// A day-partitioned query writing `analytics.<name>` with one version,
+/// effective from 2024-01-01, that reads `reads`.
+pub(crate) fn create_test_query(name: &str, reads: &[&str]) -> QueryDef {
+    let effective_from = NaiveDate::from_ymd_opt(2024, 1, 1).unwrap();
+    create_test_query_with_versions(name, vec![create_test_version(1, effective_from, reads)])
+}
+
+/// Like `create_test_query`, with `versions` in place of its single version.
+pub(crate) fn create_test_query_with_versions(name: &str, versions: Vec<VersionDef>) -> QueryDef {
+    QueryDef {
+        name: name.to_string(),
+        destination: Destination {
+            project: None,
+            dataset: "analytics".to_string(),
+            table: name.to_string(),
+            partition: PartitionConfig::day("date"),
+            cluster: None,
+            write_strategy: WriteStrategy::Merge,
+            location: None,
+        },
+        description: None,
+        owner: None,
+        tags: vec![],
+        versions,
+        cluster: None,
+    }
+}
+
+/// A version with no SQL or schema that reads `reads`.
+pub(crate) fn create_test_version(version: u32, effective_from: NaiveDate, reads: &[&str]) -> VersionDef {
+    VersionDef {
+        version,
+        effective_from,
+        source: format!("v{}.sql", version),
+        sql_content: String::new(),
+        revisions: vec![],
+        description: None,
+        backfill_since: None,
+        schema: Schema::default(),
+        dependencies: reads.iter().map(|r| r.to_string()).collect::<HashSet<_>>(),
+        invariants: InvariantsDef::default(),
+        job: Default::default(),
+    }
+}
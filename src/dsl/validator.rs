@@ -1,5 +1,7 @@
 use crate::schema::BqType;
-use super::parser::QueryDef;
+use crate::executor::{referenced_params, BUILTIN_PARAMS};
+use super::parser::{QueryDef, WriteStrategy};
+use super::job::{is_valid_label_key, is_valid_label_value};
 
 #[derive(Debug, Clone)]
 pub struct ValidationResult {
@@ -41,6 +43,7 @@ impl QueryValidator {
         Self::check_cluster_fields(query, &mut errors);
         Self::check_duplicate_versions(query, &mut errors);
         Self::check_record_fields(query, &mut errors);
+        Self::check_job_settings(query, &mut errors);
         Self::check_effective_from_order(query, &mut warnings);
         Self::check_duplicate_revisions(query, &mut warnings);
         Self::check_schema_breaking_changes(query, &mut warnings);
@@ -138,6 +141,34 @@ impl QueryValidator {
         }
     }
 
+    fn check_job_settings(query: &QueryDef, errors: &mut Vec<ValidationError>) {
+        for version in &query.versions {
+            for (key, value) in &version.job.labels {
+                if !is_valid_label_key(key) || !is_valid_label_value(value) {
+                    errors.push(ValidationError {
+                        code: "E005",
+                        message: format!(
+                            "v{}: job label '{}: {}' must be lowercase letters, digits, '_' or '-' (keys starting with a letter), at most 63 characters",
+                            version.version, key, value
+                        ),
+                    });
+                }
+            }
+
+            if version.job.reservation.is_some()
+                && query.destination.write_strategy == WriteStrategy::InsertOverwriteViaJob
+            {
+                errors.push(ValidationError {
+                    code: "E006",
+                    message: format!(
+                        "v{}: job reservation can't be used with the insert_overwrite_via_job write strategy",
+                        version.version
+                    ),
+                });
+            }
+        }
+    }
+
     fn check_effective_from_order(query: &QueryDef, warnings: &mut Vec<ValidationWarning>) {
         let mut sorted = query.versions.clone();
         sorted.sort_by_key(|v| v.version);
@@ -215,26 +246,22 @@ impl QueryValidator {
 
     fn check_sql_partition_placeholder(query: &QueryDef, warnings: &mut Vec<ValidationWarning>) {
         for version in &query.versions {
-            if !version.sql_content.contains("@partition_date")
-                && !version.sql_content.contains("@run_date")
-                && !version.sql_content.contains("@execution_date") {
+            if !references_builtin_param(&version.sql_content) {
                 warnings.push(ValidationWarning {
                     code: "W005",
                     message: format!(
-                        "v{}: SQL does not contain @partition_date placeholder",
+                        "v{}: SQL does not reference @partition_date or another built-in parameter",
                         version.version
                     ),
                 });
             }
 
             for revision in &version.revisions {
-                if !revision.sql_content.contains("@partition_date")
-                    && !revision.sql_content.contains("@run_date")
-                    && !revision.sql_content.contains("@execution_date") {
+                if !references_builtin_param(&revision.sql_content) {
                     warnings.push(ValidationWarning {
                         code: "W005",
                         message: format!(
-                            "v{}.r{}: SQL does not contain @partition_date placeholder",
+                            "v{}.r{}: SQL does not reference @partition_date or another built-in parameter",
                             version.version, revision.revision
                         ),
                     });
@@ -255,6 +282,15 @@ impl QueryValidator {
     }
 }
 
+/// Whether `sql` filters on the partition through any of `BUILTIN_PARAMS`
+/// (or the older `@run_date` / `@execution_date` names). Mentions in strings
+/// and comments don't count.
+fn references_builtin_param(sql: &str) -> bool {
+    referenced_params(sql).iter().any(|name| {
+        BUILTIN_PARAMS.contains(&name.as_str()) || name == "run_date" || name == "execution_date"
+    })
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -278,4 +314,26 @@ mod tests {
 
         assert!(result.is_valid());
     }
+
+    #[test]
+    fn test_invalid_job_label() {
+        let loader = QueryLoader::new();
+        let mut query = loader.load_query(Path::new("tests/fixtures/analytics/simple_query.yaml")).unwrap();
+        query.versions[0].job.labels.insert("Team".to_string(), "finance".to_string());
+
+        let result = QueryValidator::validate(&query);
+        assert!(!result.is_valid());
+        assert_eq!(result.errors[0].code, "E005");
+    }
+
+    #[test]
+    fn test_references_builtin_param() {
+        assert!(references_builtin_param("SELECT * FROM t WHERE date = @partition_date"));
+        assert!(references_builtin_param("SELECT * FROM t WHERE ts >= @partition_start AND ts < @partition_end"));
+        assert!(references_builtin_param("SELECT * FROM t WHERE bucket BETWEEN @range_start AND @range_end - 1"));
+        assert!(references_builtin_param("SELECT @run_id AS run_id, * FROM t"));
+        assert!(!references_builtin_param("SELECT * FROM t"));
+        assert!(!references_builtin_param("SELECT * FROM t WHERE date = @partition_dates"));
+        assert!(!references_builtin_param("SELECT '@partition_date' AS label FROM t -- @partition_date"));
+    }
 }
@@ -54,6 +54,8 @@ pub enum BigQueryError {
     },
 
     InvalidCredentials {
+        /// The credential source that failed, e.g. `service account key /path/key.json`.
+        credentials: Option<String>,
         path: Option<String>,
         reason: String,
     },
@@ -162,7 +164,8 @@ impl BigQueryError {
                     .unwrap_or_default();
                 format!(
                     "Invalid credentials{path_info}:\n  \
-                     • Check GOOGLE_APPLICATION_CREDENTIALS path\n  \
+                     • Check GOOGLE_APPLICATION_CREDENTIALS path, or the file given with\n    \
+                       --service-account-key / --authorized-user\n  \
                      • Verify the service account key is valid\n  \
                      • Run: gcloud auth application-default login"
                 )
@@ -263,8 +266,12 @@ impl fmt::Display for BigQueryError {
                 write!(f, "Connection failed: {reason}")
             }
 
-            BigQueryError::InvalidCredentials { reason, path } => {
-                write!(f, "Invalid credentials: {reason}")?;
+            BigQueryError::InvalidCredentials { credentials, path, reason } => {
+                write!(f, "Invalid credentials")?;
+                if let Some(c) = credentials {
+                    write!(f, " from {c}")?;
+                }
+                write!(f, ": {reason}")?;
                 if let Some(p) = path {
                     write!(f, " (path: {p})")?;
                 }
@@ -342,6 +349,7 @@ mod tests {
         }.error_code(), "CONNECTION_FAILED");
 
         assert_eq!(BigQueryError::InvalidCredentials {
+            credentials: None,
             path: None,
             reason: "r".into(),
         }.error_code(), "INVALID_CREDENTIALS");
@@ -485,6 +493,7 @@ mod tests {
     #[test]
     fn test_display_invalid_credentials_with_path() {
         let err = BigQueryError::InvalidCredentials {
+            credentials: None,
             path: Some("/path/to/key.json".into()),
             reason: "File not found".into(),
         };
@@ -493,6 +502,19 @@ mod tests {
         assert!(display.contains("path: /path/to/key.json"));
     }
 
+    #[test]
+    fn test_display_invalid_credentials_names_source() {
+        let err = BigQueryError::InvalidCredentials {
+            credentials: Some("service account key /keys/etl.json".into()),
+            path: Some("/keys/etl.json".into()),
+            reason: "File not found".into(),
+        };
+        assert_eq!(
+            err.to_string(),
+            "Invalid credentials from service account key /keys/etl.json: File not found (path: /keys/etl.json)"
+        );
+    }
+
     #[test]
     fn test_display_unknown_with_code() {
         let err = BigQueryError::Unknown {
@@ -582,6 +604,7 @@ mod tests {
     #[test]
     fn test_suggestion_invalid_credentials_with_path() {
         let err = BigQueryError::InvalidCredentials {
+            credentials: None,
             path: Some("/my/path.json".into()),
             reason: "invalid".into(),
         };
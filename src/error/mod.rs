@@ -4,7 +4,7 @@ mod parser;
 use thiserror::Error;
 
 pub use bq_error::{BigQueryError, QueryErrorLocation};
-pub use parser::{parse_bq_error, ErrorContext};
+pub use parser::{parse_bq_error, parse_job_error, ErrorContext};
 
 #[derive(Error, Debug)]
 pub enum BqDriftError {
@@ -56,6 +56,15 @@ pub enum BqDriftError {
     #[error("Executor error: {0}")]
     Executor(String),
 
+    #[error("Dependency cycle: {0}")]
+    DependencyCycle(String),
+
+    #[error("Cancelled: {0}")]
+    Cancelled(String),
+
+    #[error("Byte budget exceeded: {0}")]
+    BudgetExceeded(String),
+
     #[error("IO error: {0}")]
     Io(#[from] std::io::Error),
 
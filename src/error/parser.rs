@@ -14,35 +14,37 @@ pub fn parse_bq_error(error: BQError, context: ErrorContext) -> BigQueryError {
 
         BQError::NoToken => {
             BigQueryError::AuthenticationFailed {
-                reason: "No authentication token available".to_string(),
+                reason: with_credentials("No authentication token available", &context),
                 help: "Ensure you are authenticated with GCP".to_string(),
             }
         }
 
         BQError::AuthError(auth_err) => {
             BigQueryError::AuthenticationFailed {
-                reason: format!("{:?}", auth_err),
+                reason: with_credentials(&format!("{:?}", auth_err), &context),
                 help: "Check your authentication configuration".to_string(),
             }
         }
 
         BQError::YupAuthError(yup_err) => {
             BigQueryError::AuthenticationFailed {
-                reason: yup_err.to_string(),
+                reason: with_credentials(&yup_err.to_string(), &context),
                 help: "OAuth authentication failed".to_string(),
             }
         }
 
         BQError::InvalidServiceAccountKey(io_err) => {
             BigQueryError::InvalidCredentials {
-                path: std::env::var("GOOGLE_APPLICATION_CREDENTIALS").ok(),
+                credentials: context.credentials.clone(),
+                path: credentials_path(&context),
                 reason: io_err.to_string(),
             }
         }
 
         BQError::InvalidServiceAccountAuthenticator(io_err) => {
             BigQueryError::InvalidCredentials {
-                path: std::env::var("GOOGLE_APPLICATION_CREDENTIALS").ok(),
+                credentials: context.credentials.clone(),
+                path: credentials_path(&context),
                 reason: io_err.to_string(),
             }
         }
@@ -98,24 +100,63 @@ pub fn parse_bq_error(error: BQError, context: ErrorContext) -> BigQueryError {
     }
 }
 
+/// `reason`, naming the configured credentials when they are known.
+fn with_credentials(reason: &str, context: &ErrorContext) -> String {
+    match &context.credentials {
+        Some(credentials) => format!("{} (using {})", reason, credentials),
+        None => reason.to_string(),
+    }
+}
+
+/// The credentials file of the context, or `GOOGLE_APPLICATION_CREDENTIALS`
+/// when the context doesn't say which credentials were used.
+fn credentials_path(context: &ErrorContext) -> Option<String> {
+    match &context.credentials {
+        Some(_) => context.credentials_path.clone(),
+        None => std::env::var("GOOGLE_APPLICATION_CREDENTIALS").ok(),
+    }
+}
+
+/// Classify the `errorResult` of a job that finished with an error, the way
+/// `parse_bq_error` classifies a failed API call with the same reason. Jobs
+/// carry no HTTP status, so the one BigQuery documents for the reason is used.
+pub fn parse_job_error(reason: Option<&str>, message: &str, context: ErrorContext) -> BigQueryError {
+    let status = match reason {
+        Some("accessDenied" | "billingNotEnabled" | "quotaExceeded" | "rateLimitExceeded" | "responseTooLarge") => 403,
+        Some("notFound") => 404,
+        Some("duplicate") => 409,
+        Some("backendError" | "internalError") => 500,
+        _ => 400,
+    };
+    let raw_error = format!("reason: {}, message: {}", reason.unwrap_or("none"), message);
+
+    classify_error(status, reason, message, raw_error, context)
+}
+
 fn parse_response_error(
     resp: &ResponseError,
     context: ErrorContext,
 ) -> BigQueryError {
-    let status = resp.error.code;
-    let message = &resp.error.message;
-    let errors = &resp.error.errors;
-
     // Get the first error reason if available (errors is Vec<HashMap<String, String>>)
-    let reason = errors.first().and_then(|e| e.get("reason").map(|s| s.as_str()));
+    let reason = resp.error.errors.first().and_then(|e| e.get("reason").map(|s| s.as_str()));
+
+    classify_error(resp.error.code, reason, &resp.error.message, format!("{:?}", resp), context)
+}
 
+fn classify_error(
+    status: i64,
+    reason: Option<&str>,
+    message: &str,
+    raw_error: String,
+    context: ErrorContext,
+) -> BigQueryError {
     match (status, reason) {
         // 400 Bad Request
         (400, Some("invalidQuery")) => {
             let location = extract_query_location(message);
             BigQueryError::InvalidQuery {
                 sql_preview: context.sql.unwrap_or_default(),
-                message: message.clone(),
+                message: message.to_string(),
                 location,
             }
         }
@@ -126,21 +167,21 @@ fn parse_response_error(
                message.to_lowercase().contains("query") {
                 BigQueryError::InvalidQuery {
                     sql_preview: context.sql.unwrap_or_default(),
-                    message: message.clone(),
+                    message: message.to_string(),
                     location: extract_query_location(message),
                 }
             } else {
                 BigQueryError::Unknown {
                     code: Some("invalid".to_string()),
-                    message: message.clone(),
-                    raw_error: format!("{:?}", resp),
+                    message: message.to_string(),
+                    raw_error,
                 }
             }
         }
 
         (400, Some("resourcesExceeded")) => {
             BigQueryError::ResourcesExceeded {
-                message: message.clone(),
+                message: message.to_string(),
                 suggestion: "Try:\n  \
                     • Add filters to reduce data scanned\n  \
                     • Use LIMIT clause for testing\n  \
@@ -160,7 +201,7 @@ fn parse_response_error(
             BigQueryError::Unknown {
                 code: Some("backendError".to_string()),
                 message: format!("BigQuery backend error: {}", message),
-                raw_error: format!("{:?}", resp),
+                raw_error,
             }
         }
 
@@ -178,13 +219,13 @@ fn parse_response_error(
             let quota_type = extract_quota_type(message).unwrap_or_else(|| "API".to_string());
             BigQueryError::QuotaExceeded {
                 quota_type,
-                message: message.clone(),
+                message: message.to_string(),
             }
         }
 
         (403, Some("responseTooLarge")) => {
             BigQueryError::ResourcesExceeded {
-                message: message.clone(),
+                message: message.to_string(),
                 suggestion: "Response too large. Try:\n  \
                     • Add LIMIT clause\n  \
                     • Export to GCS instead\n  \
@@ -202,7 +243,7 @@ fn parse_response_error(
             BigQueryError::Unknown {
                 code: Some("duplicate".to_string()),
                 message: format!("Resource already exists: {}", message),
-                raw_error: format!("{:?}", resp),
+                raw_error,
             }
         }
 
@@ -211,15 +252,15 @@ fn parse_response_error(
             BigQueryError::Unknown {
                 code: Some(format!("HTTP_{}", status)),
                 message: format!("BigQuery server error: {}", message),
-                raw_error: format!("{:?}", resp),
+                raw_error,
             }
         }
 
         // Default
         _ => BigQueryError::Unknown {
             code: reason.map(|s| s.to_string()),
-            message: message.clone(),
-            raw_error: format!("{:?}", resp),
+            message: message.to_string(),
+            raw_error,
         }
     }
 }
@@ -337,6 +378,10 @@ pub struct ErrorContext {
     pub project: Option<String>,
     pub dataset: Option<String>,
     pub table: Option<String>,
+    /// The credential source the failing call was made with, e.g.
+    /// `service account key /path/key.json`.
+    pub credentials: Option<String>,
+    pub credentials_path: Option<String>,
 }
 
 impl ErrorContext {
@@ -360,6 +405,12 @@ impl ErrorContext {
         self
     }
 
+    pub fn with_credentials(mut self, credentials: impl Into<String>, path: Option<String>) -> Self {
+        self.credentials = Some(credentials.into());
+        self.credentials_path = path;
+        self
+    }
+
     pub fn with_table(mut self, project: impl Into<String>, dataset: impl Into<String>, table: impl Into<String>) -> Self {
         self.project = Some(project.into());
         self.dataset = Some(dataset.into());
@@ -575,4 +626,39 @@ mod tests {
             panic!("Expected Unknown, got {:?}", err);
         }
     }
+
+    #[test]
+    fn test_invalid_key_names_configured_credentials() {
+        let ctx = ErrorContext::new()
+            .with_credentials("service account key /secrets/key.json", Some("/secrets/key.json".to_string()));
+        let io_err = std::io::Error::new(std::io::ErrorKind::NotFound, "no such file");
+        let err = parse_bq_error(BQError::InvalidServiceAccountKey(io_err), ctx);
+
+        if let BigQueryError::InvalidCredentials { credentials, path, .. } = err {
+            assert_eq!(credentials.as_deref(), Some("service account key /secrets/key.json"));
+            assert_eq!(path.as_deref(), Some("/secrets/key.json"));
+        } else {
+            panic!("Expected InvalidCredentials, got {:?}", err);
+        }
+    }
+
+    #[test]
+    fn test_missing_token_names_configured_credentials() {
+        let ctx = ErrorContext::new().with_credentials("impersonation of ops@p.iam.gserviceaccount.com", None);
+        let err = parse_bq_error(BQError::NoToken, ctx);
+
+        assert!(err.to_string().contains("using impersonation of ops@p.iam.gserviceaccount.com"));
+    }
+
+    #[test]
+    fn test_parse_job_error_matches_api_classification() {
+        let err = parse_job_error(Some("invalidQuery"), "Unrecognized name: regio at [3:5]", ErrorContext::new());
+        assert!(matches!(err, BigQueryError::InvalidQuery { .. }), "got {:?}", err);
+
+        let err = parse_job_error(Some("rateLimitExceeded"), "Exceeded rate limits", ErrorContext::new());
+        assert_eq!(err.error_code(), "QUOTA_EXCEEDED");
+
+        let err = parse_job_error(Some("resourcesExceeded"), "Resources exceeded during query execution", ErrorContext::new());
+        assert_eq!(err.error_code(), "RESOURCES_EXCEEDED");
+    }
 }
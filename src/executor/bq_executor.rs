@@ -1,21 +1,34 @@
 pub use bq_runner::{Executor, ExecutorMode, QueryResult, ColumnDef, ColumnInfo};
 
 use chrono::{NaiveDate, Utc};
+use std::sync::atomic::AtomicBool;
 use crate::error::{BqDriftError, Result};
-use crate::dsl::QueryDef;
+use crate::dsl::{Destination, QueryDef};
+use crate::invariant::{InvariantChecker, InvariantReport, CheckStatus, Severity, resolve_invariants_def};
 use crate::schema::PartitionKey;
+use super::params::{QueryParams, RunContext};
+use super::runner::{partition_keys, schedule_backfill, BackfillOptions, RunSkip, SkipReason};
+use super::scratch::ScratchWriter;
+use super::strategy::{build_merge_sql, WriteTarget};
 
 #[derive(Debug)]
 pub struct ExecutorRunReport {
     pub stats: Vec<ExecutorWriteStats>,
     pub failures: Vec<ExecutorRunFailure>,
+    /// Partitions a backfill didn't attempt after reaching `max_failures`.
+    pub skipped: Vec<RunSkip>,
 }
 
 #[derive(Debug)]
 pub struct ExecutorWriteStats {
     pub query_name: String,
     pub partition_key: PartitionKey,
+    /// Table the partition was written to: the destination, or its scratch
+    /// table in scratch mode.
+    pub table: String,
     pub rows_affected: u64,
+    /// `None` when invariants were skipped.
+    pub invariant_report: Option<InvariantReport>,
 }
 
 #[derive(Debug)]
@@ -28,11 +41,36 @@ pub struct ExecutorRunFailure {
 pub struct ExecutorRunner<'a> {
     executor: &'a Executor,
     queries: Vec<QueryDef>,
+    run: RunContext,
+    run_invariants: bool,
+    scratch_dataset: Option<String>,
 }
 
 impl<'a> ExecutorRunner<'a> {
     pub fn new(executor: &'a Executor, queries: Vec<QueryDef>) -> Self {
-        Self { executor, queries }
+        Self { executor, queries, run: RunContext::new(), run_invariants: true, scratch_dataset: None }
+    }
+
+    /// Write each partition to a scratch table in `dataset`, named like
+    /// `ScratchWriter`'s (`<dataset>__<table>`), instead of the destination.
+    /// The table is created from the version's schema when missing, and
+    /// invariants run against it. Executors have no table expiration, so
+    /// scratch tables are left for the caller to drop.
+    pub fn with_scratch_dataset(mut self, dataset: impl Into<String>) -> Self {
+        self.scratch_dataset = Some(dataset.into());
+        self
+    }
+
+    /// Write partitions without running the version's before/after invariants.
+    pub fn with_skip_invariants(mut self, skip: bool) -> Self {
+        self.run_invariants = !skip;
+        self
+    }
+
+    /// Bind `@run_id` and `@run_time` from `run` instead of a fresh context.
+    pub fn with_run_context(mut self, run: RunContext) -> Self {
+        self.run = run;
+        self
     }
 
     pub fn mode(&self) -> ExecutorMode {
@@ -63,7 +101,7 @@ impl<'a> ExecutorRunner<'a> {
             }
         }
 
-        Ok(ExecutorRunReport { stats, failures })
+        Ok(ExecutorRunReport { stats, failures, skipped: Vec::new() })
     }
 
     pub async fn run_query(&self, query_name: &str, date: NaiveDate) -> Result<ExecutorWriteStats> {
@@ -101,6 +139,17 @@ impl<'a> ExecutorRunner<'a> {
         from: PartitionKey,
         to: PartitionKey,
         interval: Option<i64>,
+    ) -> Result<ExecutorRunReport> {
+        self.backfill_partitions_with(query_name, from, to, interval, &BackfillOptions::default()).await
+    }
+
+    pub async fn backfill_partitions_with(
+        &self,
+        query_name: &str,
+        from: PartitionKey,
+        to: PartitionKey,
+        interval: Option<i64>,
+        options: &BackfillOptions,
     ) -> Result<ExecutorRunReport> {
         let query = self.queries
             .iter()
@@ -109,26 +158,33 @@ impl<'a> ExecutorRunner<'a> {
                 format!("Query '{}' not found", query_name)
             ))?;
 
-        let mut stats = Vec::new();
-        let mut failures = Vec::new();
-        let mut current = from;
+        let keys = partition_keys(from, &to, interval);
+        let stopped = AtomicBool::new(false);
+        let mut failed = 0;
 
-        while current <= to {
-            match self.execute_query(query, current.clone()).await {
-                Ok(s) => stats.push(s),
-                Err(e) => failures.push(ExecutorRunFailure {
+        let results = schedule_backfill(keys, options, &stopped, &mut failed, move |key| {
+            self.execute_query(query, key)
+        }).await;
+
+        let mut report = ExecutorRunReport { stats: Vec::new(), failures: Vec::new(), skipped: Vec::new() };
+
+        for (key, result) in results {
+            match result {
+                Some(Ok(s)) => report.stats.push(s),
+                Some(Err(e)) => report.failures.push(ExecutorRunFailure {
                     query_name: query_name.to_string(),
-                    partition_key: current.clone(),
+                    partition_key: key,
                     error: e.to_string(),
                 }),
+                None => report.skipped.push(RunSkip {
+                    query_name: query_name.to_string(),
+                    partition_key: key,
+                    reason: SkipReason::MaxFailures(options.max_failures.unwrap_or(failed)),
+                }),
             }
-            current = match interval {
-                Some(i) => current.next_by(i),
-                None => current.next(),
-            };
         }
 
-        Ok(ExecutorRunReport { stats, failures })
+        Ok(report)
     }
 
     pub fn queries(&self) -> &[QueryDef] {
@@ -149,93 +205,83 @@ impl<'a> ExecutorRunner<'a> {
             .map_err(|e| BqDriftError::Executor(e.to_string()))
     }
 
+    /// Write one partition, running the version's before checks first and
+    /// its after checks once the write succeeds, all through the executor.
+    /// A failed before check with error severity aborts the write.
     async fn execute_query(&self, query_def: &QueryDef, partition_key: PartitionKey) -> Result<ExecutorWriteStats> {
-        let partition_date = partition_key.to_naive_date();
         let version = query_def
-            .get_version_for_date(partition_date)
+            .get_version_for_partition(&partition_key)
             .ok_or_else(|| BqDriftError::Partition(
                 format!("No version found for partition {}", partition_key)
             ))?;
 
+        let destination = match &self.scratch_dataset {
+            Some(dataset) => {
+                let scratch = Destination {
+                    project: None,
+                    dataset: dataset.clone(),
+                    table: ScratchWriter::scratch_table_name(query_def),
+                    ..query_def.destination.clone()
+                };
+                self.execute_sql(&version.schema.to_create_ddl(&scratch.table_ref())).await?;
+                scratch
+            }
+            None => query_def.destination.clone(),
+        };
+
+        let (before_checks, after_checks) = if self.run_invariants {
+            resolve_invariants_def(&version.invariants)
+        } else {
+            (Vec::new(), Vec::new())
+        };
+        let checker = InvariantChecker::new(self.executor, &destination, &partition_key)
+            .with_run_context(&self.run);
+        let mut invariant_report = InvariantReport::default();
+
+        if !before_checks.is_empty() {
+            let results = checker.run_checks(&before_checks).await?;
+
+            let has_error = results.iter().any(|r| {
+                r.status == CheckStatus::Failed && r.severity == Severity::Error
+            });
+
+            invariant_report.before = results;
+
+            if has_error {
+                return Err(BqDriftError::InvariantFailed(
+                    "Before invariant check(s) failed with error severity".to_string()
+                ));
+            }
+        }
+
         let sql = version.get_sql_for_date(Utc::now().date_naive());
-        let full_sql = self.build_merge_sql(query_def, sql, &partition_key);
+        let target = WriteTarget {
+            project: destination.project.as_deref(),
+            dataset: &destination.dataset,
+            table: &destination.table,
+            partition_field: destination.partition.field.as_deref().unwrap_or("date"),
+        };
+        // The executor takes plain SQL, so parameters are bound as typed literals.
+        let sql = QueryParams::for_partition(&partition_key, &destination.partition, &self.run).inline(sql);
+        let full_sql = build_merge_sql(&target, &sql, &partition_key);
 
         let rows_affected = self.executor
             .execute(&full_sql)
             .await
             .map_err(|e| BqDriftError::Executor(e.to_string()))?;
 
+        if !after_checks.is_empty() {
+            invariant_report.after = checker.run_checks(&after_checks).await?;
+        }
+
         Ok(ExecutorWriteStats {
             query_name: query_def.name.clone(),
             partition_key,
+            table: destination.table_ref(),
             rows_affected,
+            invariant_report: if self.run_invariants { Some(invariant_report) } else { None },
         })
     }
-
-    fn build_merge_sql(
-        &self,
-        query_def: &QueryDef,
-        sql: &str,
-        partition_key: &PartitionKey,
-    ) -> String {
-        let dest_table = format!(
-            "{}.{}",
-            query_def.destination.dataset,
-            query_def.destination.table
-        );
-
-        let partition_field = query_def
-            .destination
-            .partition
-            .field
-            .as_deref()
-            .unwrap_or("date");
-
-        let parameterized_sql = sql.replace("@partition_date", &format!("'{}'", partition_key.sql_value()));
-
-        let partition_condition = match partition_key {
-            PartitionKey::Hour(_) => format!(
-                "TIMESTAMP_TRUNC(target.{}, HOUR) = {}",
-                partition_field,
-                partition_key.sql_literal()
-            ),
-            PartitionKey::Day(_) => format!(
-                "target.{} = {}",
-                partition_field,
-                partition_key.sql_literal()
-            ),
-            PartitionKey::Month { .. } => format!(
-                "DATE_TRUNC(target.{}, MONTH) = {}",
-                partition_field,
-                partition_key.sql_literal()
-            ),
-            PartitionKey::Year(_) => format!(
-                "DATE_TRUNC(target.{}, YEAR) = {}",
-                partition_field,
-                partition_key.sql_literal()
-            ),
-            PartitionKey::Range(_) => format!(
-                "target.{} = {}",
-                partition_field,
-                partition_key.sql_literal()
-            ),
-        };
-
-        format!(
-            r#"
-            MERGE `{dest_table}` AS target
-            USING (
-                {parameterized_sql}
-            ) AS source
-            ON FALSE
-            WHEN NOT MATCHED BY SOURCE AND {partition_condition} THEN DELETE
-            WHEN NOT MATCHED BY TARGET THEN INSERT ROW
-            "#,
-            dest_table = dest_table,
-            parameterized_sql = parameterized_sql,
-            partition_condition = partition_condition,
-        )
-    }
 }
 
 pub fn create_mock_executor() -> Result<Executor> {
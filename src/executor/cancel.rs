@@ -0,0 +1,98 @@
+use std::sync::Arc;
+use tokio::sync::watch;
+use tracing::warn;
+
+/// Asks in-flight BigQuery jobs to stop. Clones share the same signal, so a
+/// signal set on a `ClientConfig` reaches every client built from it.
+///
+/// Once cancelled, running jobs are cancelled through the jobs.cancel API and
+/// no new jobs are started. A signal can't be reset.
+#[derive(Debug, Clone)]
+pub struct CancelSignal {
+    sender: Arc<watch::Sender<bool>>,
+}
+
+impl Default for CancelSignal {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl CancelSignal {
+    pub fn new() -> Self {
+        let (sender, _) = watch::channel(false);
+        Self { sender: Arc::new(sender) }
+    }
+
+    pub fn cancel(&self) {
+        self.sender.send_replace(true);
+    }
+
+    pub fn is_cancelled(&self) -> bool {
+        *self.sender.borrow()
+    }
+
+    /// Resolves once `cancel` has been called.
+    pub async fn cancelled(&self) {
+        let mut receiver = self.sender.subscribe();
+        // The sender lives as long as `self`, so this can't fail.
+        let _ = receiver.wait_for(|cancelled| *cancelled).await;
+    }
+
+    /// Cancel on the first SIGINT (Ctrl-C) or SIGTERM. A second signal exits
+    /// immediately without waiting for jobs to be cancelled.
+    pub fn cancel_on_shutdown(&self) {
+        let signal = self.clone();
+
+        tokio::spawn(async move {
+            shutdown_signal().await;
+            warn!("Interrupted: cancelling running BigQuery jobs (interrupt again to exit immediately)");
+            signal.cancel();
+
+            shutdown_signal().await;
+            std::process::exit(130);
+        });
+    }
+}
+
+async fn shutdown_signal() {
+    #[cfg(unix)]
+    {
+        use tokio::signal::unix::{signal, SignalKind};
+
+        match signal(SignalKind::terminate()) {
+            Ok(mut terminate) => {
+                tokio::select! {
+                    _ = tokio::signal::ctrl_c() => {}
+                    _ = terminate.recv() => {}
+                }
+            }
+            Err(_) => {
+                let _ = tokio::signal::ctrl_c().await;
+            }
+        }
+    }
+
+    #[cfg(not(unix))]
+    {
+        let _ = tokio::signal::ctrl_c().await;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[tokio::test]
+    async fn test_clones_share_the_signal() {
+        let signal = CancelSignal::new();
+        let clone = signal.clone();
+        assert!(!clone.is_cancelled());
+
+        let waiter = tokio::spawn(async move { clone.cancelled().await });
+        signal.cancel();
+
+        waiter.await.unwrap();
+        assert!(signal.is_cancelled());
+    }
+}
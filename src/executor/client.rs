@@ -1,38 +1,242 @@
 use chrono::{DateTime, Utc};
+use std::collections::HashMap;
+use std::future::Future;
+use std::time::{Duration, Instant};
+use tracing::{debug, warn};
 use gcp_bigquery_client::Client;
+use gcp_bigquery_client::error::BQError;
 use gcp_bigquery_client::model::dataset::Dataset;
 use gcp_bigquery_client::model::field_type::FieldType;
+use gcp_bigquery_client::model::get_query_results_parameters::GetQueryResultsParameters;
+use gcp_bigquery_client::model::job::Job;
+use gcp_bigquery_client::model::job_configuration::JobConfiguration;
+use gcp_bigquery_client::model::job_configuration_query::JobConfigurationQuery;
+use gcp_bigquery_client::model::job_reference::JobReference;
 use gcp_bigquery_client::model::query_request::QueryRequest;
+use gcp_bigquery_client::model::query_response::QueryResponse;
+use gcp_bigquery_client::model::range_partitioning::RangePartitioning;
 use gcp_bigquery_client::model::table::Table;
 use gcp_bigquery_client::model::table_field_schema::TableFieldSchema;
+use gcp_bigquery_client::model::table_row::TableRow;
 use gcp_bigquery_client::model::table_schema::TableSchema;
+use gcp_bigquery_client::model::table_reference::TableReference;
 use gcp_bigquery_client::model::time_partitioning::TimePartitioning;
 use gcp_bigquery_client::model::clustering::Clustering;
-use crate::error::{BqDriftError, Result, parse_bq_error, ErrorContext};
-use crate::schema::{BqType, Field, FieldMode, Schema, PartitionConfig, PartitionType, ClusterConfig};
-use crate::dsl::QueryDef;
+use crate::error::{BqDriftError, BigQueryError, Result, parse_bq_error, parse_job_error, ErrorContext};
+use crate::schema::{BqType, Field, FieldMode, Schema, TableDefinition, PartitionConfig, PartitionType, ClusterConfig};
+use crate::dsl::{Destination, JobConfig, QueryDef};
+use super::cancel::CancelSignal;
+use super::credentials::Credentials;
+use super::params::QueryParams;
+use super::retry::RetryPolicy;
+
+/// Connection-level settings shared by every call a `BqClient` makes.
+#[derive(Debug, Clone, Default)]
+pub struct ClientConfig {
+    pub retry: RetryPolicy,
+    /// Sent as `maximumBytesBilled` on every query job; BigQuery fails jobs
+    /// that would bill more instead of running them.
+    pub maximum_bytes_billed: Option<i64>,
+    /// Jobs still running after this long are cancelled and fail with
+    /// `BqDriftError::Cancelled`.
+    pub job_timeout: Option<Duration>,
+    /// Cancels running jobs, and refuses new ones, once triggered.
+    pub cancel: CancelSignal,
+    /// Location of the datasets bqdrift creates and of the jobs it runs, e.g.
+    /// `EU`. BigQuery's default applies when unset.
+    pub location: Option<String>,
+    pub credentials: Credentials,
+    /// Root of the BigQuery v2 REST API, e.g. `http://localhost:9050` for an
+    /// emulator. Google's endpoint is used when unset.
+    pub endpoint: Option<String>,
+}
+
+impl ClientConfig {
+    pub fn with_retry(mut self, retry: RetryPolicy) -> Self {
+        self.retry = retry;
+        self
+    }
+
+    pub fn with_maximum_bytes_billed(mut self, bytes: Option<i64>) -> Self {
+        self.maximum_bytes_billed = bytes;
+        self
+    }
+
+    pub fn with_job_timeout(mut self, timeout: Option<Duration>) -> Self {
+        self.job_timeout = timeout;
+        self
+    }
+
+    pub fn with_cancel_signal(mut self, cancel: CancelSignal) -> Self {
+        self.cancel = cancel;
+        self
+    }
+
+    pub fn with_location(mut self, location: Option<String>) -> Self {
+        self.location = location;
+        self
+    }
+
+    pub fn with_credentials(mut self, credentials: Credentials) -> Self {
+        self.credentials = credentials;
+        self
+    }
+
+    pub fn with_endpoint(mut self, endpoint: Option<String>) -> Self {
+        self.endpoint = endpoint;
+        self
+    }
+}
+
+/// What happened while running a query job, as reported by BigQuery.
+/// Figures BigQuery didn't report are `None`.
+#[derive(Debug, Clone, Default)]
+pub struct JobStats {
+    pub job_id: Option<String>,
+    /// Rows inserted, updated or deleted by a DML statement.
+    pub rows_affected: Option<i64>,
+    /// Rows inserted by a DML statement, from its DML statistics.
+    pub rows_inserted: Option<i64>,
+    pub bytes_processed: Option<i64>,
+    pub bytes_billed: Option<i64>,
+    pub slot_ms: Option<i64>,
+    /// Wall-clock time from submitting the job to receiving its statistics,
+    /// retries included.
+    pub elapsed_ms: u64,
+    /// Attempts that failed with a retryable error before the job went through.
+    pub retries: u32,
+}
+
+impl JobStats {
+    /// Fold in the statistics of another job run for the same write. Counters
+    /// are summed; the job id becomes the other job's.
+    pub fn accumulate(&mut self, other: &JobStats) {
+        self.job_id = other.job_id.clone().or(self.job_id.take());
+        self.rows_affected = sum_counts(self.rows_affected, other.rows_affected);
+        self.rows_inserted = sum_counts(self.rows_inserted, other.rows_inserted);
+        self.bytes_processed = sum_counts(self.bytes_processed, other.bytes_processed);
+        self.bytes_billed = sum_counts(self.bytes_billed, other.bytes_billed);
+        self.slot_ms = sum_counts(self.slot_ms, other.slot_ms);
+        self.elapsed_ms += other.elapsed_ms;
+        self.retries += other.retries;
+    }
+}
+
+/// A client-generated job id, unique per logical job.
+fn new_job_id() -> String {
+    format!("bqdrift_{}", uuid::Uuid::new_v4().simple())
+}
+
+/// BigQuery answers 409 when a job id is reused.
+fn is_duplicate(error: &BQError) -> bool {
+    matches!(error, BQError::ResponseError { error } if error.error.code == 409)
+}
+
+fn sum_counts(a: Option<i64>, b: Option<i64>) -> Option<i64> {
+    match (a, b) {
+        (Some(a), Some(b)) => Some(a + b),
+        (a, b) => a.or(b),
+    }
+}
+
+/// BigQuery reports int64 statistics as decimal strings.
+fn parse_count(value: Option<&str>) -> Option<i64> {
+    value.and_then(|v| v.parse().ok())
+}
 
 #[derive(Clone)]
 pub struct BqClient {
     client: Client,
     project_id: String,
+    config: ClientConfig,
+    /// Settings from the `job:` block of the query being written.
+    job: JobConfig,
+    /// Project of the tables this client reads and creates, when it differs
+    /// from `project_id`, which jobs run (and are billed) in.
+    table_project: Option<String>,
 }
 
 impl BqClient {
     pub async fn new(project_id: impl Into<String>) -> Result<Self> {
-        let client = Client::from_application_default_credentials()
-            .await
-            .map_err(|e| {
-                let ctx = ErrorContext::new().with_operation("client_init");
-                BqDriftError::BigQuery(parse_bq_error(e, ctx))
-            })?;
+        Self::connect(project_id, ClientConfig::default()).await
+    }
+
+    /// Connect with `config.credentials`, to `config.endpoint` when set.
+    pub async fn connect(project_id: impl Into<String>, config: ClientConfig) -> Result<Self> {
+        let mut client = config.credentials.client().await?;
+        if let Some(endpoint) = &config.endpoint {
+            client.v2_base_url(endpoint.trim_end_matches('/').to_string());
+        }
 
         Ok(Self {
             client,
             project_id: project_id.into(),
+            config,
+            job: JobConfig::default(),
+            table_project: None,
         })
     }
 
+    pub fn with_retry_policy(mut self, retry: RetryPolicy) -> Self {
+        self.config.retry = retry;
+        self
+    }
+
+    pub fn with_maximum_bytes_billed(mut self, bytes: Option<i64>) -> Self {
+        self.config.maximum_bytes_billed = bytes;
+        self
+    }
+
+    pub fn with_job_timeout(mut self, timeout: Option<Duration>) -> Self {
+        self.config.job_timeout = timeout;
+        self
+    }
+
+    pub fn with_cancel_signal(mut self, cancel: CancelSignal) -> Self {
+        self.config.cancel = cancel;
+        self
+    }
+
+    /// Apply a query's job settings to every job this client submits. A
+    /// per-query `maximum_bytes_billed` can only lower the client's budget,
+    /// and a per-query `timeout` replaces `job_timeout`.
+    pub fn with_job_config(mut self, job: JobConfig) -> Self {
+        self.job = job;
+        self
+    }
+
+    pub fn with_location(mut self, location: Option<String>) -> Self {
+        self.config.location = location;
+        self
+    }
+
+    /// Work on `destination`'s project and in its location, when it names
+    /// them. Jobs still run in the client's project.
+    pub fn for_destination(mut self, destination: &Destination) -> Self {
+        if let Some(project) = &destination.project {
+            self.table_project = Some(project.clone());
+        }
+        if let Some(location) = &destination.location {
+            self.config.location = Some(location.clone());
+        }
+        self
+    }
+
+    pub fn config(&self) -> &ClientConfig {
+        &self.config
+    }
+
+    /// Error context naming the credentials this client authenticates with.
+    fn error_context(&self) -> ErrorContext {
+        let credentials = &self.config.credentials;
+        ErrorContext::new()
+            .with_credentials(credentials.to_string(), credentials.path().map(|p| p.display().to_string()))
+    }
+
+    pub fn is_cancelled(&self) -> bool {
+        self.config.cancel.is_cancelled()
+    }
+
     pub async fn create_table(&self, query_def: &QueryDef) -> Result<()> {
         let latest = query_def.latest_version()
             .ok_or_else(|| BqDriftError::Schema("No versions defined".into()))?;
@@ -42,7 +246,7 @@ impl BqClient {
         let clustering = query_def.cluster.as_ref().map(|c| self.build_clustering(c));
 
         let mut table = Table::new(
-            &self.project_id,
+            self.table_project(),
             &query_def.destination.dataset,
             &query_def.destination.table,
             schema,
@@ -58,9 +262,9 @@ impl BqClient {
             .create(table)
             .await
             .map_err(|e| {
-                let ctx = ErrorContext::new()
+                let ctx = self.error_context()
                     .with_operation("create_table")
-                    .with_table(&self.project_id, &query_def.destination.dataset, &query_def.destination.table);
+                    .with_table(self.table_project(), &query_def.destination.dataset, &query_def.destination.table);
                 BqDriftError::BigQuery(parse_bq_error(e, ctx))
             })?;
 
@@ -68,29 +272,516 @@ impl BqClient {
     }
 
     pub async fn execute_query(&self, sql: &str) -> Result<()> {
-        let request = QueryRequest::new(sql);
+        self.run_query("execute_query", sql, &QueryParams::new()).await.map(|_| ())
+    }
 
-        self.client
-            .job()
-            .query(&self.project_id, request)
-            .await
-            .map_err(|e| {
-                let ctx = ErrorContext::new()
-                    .with_operation("execute_query")
-                    .with_sql(sql);
-                BqDriftError::BigQuery(parse_bq_error(e, ctx))
-            })?;
+    /// Execute a query as a job and collect its statistics: affected rows,
+    /// bytes processed and billed, slot time, and retries spent on transient
+    /// errors. `params` are sent as named query parameters. The job is
+    /// cancelled on timeout or when the client's cancel signal fires.
+    ///
+    /// With a reservation the job runs as a script (see `job_sql`), which
+    /// reports no affected rows, so the count is read back from `@@row_count`.
+    pub async fn execute_query_with_stats(&self, sql: &str, params: &QueryParams) -> Result<JobStats> {
+        if self.job.reservation.is_some() {
+            let script = format!("{};\nSELECT @@row_count", sql.trim_end().trim_end_matches(';'));
+            return self.execute_script_with_stats(&script, params).await;
+        }
 
-        Ok(())
+        let (stats, _) = self.run_job("execute_query", sql, params, None).await?;
+        Ok(stats)
+    }
+
+    fn apply_job_statistics(stats: &mut JobStats, job: Job) {
+        let Some(statistics) = job.statistics else {
+            return;
+        };
+
+        stats.bytes_processed = stats.bytes_processed
+            .or_else(|| parse_count(statistics.total_bytes_processed.as_deref()));
+        stats.slot_ms = parse_count(statistics.total_slot_ms.as_deref());
+
+        if let Some(query) = statistics.query {
+            stats.bytes_billed = parse_count(query.total_bytes_billed.as_deref());
+            stats.rows_affected = stats.rows_affected
+                .or_else(|| parse_count(query.num_dml_affected_rows.as_deref()));
+            stats.rows_inserted = query.dml_stats
+                .as_ref()
+                .and_then(|dml| parse_count(dml.inserted_row_count.as_deref()));
+        }
+    }
+
+    /// Run `sql` as a query job writing its result to `destination`, whose
+    /// table id may carry a partition decorator, with the given write
+    /// disposition (e.g. `WRITE_TRUNCATE`). Waits for the job to finish.
+    pub async fn query_to_table(
+        &self,
+        sql: &str,
+        params: &QueryParams,
+        destination: &TableReference,
+        write_disposition: &str,
+    ) -> Result<JobStats> {
+        let (stats, _) = self.run_job("query_to_table", sql, params, Some((destination, write_disposition))).await?;
+        Ok(stats)
+    }
+
+    /// Run a multi-statement script as a single query job and wait for it to
+    /// finish. `rows_affected` is read from the first cell of the script's
+    /// final `SELECT`, when it has one.
+    pub async fn execute_script_with_stats(&self, sql: &str, params: &QueryParams) -> Result<JobStats> {
+        let (mut stats, job_ref) = self.run_job("execute_script", sql, params, None).await?;
+
+        let job_id = stats.job_id.clone().unwrap_or_default();
+        let results_params = GetQueryResultsParameters {
+            location: job_ref.and_then(|r| r.location),
+            ..Default::default()
+        };
+
+        // Like job statistics, the row count is informational.
+        match self.client.job().get_query_results(&self.project_id, &job_id, results_params).await {
+            Ok(results) => {
+                let first_cell = results.rows
+                    .as_ref()
+                    .and_then(|rows| rows.first())
+                    .and_then(|row| Self::row_to_strings(row).into_iter().next().flatten());
+                stats.rows_affected = parse_count(first_cell.as_deref());
+            }
+            Err(e) => debug!("Could not fetch results of script job {}: {}", job_id, e),
+        }
+
+        Ok(stats)
+    }
+
+    /// Insert a query job, wait for it to finish and collect its statistics.
+    ///
+    /// The job id is generated here and sent with every attempt, so an insert
+    /// retried after its response was lost finds the job already created
+    /// (409 duplicate) and waits on it instead of running the statement twice.
+    /// A job that finishes with an error the retry policy covers, e.g.
+    /// `rateLimitExceeded`, is submitted again under a fresh job id.
+    async fn run_job(
+        &self,
+        operation: &str,
+        sql: &str,
+        params: &QueryParams,
+        destination: Option<(&TableReference, &str)>,
+    ) -> Result<(JobStats, Option<JobReference>)> {
+        if destination.is_some() && self.job.reservation.is_some() {
+            return Err(BqDriftError::Client(
+                "a job reservation can't be combined with a destination table".to_string()
+            ));
+        }
+
+        let policy = &self.config.retry;
+        let started = Instant::now();
+        let mut attempt = 1;
+        let mut retries = 0;
+
+        loop {
+            if self.is_cancelled() {
+                return Err(BqDriftError::Cancelled("not started, the run was interrupted".to_string()));
+            }
+
+            let job_id = new_job_id();
+            let (job_ref, insert_retries) = self.insert_job(operation, sql, params, destination, &job_id).await?;
+            retries += insert_retries;
+
+            let job = self.wait_for_job(&job_id, job_ref.location.as_deref(), sql).await?;
+
+            if let Some(error) = self.job_error(&job, operation, sql) {
+                if attempt >= policy.max_attempts || !policy.is_retryable(&error) {
+                    return Err(BqDriftError::BigQuery(error));
+                }
+
+                let delay = policy.backoff(attempt);
+                warn!(
+                    operation,
+                    attempt,
+                    max_attempts = policy.max_attempts,
+                    delay_ms = delay.as_millis() as u64,
+                    "Resubmitting job {} after transient BigQuery error: {}",
+                    job_id,
+                    error
+                );
+                tokio::time::sleep(delay).await;
+                attempt += 1;
+                retries += 1;
+                continue;
+            }
+
+            let mut stats = JobStats {
+                job_id: Some(job_id),
+                retries,
+                ..Default::default()
+            };
+            Self::apply_job_statistics(&mut stats, job);
+
+            stats.elapsed_ms = started.elapsed().as_millis() as u64;
+            return Ok((stats, Some(job_ref)));
+        }
+    }
+
+    /// The error a finished job failed with, classified like a failed call.
+    fn job_error(&self, job: &Job, operation: &str, sql: &str) -> Option<BigQueryError> {
+        let error = job.status.as_ref()?.error_result.as_ref()?;
+        let ctx = self.error_context()
+            .with_operation(operation)
+            .with_sql(sql);
+
+        Some(parse_job_error(
+            error.reason.as_deref(),
+            error.message.as_deref().unwrap_or_default(),
+            ctx,
+        ))
+    }
+
+    /// Insert the query job `job_id`, retrying transient failures. Returns
+    /// the job's reference and the retries spent.
+    async fn insert_job(
+        &self,
+        operation: &str,
+        sql: &str,
+        params: &QueryParams,
+        destination: Option<(&TableReference, &str)>,
+        job_id: &str,
+    ) -> Result<(JobReference, u32)> {
+        let (inserted, retries) = self.retrying(operation, sql, move || async move {
+            let job = self.query_job(sql, params, destination, job_id);
+            match self.client.job().insert(&self.project_id, job).await {
+                Ok(job) => Ok(job.job_reference),
+                Err(e) if is_duplicate(&e) => {
+                    debug!("Job {} already exists, waiting on it", job_id);
+                    Ok(None)
+                }
+                Err(e) => Err(e),
+            }
+        }).await?;
+
+        let job_ref = inserted.unwrap_or_else(|| JobReference {
+            project_id: Some(self.project_id.clone()),
+            job_id: Some(job_id.to_string()),
+            location: self.config.location.clone(),
+        });
+        Ok((job_ref, retries))
+    }
+
+    fn query_job(
+        &self,
+        sql: &str,
+        params: &QueryParams,
+        destination: Option<(&TableReference, &str)>,
+        job_id: &str,
+    ) -> Job {
+        let mut query = JobConfigurationQuery {
+            query: self.job_sql(sql),
+            use_legacy_sql: Some(false),
+            maximum_bytes_billed: self.maximum_bytes_billed().map(|b| b.to_string()),
+            priority: self.job.priority.map(|p| p.as_str().to_string()),
+            ..Default::default()
+        };
+
+        if let Some((table, write_disposition)) = destination {
+            query.destination_table = Some(table.clone());
+            query.write_disposition = Some(write_disposition.to_string());
+        }
+
+        if !params.is_empty() {
+            query.parameter_mode = Some("NAMED".to_string());
+            query.query_parameters = Some(params.to_query_parameters());
+        }
+
+        Job {
+            configuration: Some(JobConfiguration {
+                query: Some(query),
+                labels: self.job_labels(),
+                ..Default::default()
+            }),
+            job_reference: Some(JobReference {
+                project_id: Some(self.project_id.clone()),
+                job_id: Some(job_id.to_string()),
+                location: self.config.location.clone(),
+            }),
+            ..Default::default()
+        }
+    }
+
+    /// The tighter of the client's byte budget and the query's own limit.
+    fn maximum_bytes_billed(&self) -> Option<i64> {
+        match (self.config.maximum_bytes_billed, self.job.maximum_bytes_billed) {
+            (Some(a), Some(b)) => Some(a.min(b)),
+            (a, b) => a.or(b),
+        }
+    }
+
+    fn job_labels(&self) -> Option<HashMap<String, String>> {
+        if self.job.labels.is_empty() {
+            return None;
+        }
+        Some(self.job.labels.clone().into_iter().collect())
+    }
+
+    /// BigQuery's job resource has a `reservation` field, but the client
+    /// library's job and query request models don't, so the reservation is
+    /// selected with the `@@reservation` system variable instead. That turns
+    /// the statement into a script job: its statistics carry no
+    /// `numDmlAffectedRows`, and it can't write to a destination table.
+    fn job_sql(&self, sql: &str) -> String {
+        match &self.job.reservation {
+            Some(reservation) => format!(
+                "SET @@reservation = '{}';\n{}",
+                reservation.replace('\\', "\\\\").replace('\'', "\\'"),
+                sql
+            ),
+            None => sql.to_string(),
+        }
+    }
+
+    /// Poll a job until BigQuery reports it `DONE`, with or without an error
+    /// result. A job that outlives its timeout, or is still running when the
+    /// cancel signal fires, is cancelled.
+    async fn wait_for_job(&self, job_id: &str, location: Option<&str>, sql: &str) -> Result<Job> {
+        let started = Instant::now();
+        let mut delay = Duration::from_millis(500);
+        let job_timeout = self.job.timeout().or(self.config.job_timeout);
+
+        loop {
+            let (job, _) = self.retrying("get_job", sql, || {
+                self.client.job().get_job(&self.project_id, job_id, location)
+            }).await?;
+
+            let done = job.status.as_ref().and_then(|s| s.state.as_deref()) == Some("DONE");
+            if done {
+                return Ok(job);
+            }
+
+            let mut wait = delay;
+            if let Some(timeout) = job_timeout {
+                match timeout.checked_sub(started.elapsed()) {
+                    Some(remaining) if !remaining.is_zero() => wait = wait.min(remaining),
+                    _ => {
+                        self.cancel_job(job_id, location).await;
+                        return Err(BqDriftError::Cancelled(format!(
+                            "job {} ran longer than the {}s timeout",
+                            job_id,
+                            timeout.as_secs()
+                        )));
+                    }
+                }
+            }
+
+            tokio::select! {
+                _ = tokio::time::sleep(wait) => {}
+                _ = self.config.cancel.cancelled() => {
+                    self.cancel_job(job_id, location).await;
+                    return Err(BqDriftError::Cancelled(format!("job {} was interrupted", job_id)));
+                }
+            }
+
+            delay = (delay * 2).min(Duration::from_secs(5));
+        }
+    }
+
+    /// Ask BigQuery to cancel a running job. Cancellation is best effort: the
+    /// job may still finish, and a failed request is only logged.
+    async fn cancel_job(&self, job_id: &str, location: Option<&str>) {
+        match self.client.job().cancel_job(&self.project_id, job_id, location).await {
+            Ok(_) => warn!("Cancelled BigQuery job {}", job_id),
+            Err(e) => warn!("Could not cancel BigQuery job {}: {}", job_id, e),
+        }
+    }
+
+    /// Bytes the query would process, from a dry-run job. Dry runs are free
+    /// and don't execute the statement. They use no slots either, so the
+    /// reservation is left out and the statement is priced as written.
+    pub async fn dry_run_query(&self, sql: &str, params: &QueryParams) -> Result<i64> {
+        let mut request = self.query_request(sql, params);
+        request.query = sql.to_string();
+        request.dry_run = Some(true);
+        let (response, _) = self.retrying("dry_run_query", sql, || {
+            self.client.job().query(&self.project_id, request.clone())
+        }).await?;
+
+        Ok(parse_count(response.total_bytes_processed.as_deref()).unwrap_or(0))
+    }
+
+    /// A jobs.query request with a fresh `requestId`. Build one per logical
+    /// query and resend it on retry, so BigQuery deduplicates the attempts.
+    fn query_request(&self, sql: &str, params: &QueryParams) -> QueryRequest {
+        let mut request = QueryRequest::new(self.job_sql(sql));
+        request.request_id = Some(uuid::Uuid::new_v4().to_string());
+        request.maximum_bytes_billed = self.maximum_bytes_billed().map(|b| b.to_string());
+        request.labels = self.job_labels();
+        request.location = self.config.location.clone();
+        if !params.is_empty() {
+            request.parameter_mode = Some("NAMED".to_string());
+            request.query_parameters = Some(params.to_query_parameters());
+        }
+        request
+    }
+
+    async fn run_query(&self, operation: &str, sql: &str, params: &QueryParams) -> Result<(QueryResponse, u32)> {
+        let request = self.query_request(sql, params);
+        self.retrying(operation, sql, || {
+            self.client.job().query(&self.project_id, request.clone())
+        }).await
+    }
+
+    /// Run `call` until it succeeds, fails with an error the retry policy
+    /// doesn't cover, or runs out of attempts. Returns the retries spent.
+    async fn retrying<T, F, Fut>(&self, operation: &str, sql: &str, call: F) -> Result<(T, u32)>
+    where
+        F: Fn() -> Fut,
+        Fut: Future<Output = std::result::Result<T, BQError>>,
+    {
+        let policy = &self.config.retry;
+        let mut attempt = 1;
+
+        loop {
+            match call().await {
+                Ok(value) => return Ok((value, attempt - 1)),
+                Err(e) => {
+                    let ctx = self.error_context()
+                        .with_operation(operation)
+                        .with_sql(sql);
+                    let error = parse_bq_error(e, ctx);
+
+                    if attempt >= policy.max_attempts || !policy.is_retryable(&error) {
+                        return Err(BqDriftError::BigQuery(error));
+                    }
+
+                    let delay = policy.backoff(attempt);
+                    warn!(
+                        operation,
+                        attempt,
+                        max_attempts = policy.max_attempts,
+                        delay_ms = delay.as_millis() as u64,
+                        "Retrying after transient BigQuery error: {}",
+                        error
+                    );
+                    tokio::time::sleep(delay).await;
+                    attempt += 1;
+                }
+            }
+        }
     }
 
     pub async fn table_exists(&self, dataset: &str, table: &str) -> Result<bool> {
-        match self.client.table().get(&self.project_id, dataset, table, None).await {
+        match self.client.table().get(self.table_project(), dataset, table, None).await {
             Ok(_) => Ok(true),
             Err(_) => Ok(false),
         }
     }
 
+    /// The live schema, partitioning and clustering of `dataset.table`, or
+    /// `None` if the table doesn't exist.
+    pub async fn get_table_definition(&self, dataset: &str, table: &str) -> Result<Option<TableDefinition>> {
+        let t = match self.client.table().get(self.table_project(), dataset, table, None).await {
+            Ok(t) => t,
+            Err(e) => {
+                let ctx = self.error_context()
+                    .with_operation("get_table")
+                    .with_table(self.table_project(), dataset, table);
+                return match parse_bq_error(e, ctx) {
+                    BigQueryError::TableNotFound { .. } => Ok(None),
+                    other => Err(BqDriftError::BigQuery(other)),
+                };
+            }
+        };
+
+        let fields = t.schema.fields.as_deref().unwrap_or_default();
+        let partition = match (&t.time_partitioning, &t.range_partitioning) {
+            (Some(tp), _) => Some(Self::parse_time_partitioning(tp)),
+            (None, Some(rp)) => Some(Self::parse_range_partitioning(rp)),
+            (None, None) => None,
+        };
+
+        Ok(Some(TableDefinition {
+            schema: Schema::from_fields(Self::parse_fields(fields)?),
+            partition,
+            cluster_fields: t.clustering.and_then(|c| c.fields).unwrap_or_default(),
+        }))
+    }
+
+    /// The live schema of `dataset.table`, or `None` if the table doesn't exist.
+    pub async fn get_table_schema(&self, dataset: &str, table: &str) -> Result<Option<Schema>> {
+        Ok(self.get_table_definition(dataset, table).await?.map(|t| t.schema))
+    }
+
+    fn parse_time_partitioning(tp: &TimePartitioning) -> PartitionConfig {
+        let partition_type = match tp.r#type.as_str() {
+            "HOUR" => PartitionType::Hour,
+            "MONTH" => PartitionType::Month,
+            "YEAR" => PartitionType::Year,
+            _ => PartitionType::Day,
+        };
+
+        match &tp.field {
+            Some(field) => PartitionConfig {
+                partition_type,
+                ..PartitionConfig::day(field)
+            },
+            None => PartitionConfig::ingestion_time(partition_type),
+        }
+    }
+
+    fn parse_range_partitioning(rp: &RangePartitioning) -> PartitionConfig {
+        let bound = |v: Option<&String>| v.and_then(|v| v.parse().ok()).unwrap_or(0);
+        let range = rp.range.as_ref();
+
+        PartitionConfig::range(
+            rp.field.clone().unwrap_or_default(),
+            bound(range.and_then(|r| r.start.as_ref())),
+            bound(range.and_then(|r| r.end.as_ref())),
+            bound(range.and_then(|r| r.interval.as_ref())),
+        )
+    }
+
+    fn parse_fields(fields: &[TableFieldSchema]) -> Result<Vec<Field>> {
+        fields.iter().map(Self::parse_field).collect()
+    }
+
+    fn parse_field(tfs: &TableFieldSchema) -> Result<Field> {
+        let field_type = Self::from_field_type(&tfs.r#type)
+            .ok_or_else(|| BqDriftError::Schema(format!(
+                "Unsupported type {:?} for field '{}'", tfs.r#type, tfs.name
+            )))?;
+
+        let mut field = Field::new(&tfs.name, field_type);
+        match tfs.mode.as_deref() {
+            Some("REQUIRED") => field = field.required(),
+            Some("REPEATED") => field = field.repeated(),
+            _ => {}
+        }
+
+        field.description = tfs.description.clone();
+        if let Some(nested) = &tfs.fields {
+            field.fields = Some(Self::parse_fields(nested)?);
+        }
+
+        Ok(field)
+    }
+
+    fn from_field_type(field_type: &FieldType) -> Option<BqType> {
+        Some(match field_type {
+            FieldType::String => BqType::String,
+            FieldType::Bytes => BqType::Bytes,
+            FieldType::Integer | FieldType::Int64 => BqType::Int64,
+            FieldType::Float | FieldType::Float64 => BqType::Float64,
+            FieldType::Numeric => BqType::Numeric,
+            FieldType::Bignumeric => BqType::Bignumeric,
+            FieldType::Boolean | FieldType::Bool => BqType::Bool,
+            FieldType::Date => BqType::Date,
+            FieldType::Datetime => BqType::Datetime,
+            FieldType::Time => BqType::Time,
+            FieldType::Timestamp => BqType::Timestamp,
+            FieldType::Geography => BqType::Geography,
+            FieldType::Json => BqType::Json,
+            FieldType::Record | FieldType::Struct => BqType::Record,
+            #[allow(unreachable_patterns)]
+            _ => return None,
+        })
+    }
+
     fn build_table_schema(&self, schema: &Schema) -> TableSchema {
         let fields: Vec<TableFieldSchema> = schema
             .fields
@@ -165,25 +856,20 @@ impl BqClient {
         }
     }
 
+    /// Project jobs run in.
     pub fn project_id(&self) -> &str {
         &self.project_id
     }
 
+    /// Project of the tables this client reads and creates.
+    pub fn table_project(&self) -> &str {
+        self.table_project.as_deref().unwrap_or(&self.project_id)
+    }
+
     /// Execute a query and return the row count from the first column of the first row.
     /// Useful for COUNT(*) queries or invariant checks.
-    pub async fn query_row_count(&self, sql: &str) -> Result<i64> {
-        let request = QueryRequest::new(sql);
-
-        let result = self.client
-            .job()
-            .query(&self.project_id, request)
-            .await
-            .map_err(|e| {
-                let ctx = ErrorContext::new()
-                    .with_operation("query_row_count")
-                    .with_sql(sql);
-                BqDriftError::BigQuery(parse_bq_error(e, ctx))
-            })?;
+    pub async fn query_row_count(&self, sql: &str, params: &QueryParams) -> Result<i64> {
+        let (result, _) = self.run_query("query_row_count", sql, params).await?;
 
         // Get the first row, first column as integer
         if let Some(rows) = result.rows.as_ref() {
@@ -208,19 +894,8 @@ impl BqClient {
     }
 
     /// Execute a query and return a single float value from the first column of the first row.
-    pub async fn query_single_float(&self, sql: &str) -> Result<Option<f64>> {
-        let request = QueryRequest::new(sql);
-
-        let result = self.client
-            .job()
-            .query(&self.project_id, request)
-            .await
-            .map_err(|e| {
-                let ctx = ErrorContext::new()
-                    .with_operation("query_single_float")
-                    .with_sql(sql);
-                BqDriftError::BigQuery(parse_bq_error(e, ctx))
-            })?;
+    pub async fn query_single_float(&self, sql: &str, params: &QueryParams) -> Result<Option<f64>> {
+        let (result, _) = self.run_query("query_single_float", sql, params).await?;
 
         if let Some(rows) = result.rows.as_ref() {
             if let Some(first_row) = rows.first() {
@@ -244,19 +919,8 @@ impl BqClient {
     }
 
     /// Execute a query and return a single integer value from the first column of the first row.
-    pub async fn query_single_int(&self, sql: &str) -> Result<Option<i64>> {
-        let request = QueryRequest::new(sql);
-
-        let result = self.client
-            .job()
-            .query(&self.project_id, request)
-            .await
-            .map_err(|e| {
-                let ctx = ErrorContext::new()
-                    .with_operation("query_single_int")
-                    .with_sql(sql);
-                BqDriftError::BigQuery(parse_bq_error(e, ctx))
-            })?;
+    pub async fn query_single_int(&self, sql: &str, params: &QueryParams) -> Result<Option<i64>> {
+        let (result, _) = self.run_query("query_single_int", sql, params).await?;
 
         if let Some(rows) = result.rows.as_ref() {
             if let Some(first_row) = rows.first() {
@@ -281,19 +945,8 @@ impl BqClient {
 
     /// Execute a query and return two float values from first two columns of the first row.
     /// Useful for MIN/MAX queries.
-    pub async fn query_two_floats(&self, sql: &str) -> Result<(Option<f64>, Option<f64>)> {
-        let request = QueryRequest::new(sql);
-
-        let result = self.client
-            .job()
-            .query(&self.project_id, request)
-            .await
-            .map_err(|e| {
-                let ctx = ErrorContext::new()
-                    .with_operation("query_two_floats")
-                    .with_sql(sql);
-                BqDriftError::BigQuery(parse_bq_error(e, ctx))
-            })?;
+    pub async fn query_two_floats(&self, sql: &str, params: &QueryParams) -> Result<(Option<f64>, Option<f64>)> {
+        let (result, _) = self.run_query("query_two_floats", sql, params).await?;
 
         let mut first: Option<f64> = None;
         let mut second: Option<f64> = None;
@@ -326,17 +979,100 @@ impl BqClient {
         Ok((first, second))
     }
 
+    /// Runs a query and returns every row as string cells (`NULL` becomes `None`),
+    /// following page tokens until the result set is exhausted.
+    ///
+    /// jobs.query returns without rows when the query outlasts the request's
+    /// timeout. The job is then polled like any other, under the job timeout
+    /// and cancel signal, before its results are read.
+    pub async fn query_rows(&self, sql: &str) -> Result<Vec<Vec<Option<String>>>> {
+        let (result, _) = self.run_query("query_rows", sql, &QueryParams::new()).await?;
+
+        let job_ref = result.job_reference;
+        let mut rows: Vec<Vec<Option<String>>> = result
+            .rows
+            .unwrap_or_default()
+            .iter()
+            .map(Self::row_to_strings)
+            .collect();
+        let mut page_token = result.page_token;
+
+        if result.job_complete != Some(true) {
+            let job_ref = job_ref.as_ref().ok_or_else(|| {
+                BqDriftError::Client("query did not complete and returned no job reference".to_string())
+            })?;
+            let job_id = job_ref.job_id.clone().unwrap_or_default();
+
+            let job = self.wait_for_job(&job_id, job_ref.location.as_deref(), sql).await?;
+            if let Some(error) = self.job_error(&job, "query_rows", sql) {
+                return Err(BqDriftError::BigQuery(error));
+            }
+
+            (rows, page_token) = self.query_results_page(job_ref, None, sql).await?;
+        }
+
+        while let (Some(token), Some(job_ref)) = (page_token.take(), job_ref.as_ref()) {
+            let (page, next) = self.query_results_page(job_ref, Some(token), sql).await?;
+            rows.extend(page);
+            page_token = next;
+        }
+
+        Ok(rows)
+    }
+
+    /// One page of a finished query job's results, with the token of the next.
+    async fn query_results_page(
+        &self,
+        job_ref: &JobReference,
+        page_token: Option<String>,
+        sql: &str,
+    ) -> Result<(Vec<Vec<Option<String>>>, Option<String>)> {
+        let job_id = job_ref.job_id.clone().unwrap_or_default();
+        let params = GetQueryResultsParameters {
+            page_token,
+            location: job_ref.location.clone(),
+            ..Default::default()
+        };
+
+        let (page, _) = self.retrying("query_rows", sql, || {
+            self.client
+                .job()
+                .get_query_results(&self.project_id, &job_id, params.clone())
+        }).await?;
+
+        let rows = page.rows.unwrap_or_default().iter().map(Self::row_to_strings).collect();
+        Ok((rows, page.page_token))
+    }
+
+    fn row_to_strings(row: &TableRow) -> Vec<Option<String>> {
+        row.columns
+            .as_ref()
+            .map(|cells| {
+                cells
+                    .iter()
+                    .map(|cell| match &cell.value {
+                        None | Some(serde_json::Value::Null) => None,
+                        Some(serde_json::Value::String(s)) => Some(s.clone()),
+                        Some(other) => Some(other.to_string()),
+                    })
+                    .collect()
+            })
+            .unwrap_or_default()
+    }
+
+    /// Create `dataset` in the client's location unless it exists.
     pub async fn ensure_dataset(&self, dataset: &str) -> Result<()> {
-        match self.client.dataset().get(&self.project_id, dataset).await {
+        match self.client.dataset().get(self.table_project(), dataset).await {
             Ok(_) => Ok(()),
             Err(_) => {
-                let ds = Dataset::new(&self.project_id, dataset);
+                let mut ds = Dataset::new(self.table_project(), dataset);
+                ds.location = self.config.location.clone();
                 self.client
                     .dataset()
                     .create(ds)
                     .await
                     .map_err(|e| {
-                        let ctx = ErrorContext::new()
+                        let ctx = self.error_context()
                             .with_operation("create_dataset");
                         BqDriftError::BigQuery(parse_bq_error(e, ctx))
                     })?;
@@ -346,7 +1082,7 @@ impl BqClient {
     }
 
     pub async fn drop_table(&self, dataset: &str, table: &str) -> Result<()> {
-        match self.client.table().delete(&self.project_id, dataset, table).await {
+        match self.client.table().delete(self.table_project(), dataset, table).await {
             Ok(_) => Ok(()),
             Err(_) => Ok(()),
         }
@@ -366,7 +1102,7 @@ impl BqClient {
         let clustering = cluster_config.map(|c| self.build_clustering(c));
 
         let mut tbl = Table::new(
-            &self.project_id,
+            self.table_project(),
             dataset,
             table,
             table_schema,
@@ -383,9 +1119,9 @@ impl BqClient {
             .create(tbl)
             .await
             .map_err(|e| {
-                let ctx = ErrorContext::new()
+                let ctx = self.error_context()
                     .with_operation("create_table_with_expiration")
-                    .with_table(&self.project_id, dataset, table);
+                    .with_table(self.table_project(), dataset, table);
                 BqDriftError::BigQuery(parse_bq_error(e, ctx))
             })?;
 
@@ -395,10 +1131,10 @@ impl BqClient {
     pub async fn list_tables(&self, dataset: &str) -> Result<Vec<String>> {
         let tables = self.client
             .table()
-            .list(&self.project_id, dataset, Default::default())
+            .list(self.table_project(), dataset, Default::default())
             .await
             .map_err(|e| {
-                let ctx = ErrorContext::new()
+                let ctx = self.error_context()
                     .with_operation("list_tables");
                 BqDriftError::BigQuery(parse_bq_error(e, ctx))
             })?;
@@ -413,3 +1149,44 @@ impl BqClient {
         Ok(table_names)
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_count() {
+        assert_eq!(parse_count(Some("10485760")), Some(10485760));
+        assert_eq!(parse_count(Some("n/a")), None);
+        assert_eq!(parse_count(None), None);
+    }
+
+    #[test]
+    fn test_job_stats_accumulate() {
+        let mut total = JobStats {
+            job_id: Some("delete_job".to_string()),
+            rows_affected: Some(100),
+            bytes_processed: Some(1000),
+            elapsed_ms: 500,
+            retries: 1,
+            ..Default::default()
+        };
+
+        total.accumulate(&JobStats {
+            job_id: Some("insert_job".to_string()),
+            rows_affected: Some(120),
+            bytes_processed: Some(2000),
+            bytes_billed: Some(10485760),
+            elapsed_ms: 700,
+            ..Default::default()
+        });
+
+        assert_eq!(total.job_id.as_deref(), Some("insert_job"));
+        assert_eq!(total.rows_affected, Some(220));
+        assert_eq!(total.bytes_processed, Some(3000));
+        assert_eq!(total.bytes_billed, Some(10485760));
+        assert_eq!(total.slot_ms, None);
+        assert_eq!(total.elapsed_ms, 1200);
+        assert_eq!(total.retries, 1);
+    }
+}
@@ -0,0 +1,181 @@
+use async_trait::async_trait;
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::fmt;
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
+use gcp_bigquery_client::Client;
+use gcp_bigquery_client::auth::Authenticator;
+use gcp_bigquery_client::error::BQError;
+use yup_oauth2::authenticator::DefaultAuthenticator;
+use yup_oauth2::ServiceAccountImpersonationAuthenticator;
+use crate::error::{BigQueryError, BqDriftError, Result};
+
+const BIGQUERY_SCOPE: &str = "https://www.googleapis.com/auth/bigquery";
+
+/// Where a `BqClient` gets its OAuth credentials.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum Credentials {
+    /// `GOOGLE_APPLICATION_CREDENTIALS`, then the gcloud user credentials,
+    /// then the metadata server.
+    #[default]
+    ApplicationDefault,
+    /// A service account JSON key file.
+    ServiceAccountKey { path: PathBuf },
+    /// An `authorized_user` JSON file, as written by
+    /// `gcloud auth application-default login`.
+    AuthorizedUser { path: PathBuf },
+    /// The service account bound to the workload through the GKE metadata server.
+    WorkloadIdentity,
+    /// Short-lived tokens for `service_account`, minted with user credentials
+    /// that hold `roles/iam.serviceAccountTokenCreator` on it. The user
+    /// credentials default to gcloud's application default credentials.
+    Impersonated {
+        service_account: String,
+        #[serde(default)]
+        user_credentials: Option<PathBuf>,
+    },
+    /// No credentials at all, for BigQuery emulators and local stand-ins
+    /// reached through `ClientConfig::endpoint`.
+    NoAuth,
+}
+
+impl fmt::Display for Credentials {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Credentials::ApplicationDefault => write!(f, "application default credentials"),
+            Credentials::ServiceAccountKey { path } => write!(f, "service account key {}", path.display()),
+            Credentials::AuthorizedUser { path } => write!(f, "authorized user credentials {}", path.display()),
+            Credentials::WorkloadIdentity => write!(f, "workload identity"),
+            Credentials::Impersonated { service_account, .. } => {
+                write!(f, "impersonation of {}", service_account)
+            }
+            Credentials::NoAuth => write!(f, "no authentication"),
+        }
+    }
+}
+
+impl Credentials {
+    /// The credentials file read, if any.
+    pub fn path(&self) -> Option<PathBuf> {
+        match self {
+            Credentials::ApplicationDefault => std::env::var("GOOGLE_APPLICATION_CREDENTIALS").ok().map(PathBuf::from),
+            Credentials::ServiceAccountKey { path } | Credentials::AuthorizedUser { path } => Some(path.clone()),
+            Credentials::WorkloadIdentity | Credentials::NoAuth => None,
+            Credentials::Impersonated { user_credentials, .. } => {
+                user_credentials.clone().or_else(gcloud_user_credentials)
+            }
+        }
+    }
+
+    /// Build a BigQuery client authenticated with these credentials. Failures
+    /// are reported as `InvalidCredentials` naming the source.
+    pub(crate) async fn client(&self) -> Result<Client> {
+        let client = match self {
+            Credentials::ApplicationDefault => Client::from_application_default_credentials().await,
+            Credentials::ServiceAccountKey { path } => Client::from_service_account_key_file(&path_str(path)).await,
+            Credentials::AuthorizedUser { path } => Client::from_authorized_user_secret(&path_str(path)).await,
+            Credentials::WorkloadIdentity => Client::with_workload_identity(false).await,
+            Credentials::Impersonated { service_account, .. } => {
+                let path = self.path().ok_or_else(|| self.invalid("no user credentials to impersonate with"))?;
+                let auth = ImpersonatedAuthenticator::new(&path, service_account)
+                    .await
+                    .map_err(|reason| self.invalid(&reason))?;
+                Client::from_authenticator(Arc::new(auth)).await
+            }
+            Credentials::NoAuth => Client::from_authenticator(Arc::new(NoAuthenticator)).await,
+        };
+
+        client.map_err(|e| self.invalid(&e.to_string()))
+    }
+
+    fn invalid(&self, reason: &str) -> BqDriftError {
+        BqDriftError::BigQuery(BigQueryError::InvalidCredentials {
+            credentials: Some(self.to_string()),
+            path: self.path().map(|p| p.display().to_string()),
+            reason: reason.to_string(),
+        })
+    }
+}
+
+/// Named credential sets a JSON-RPC session can pick with
+/// `session_create`'s `credentials` parameter, loaded from a YAML map of
+/// profile name to credentials.
+pub fn load_credential_profiles(path: impl AsRef<Path>) -> Result<HashMap<String, Credentials>> {
+    let content = std::fs::read_to_string(path)?;
+    Ok(serde_yaml::from_str(&content)?)
+}
+
+fn path_str(path: &Path) -> String {
+    path.to_string_lossy().to_string()
+}
+
+/// Where `gcloud auth application-default login` writes user credentials.
+fn gcloud_user_credentials() -> Option<PathBuf> {
+    let path = dirs::config_dir()?.join("gcloud").join("application_default_credentials.json");
+    path.exists().then_some(path)
+}
+
+struct ImpersonatedAuthenticator {
+    auth: DefaultAuthenticator,
+}
+
+impl ImpersonatedAuthenticator {
+    async fn new(user_credentials: &Path, service_account: &str) -> std::result::Result<Self, String> {
+        let secret = yup_oauth2::read_authorized_user_secret(user_credentials)
+            .await
+            .map_err(|e| e.to_string())?;
+        let auth = ServiceAccountImpersonationAuthenticator::builder(secret, service_account)
+            .build()
+            .await
+            .map_err(|e| e.to_string())?;
+
+        Ok(Self { auth })
+    }
+}
+
+#[async_trait]
+impl Authenticator for ImpersonatedAuthenticator {
+    async fn access_token(&self) -> std::result::Result<String, BQError> {
+        let token = self.auth.token(&[BIGQUERY_SCOPE]).await.map_err(BQError::YupAuthError)?;
+        token.token().map(str::to_string).ok_or(BQError::NoToken)
+    }
+}
+
+/// Hands out a placeholder token; emulators don't check it.
+struct NoAuthenticator;
+
+#[async_trait]
+impl Authenticator for NoAuthenticator {
+    async fn access_token(&self) -> std::result::Result<String, BQError> {
+        Ok("no-auth".to_string())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_profiles() {
+        let yaml = r#"
+finance:
+  type: service_account_key
+  path: /secrets/finance.json
+ops:
+  type: impersonated
+  service_account: ops@my-project.iam.gserviceaccount.com
+gke:
+  type: workload_identity
+emulator:
+  type: no_auth
+"#;
+        let profiles: HashMap<String, Credentials> = serde_yaml::from_str(yaml).unwrap();
+
+        assert_eq!(profiles["finance"], Credentials::ServiceAccountKey { path: PathBuf::from("/secrets/finance.json") });
+        assert_eq!(profiles["ops"].to_string(), "impersonation of ops@my-project.iam.gserviceaccount.com");
+        assert_eq!(profiles["gke"], Credentials::WorkloadIdentity);
+        assert_eq!(profiles["emulator"], Credentials::NoAuth);
+    }
+}
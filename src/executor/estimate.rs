@@ -0,0 +1,168 @@
+use crate::error::{BqDriftError, Result};
+use crate::schema::PartitionKey;
+use super::partition_writer::format_bytes;
+use super::runner::RunFailure;
+
+/// Bytes a single partition write would process, from a dry run of its MERGE.
+#[derive(Debug, Clone)]
+pub struct PartitionEstimate {
+    pub query_name: String,
+    pub partition_key: PartitionKey,
+    pub bytes_processed: i64,
+}
+
+/// Dry-run estimate of a run, backfill or sync plan. Partitions whose dry
+/// run failed (invalid SQL, missing tables) are listed in `failures`.
+#[derive(Debug, Default)]
+pub struct CostEstimate {
+    pub partitions: Vec<PartitionEstimate>,
+    pub failures: Vec<RunFailure>,
+}
+
+impl CostEstimate {
+    pub fn total_bytes(&self) -> i64 {
+        self.partitions.iter().map(|p| p.bytes_processed).sum()
+    }
+
+    /// Estimated bytes per query, in the order queries first appear in the plan.
+    pub fn query_totals(&self) -> Vec<(String, i64)> {
+        let mut totals: Vec<(String, i64)> = Vec::new();
+
+        for partition in &self.partitions {
+            match totals.iter_mut().find(|(name, _)| *name == partition.query_name) {
+                Some((_, bytes)) => *bytes += partition.bytes_processed,
+                None => totals.push((partition.query_name.clone(), partition.bytes_processed)),
+            }
+        }
+
+        totals
+    }
+
+    /// Fails with `BudgetExceeded` when the plan would process more than
+    /// `budget` bytes, or when any partition couldn't be priced, since its
+    /// cost is unknown.
+    pub fn check_budget(&self, budget: Option<i64>) -> Result<()> {
+        let Some(budget) = budget else {
+            return Ok(());
+        };
+
+        if !self.failures.is_empty() {
+            let unpriced: Vec<String> = self.failures
+                .iter()
+                .map(|f| format!("{} {} ({})", f.query_name, f.partition_key, f.error))
+                .collect();
+            return Err(BqDriftError::BudgetExceeded(format!(
+                "{} partitions could not be priced against the {} budget: {}",
+                self.failures.len(),
+                format_bytes(budget),
+                unpriced.join(", "),
+            )));
+        }
+
+        if self.total_bytes() > budget {
+            return Err(BqDriftError::BudgetExceeded(format!(
+                "plan would process {} across {} partitions, over the {} budget",
+                format_bytes(self.total_bytes()),
+                self.partitions.len(),
+                format_bytes(budget),
+            )));
+        }
+
+        Ok(())
+    }
+
+    pub fn merge(&mut self, other: CostEstimate) {
+        self.partitions.extend(other.partitions);
+        self.failures.extend(other.failures);
+    }
+}
+
+/// Parses a byte count such as `1073741824`, `500MB`, `10GB` or `1.5TB`.
+/// Units are binary (1 GB = 1024 MB) and case-insensitive.
+pub fn parse_byte_size(value: &str) -> Result<i64> {
+    let trimmed = value.trim();
+    let split = trimmed
+        .find(|c: char| !(c.is_ascii_digit() || c == '.'))
+        .unwrap_or(trimmed.len());
+    let (number, unit) = trimmed.split_at(split);
+
+    let multiplier: i64 = match unit.trim().to_ascii_uppercase().as_str() {
+        "" | "B" => 1,
+        "KB" | "KIB" => 1 << 10,
+        "MB" | "MIB" => 1 << 20,
+        "GB" | "GIB" => 1 << 30,
+        "TB" | "TIB" => 1 << 40,
+        _ => return Err(BqDriftError::Executor(format!("Invalid byte size '{}'", value))),
+    };
+
+    let number: f64 = number
+        .parse()
+        .map_err(|_| BqDriftError::Executor(format!("Invalid byte size '{}'", value)))?;
+
+    Ok((number * multiplier as f64) as i64)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::NaiveDate;
+
+    fn estimate(query_name: &str, day: u32, bytes: i64) -> PartitionEstimate {
+        PartitionEstimate {
+            query_name: query_name.to_string(),
+            partition_key: PartitionKey::Day(NaiveDate::from_ymd_opt(2024, 6, day).unwrap()),
+            bytes_processed: bytes,
+        }
+    }
+
+    #[test]
+    fn test_parse_byte_size() {
+        assert_eq!(parse_byte_size("1024").unwrap(), 1024);
+        assert_eq!(parse_byte_size("500MB").unwrap(), 500 * 1024 * 1024);
+        assert_eq!(parse_byte_size("10 gb").unwrap(), 10 * 1024 * 1024 * 1024);
+        assert_eq!(parse_byte_size("1.5TB").unwrap(), 1024 * 1024 * 1024 * 1024 * 3 / 2);
+        assert!(parse_byte_size("10 parsecs").is_err());
+        assert!(parse_byte_size("GB").is_err());
+    }
+
+    #[test]
+    fn test_query_totals() {
+        let plan = CostEstimate {
+            partitions: vec![estimate("daily", 1, 100), estimate("summary", 1, 10), estimate("daily", 2, 150)],
+            failures: vec![],
+        };
+
+        assert_eq!(plan.total_bytes(), 260);
+        assert_eq!(plan.query_totals(), vec![("daily".to_string(), 250), ("summary".to_string(), 10)]);
+    }
+
+    #[test]
+    fn test_check_budget() {
+        let plan = CostEstimate {
+            partitions: vec![estimate("daily", 1, 600), estimate("daily", 2, 600)],
+            failures: vec![],
+        };
+
+        assert!(plan.check_budget(None).is_ok());
+        assert!(plan.check_budget(Some(1200)).is_ok());
+        assert!(matches!(plan.check_budget(Some(1000)), Err(BqDriftError::BudgetExceeded(_))));
+    }
+
+    #[test]
+    fn test_check_budget_fails_on_unpriced_partitions() {
+        let plan = CostEstimate {
+            partitions: vec![estimate("daily", 1, 100)],
+            failures: vec![RunFailure {
+                query_name: "daily".to_string(),
+                partition_key: PartitionKey::Day(NaiveDate::from_ymd_opt(2024, 6, 2).unwrap()),
+                error: "Table not found".to_string(),
+            }],
+        };
+
+        assert!(plan.check_budget(None).is_ok());
+        match plan.check_budget(Some(1 << 30)) {
+            Err(BqDriftError::BudgetExceeded(message)) => assert!(message.contains("daily 2024-06-02")),
+            other => panic!("unexpected result: {:?}", other),
+        }
+    }
+}
@@ -1,12 +1,24 @@
+mod cancel;
 mod client;
+mod credentials;
+mod estimate;
+mod params;
+mod retry;
 mod partition_writer;
 mod runner;
 mod scratch;
+mod strategy;
 mod bq_executor;
 
-pub use client::BqClient;
-pub use partition_writer::{PartitionWriter, PartitionWriteStats};
-pub use runner::{Runner, RunReport, RunFailure};
+pub use cancel::CancelSignal;
+pub use client::{BqClient, ClientConfig, JobStats};
+pub use credentials::{Credentials, load_credential_profiles};
+pub use params::{QueryParams, ParamValue, RunContext, BUILTIN_PARAMS};
+pub(crate) use params::referenced_params;
+pub use retry::{RetryPolicy, DEFAULT_RETRYABLE_ERRORS};
+pub use partition_writer::{PartitionWriter, PartitionWriteStats, format_bytes};
+pub use estimate::{CostEstimate, PartitionEstimate, parse_byte_size};
+pub use runner::{Runner, RunReport, RunFailure, RunSkip, SkipReason, BackfillOptions, BackfillStep, cascade_plan};
 pub use scratch::{ScratchConfig, ScratchWriter, ScratchWriteStats, PromoteStats};
 
 pub use bq_executor::{
@@ -0,0 +1,484 @@
+use chrono::{DateTime, Datelike, NaiveDate, NaiveDateTime, SubsecRound, Utc};
+use gcp_bigquery_client::model::query_parameter::QueryParameter;
+use gcp_bigquery_client::model::query_parameter_type::QueryParameterType;
+use gcp_bigquery_client::model::query_parameter_value::QueryParameterValue;
+use crate::schema::{PartitionConfig, PartitionKey};
+
+/// Parameters bound for every partition write and invariant check.
+///
+/// | Parameter | Type | Value |
+/// |-----------|------|-------|
+/// | `@partition_date` | `DATE`, `TIMESTAMP` or `INT64` | The partition key itself |
+/// | `@partition_start` | same as the key | First value in the partition |
+/// | `@partition_end` | same as the key | First value after the partition (exclusive) |
+/// | `@partition_hour` | `TIMESTAMP` | Start of the partition, time partitions only |
+/// | `@partition_month` | `DATE` | First day of the partition's month, time partitions only |
+/// | `@range_start` / `@range_end` | `INT64` | Integer range bounds, range partitions only |
+/// | `@run_time` | `TIMESTAMP` | When the run started |
+/// | `@run_id` | `STRING` | Unique id of the run |
+pub const BUILTIN_PARAMS: &[&str] = &[
+    "partition_date",
+    "partition_start",
+    "partition_end",
+    "partition_hour",
+    "partition_month",
+    "range_start",
+    "range_end",
+    "run_time",
+    "run_id",
+];
+
+/// Identifies one bqdrift invocation. Every partition written by the same
+/// writer sees the same `@run_id` and `@run_time`.
+#[derive(Debug, Clone)]
+pub struct RunContext {
+    pub run_id: String,
+    pub run_time: DateTime<Utc>,
+}
+
+impl Default for RunContext {
+    fn default() -> Self {
+        Self {
+            run_id: uuid::Uuid::new_v4().to_string(),
+            run_time: Utc::now(),
+        }
+    }
+}
+
+impl RunContext {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn with_run_id(mut self, run_id: impl Into<String>) -> Self {
+        self.run_id = run_id.into();
+        self
+    }
+
+    pub fn with_run_time(mut self, run_time: DateTime<Utc>) -> Self {
+        self.run_time = run_time;
+        self
+    }
+}
+
+/// A typed value bound to a named query parameter such as `@partition_date`.
+#[derive(Debug, Clone, PartialEq)]
+pub enum ParamValue {
+    Date(NaiveDate),
+    Timestamp(NaiveDateTime),
+    Int64(i64),
+    String(String),
+}
+
+impl ParamValue {
+    /// The partition's own value: `DATE` for day, month and year partitions
+    /// (the first day of the period), `TIMESTAMP` for hour partitions and
+    /// `INT64` for integer ranges.
+    pub fn for_partition(partition_key: &PartitionKey) -> Self {
+        match partition_key {
+            PartitionKey::Hour(dt) => ParamValue::Timestamp(*dt),
+            PartitionKey::Range(n) => ParamValue::Int64(*n),
+            other => ParamValue::Date(other.to_naive_date()),
+        }
+    }
+
+    pub fn type_name(&self) -> &'static str {
+        match self {
+            ParamValue::Date(_) => "DATE",
+            ParamValue::Timestamp(_) => "TIMESTAMP",
+            ParamValue::Int64(_) => "INT64",
+            ParamValue::String(_) => "STRING",
+        }
+    }
+
+    /// The value as BigQuery expects it in a `QueryParameterValue`.
+    pub fn value_string(&self) -> String {
+        match self {
+            ParamValue::Date(d) => d.format("%Y-%m-%d").to_string(),
+            ParamValue::Timestamp(dt) => dt.format("%Y-%m-%d %H:%M:%S%.f").to_string(),
+            ParamValue::Int64(n) => n.to_string(),
+            ParamValue::String(s) => s.clone(),
+        }
+    }
+
+    /// A typed SQL literal, e.g. `DATE '2024-06-15'`.
+    pub fn sql_literal(&self) -> String {
+        match self {
+            ParamValue::Date(_) | ParamValue::Timestamp(_) => {
+                format!("{} '{}'", self.type_name(), self.value_string())
+            }
+            ParamValue::Int64(n) => n.to_string(),
+            ParamValue::String(s) => format!("'{}'", s.replace('\\', "\\\\").replace('\'', "\\'")),
+        }
+    }
+}
+
+/// Named parameters sent alongside a statement instead of being spliced
+/// into its text.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct QueryParams {
+    params: Vec<(String, ParamValue)>,
+}
+
+impl QueryParams {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// The `BUILTIN_PARAMS` that apply to `partition_key`. `partition`
+    /// supplies the interval of integer range partitions.
+    pub fn for_partition(partition_key: &PartitionKey, partition: &PartitionConfig, run: &RunContext) -> Self {
+        let end = partition_key.next_by(partition.interval.unwrap_or(1));
+
+        let params = Self::new()
+            .with("partition_date", ParamValue::for_partition(partition_key))
+            .with("partition_start", ParamValue::for_partition(partition_key))
+            .with("partition_end", ParamValue::for_partition(&end));
+
+        let params = match partition_key {
+            PartitionKey::Range(n) => params
+                .with("range_start", ParamValue::Int64(*n))
+                .with("range_end", ParamValue::for_partition(&end)),
+            _ => {
+                let start = partition_start_time(partition_key);
+                params
+                    .with("partition_hour", ParamValue::Timestamp(start))
+                    .with("partition_month", ParamValue::Date(start.date().with_day(1).unwrap_or(start.date())))
+            }
+        };
+
+        params
+            .with("run_time", ParamValue::Timestamp(run.run_time.naive_utc().trunc_subsecs(6)))
+            .with("run_id", ParamValue::String(run.run_id.clone()))
+    }
+
+    /// Bind `name` (without the `@`), replacing any earlier value.
+    pub fn with(mut self, name: impl Into<String>, value: ParamValue) -> Self {
+        let name = name.into();
+        match self.params.iter_mut().find(|(n, _)| *n == name) {
+            Some((_, existing)) => *existing = value,
+            None => self.params.push((name, value)),
+        }
+        self
+    }
+
+    pub fn get(&self, name: &str) -> Option<&ParamValue> {
+        self.params.iter().find(|(n, _)| n == name).map(|(_, v)| v)
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.params.is_empty()
+    }
+
+    pub fn iter(&self) -> impl Iterator<Item = (&str, &ParamValue)> {
+        self.params.iter().map(|(n, v)| (n.as_str(), v))
+    }
+
+    pub fn to_query_parameters(&self) -> Vec<QueryParameter> {
+        self.params
+            .iter()
+            .map(|(name, value)| QueryParameter {
+                name: Some(name.clone()),
+                parameter_type: Some(QueryParameterType {
+                    r#type: value.type_name().to_string(),
+                    ..Default::default()
+                }),
+                parameter_value: Some(QueryParameterValue {
+                    value: Some(value.value_string()),
+                    ..Default::default()
+                }),
+            })
+            .collect()
+    }
+
+    /// Replace references to bound parameters with typed literals, for
+    /// engines that can't take parameters separately. Occurrences inside
+    /// string literals, quoted identifiers and comments are left alone, as
+    /// are `@@system_variables` and parameters that aren't bound.
+    pub fn inline(&self, sql: &str) -> String {
+        replace_params(sql, |name| self.get(name).map(ParamValue::sql_literal))
+    }
+}
+
+/// Names of the `@parameters` `sql` references, in order. Mentions inside
+/// string literals, quoted identifiers and comments don't count, and neither
+/// do `@@system_variables`.
+pub(crate) fn referenced_params(sql: &str) -> Vec<String> {
+    let mut names = Vec::new();
+    replace_params(sql, |name| {
+        if !name.is_empty() {
+            names.push(name.to_string());
+        }
+        None
+    });
+    names
+}
+
+/// Replace each `@parameter` reference in code with what `replace` returns
+/// for its name, keeping the reference when it returns None. A reference
+/// runs to the end of the identifier, so `@partition_date` never matches
+/// the start of `@partition_datetime`.
+fn replace_params(sql: &str, mut replace: impl FnMut(&str) -> Option<String>) -> String {
+    let chars: Vec<char> = sql.chars().collect();
+    let mut out = String::with_capacity(sql.len());
+    let mut i = 0;
+
+    while i < chars.len() {
+        let c = chars[i];
+        let next = chars.get(i + 1).copied();
+
+        match c {
+            '\'' | '"' | '`' => {
+                let end = quoted_end(&chars, i);
+                out.extend(&chars[i..end]);
+                i = end;
+            }
+            '-' if next == Some('-') => {
+                let end = line_end(&chars, i);
+                out.extend(&chars[i..end]);
+                i = end;
+            }
+            '#' => {
+                let end = line_end(&chars, i);
+                out.extend(&chars[i..end]);
+                i = end;
+            }
+            '/' if next == Some('*') => {
+                let end = block_comment_end(&chars, i);
+                out.extend(&chars[i..end]);
+                i = end;
+            }
+            '@' if next == Some('@') => {
+                let end = identifier_end(&chars, i + 2);
+                out.extend(&chars[i..end]);
+                i = end;
+            }
+            '@' => {
+                let end = identifier_end(&chars, i + 1);
+                let name: String = chars[i + 1..end].iter().collect();
+                match replace(&name) {
+                    Some(literal) if !name.is_empty() => out.push_str(&literal),
+                    _ => out.extend(&chars[i..end]),
+                }
+                i = end;
+            }
+            _ => {
+                out.push(c);
+                i += 1;
+            }
+        }
+    }
+
+    out
+}
+
+fn partition_start_time(partition_key: &PartitionKey) -> NaiveDateTime {
+    match partition_key {
+        PartitionKey::Hour(dt) => *dt,
+        other => other.to_naive_date().and_hms_opt(0, 0, 0).unwrap_or_default(),
+    }
+}
+
+fn identifier_end(chars: &[char], start: usize) -> usize {
+    let mut end = start;
+    while end < chars.len() && (chars[end].is_ascii_alphanumeric() || chars[end] == '_') {
+        end += 1;
+    }
+    end
+}
+
+fn line_end(chars: &[char], start: usize) -> usize {
+    let mut end = start;
+    while end < chars.len() && chars[end] != '\n' {
+        end += 1;
+    }
+    end
+}
+
+fn block_comment_end(chars: &[char], start: usize) -> usize {
+    let mut end = start + 2;
+    while end < chars.len() {
+        if chars[end] == '*' && chars.get(end + 1) == Some(&'/') {
+            return end + 2;
+        }
+        end += 1;
+    }
+    chars.len()
+}
+
+/// End (exclusive) of the quoted string or identifier starting at `start`,
+/// including triple-quoted strings and backslash escapes.
+fn quoted_end(chars: &[char], start: usize) -> usize {
+    let quote = chars[start];
+    let triple = quote != '`'
+        && chars.get(start + 1) == Some(&quote)
+        && chars.get(start + 2) == Some(&quote);
+    let mut end = if triple { start + 3 } else { start + 1 };
+
+    while end < chars.len() {
+        if chars[end] == '\\' {
+            end += 2;
+            continue;
+        }
+        if chars[end] == quote {
+            if !triple {
+                return end + 1;
+            }
+            if chars.get(end + 1) == Some(&quote) && chars.get(end + 2) == Some(&quote) {
+                return end + 3;
+            }
+        }
+        end += 1;
+    }
+
+    chars.len()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn day(d: u32) -> PartitionKey {
+        PartitionKey::Day(NaiveDate::from_ymd_opt(2024, 6, d).unwrap())
+    }
+
+    fn run() -> RunContext {
+        RunContext::new()
+            .with_run_id("run-1")
+            .with_run_time(DateTime::from_naive_utc_and_offset(
+                NaiveDate::from_ymd_opt(2024, 6, 16).unwrap().and_hms_opt(2, 30, 0).unwrap(),
+                Utc,
+            ))
+    }
+
+    fn params_for(partition_key: &PartitionKey, partition: &PartitionConfig) -> QueryParams {
+        QueryParams::for_partition(partition_key, partition, &run())
+    }
+
+    fn literal(params: &QueryParams, name: &str) -> Option<String> {
+        params.get(name).map(|v| v.sql_literal())
+    }
+
+    #[test]
+    fn test_partition_param_types() {
+        assert_eq!(
+            ParamValue::for_partition(&day(15)),
+            ParamValue::Date(NaiveDate::from_ymd_opt(2024, 6, 15).unwrap())
+        );
+        assert_eq!(
+            ParamValue::for_partition(&PartitionKey::Month { year: 2024, month: 3 }).sql_literal(),
+            "DATE '2024-03-01'"
+        );
+
+        let hour = NaiveDate::from_ymd_opt(2024, 6, 15).unwrap().and_hms_opt(8, 0, 0).unwrap();
+        let value = ParamValue::for_partition(&PartitionKey::Hour(hour));
+        assert_eq!(value.type_name(), "TIMESTAMP");
+        assert_eq!(value.value_string(), "2024-06-15 08:00:00");
+
+        assert_eq!(ParamValue::for_partition(&PartitionKey::Range(42)).type_name(), "INT64");
+    }
+
+    #[test]
+    fn test_day_partition_params() {
+        let params = params_for(&day(15), &PartitionConfig::day("date"));
+
+        assert_eq!(literal(&params, "partition_date").as_deref(), Some("DATE '2024-06-15'"));
+        assert_eq!(literal(&params, "partition_start").as_deref(), Some("DATE '2024-06-15'"));
+        assert_eq!(literal(&params, "partition_end").as_deref(), Some("DATE '2024-06-16'"));
+        assert_eq!(literal(&params, "partition_hour").as_deref(), Some("TIMESTAMP '2024-06-15 00:00:00'"));
+        assert_eq!(literal(&params, "partition_month").as_deref(), Some("DATE '2024-06-01'"));
+        assert_eq!(literal(&params, "run_time").as_deref(), Some("TIMESTAMP '2024-06-16 02:30:00'"));
+        assert_eq!(literal(&params, "run_id").as_deref(), Some("'run-1'"));
+        assert!(params.get("range_start").is_none());
+    }
+
+    #[test]
+    fn test_hour_and_month_partition_params() {
+        let hour = NaiveDate::from_ymd_opt(2024, 12, 31).unwrap().and_hms_opt(23, 0, 0).unwrap();
+        let params = params_for(&PartitionKey::Hour(hour), &PartitionConfig::hour("ts"));
+
+        assert_eq!(literal(&params, "partition_start").as_deref(), Some("TIMESTAMP '2024-12-31 23:00:00'"));
+        assert_eq!(literal(&params, "partition_end").as_deref(), Some("TIMESTAMP '2025-01-01 00:00:00'"));
+        assert_eq!(literal(&params, "partition_hour").as_deref(), Some("TIMESTAMP '2024-12-31 23:00:00'"));
+        assert_eq!(literal(&params, "partition_month").as_deref(), Some("DATE '2024-12-01'"));
+
+        let params = params_for(&PartitionKey::Month { year: 2024, month: 12 }, &PartitionConfig::month("date"));
+        assert_eq!(literal(&params, "partition_start").as_deref(), Some("DATE '2024-12-01'"));
+        assert_eq!(literal(&params, "partition_end").as_deref(), Some("DATE '2025-01-01'"));
+    }
+
+    #[test]
+    fn test_range_partition_params() {
+        let params = params_for(&PartitionKey::Range(100), &PartitionConfig::range("bucket", 0, 1000, 50));
+
+        assert_eq!(literal(&params, "range_start").as_deref(), Some("100"));
+        assert_eq!(literal(&params, "range_end").as_deref(), Some("150"));
+        assert_eq!(literal(&params, "partition_end").as_deref(), Some("150"));
+        assert!(params.get("partition_hour").is_none());
+        assert!(params.get("partition_month").is_none());
+    }
+
+    #[test]
+    fn test_builtin_params_are_documented() {
+        let params = params_for(&day(15), &PartitionConfig::day("date"));
+        for (name, _) in params.iter() {
+            assert!(BUILTIN_PARAMS.contains(&name), "{} missing from BUILTIN_PARAMS", name);
+        }
+    }
+
+    #[test]
+    fn test_to_query_parameters() {
+        let params = QueryParams::new().with("partition_date", ParamValue::for_partition(&day(15)));
+        let bq = params.to_query_parameters();
+
+        assert_eq!(bq.len(), 1);
+        assert_eq!(bq[0].name.as_deref(), Some("partition_date"));
+        assert_eq!(bq[0].parameter_type.as_ref().unwrap().r#type, "DATE");
+        assert_eq!(bq[0].parameter_value.as_ref().unwrap().value.as_deref(), Some("2024-06-15"));
+    }
+
+    #[test]
+    fn test_with_replaces_existing() {
+        let params = QueryParams::new()
+            .with("partition_date", ParamValue::for_partition(&day(1)))
+            .with("partition_date", ParamValue::for_partition(&day(2)));
+        assert_eq!(params.iter().count(), 1);
+        assert_eq!(params.get("partition_date"), Some(&ParamValue::for_partition(&day(2))));
+    }
+
+    #[test]
+    fn test_inline_replaces_only_code() {
+        let params = params_for(&day(15), &PartitionConfig::day("date"));
+        let sql = "SELECT '@partition_date' AS s, @partition_date AS d -- @partition_date\n\
+                   FROM t /* @partition_date */ WHERE d = @partition_date AND x = @other AND @@error.message IS NULL";
+
+        assert_eq!(
+            params.inline(sql),
+            "SELECT '@partition_date' AS s, DATE '2024-06-15' AS d -- @partition_date\n\
+             FROM t /* @partition_date */ WHERE d = DATE '2024-06-15' AND x = @other AND @@error.message IS NULL"
+        );
+    }
+
+    #[test]
+    fn test_inline_skips_escaped_and_triple_quoted_strings() {
+        let params = params_for(&day(15), &PartitionConfig::day("date"));
+        let sql = r#"SELECT 'it\'s @partition_date', """a "@partition_date" b""", @partition_date"#;
+
+        assert_eq!(
+            params.inline(sql),
+            r#"SELECT 'it\'s @partition_date', """a "@partition_date" b""", DATE '2024-06-15'"#
+        );
+    }
+
+    #[test]
+    fn test_inline_does_not_match_longer_names() {
+        let params = params_for(&day(15), &PartitionConfig::day("date"));
+        assert_eq!(params.inline("SELECT @partition_date_end"), "SELECT @partition_date_end");
+    }
+
+    #[test]
+    fn test_referenced_params() {
+        let sql = "SELECT @run_id, '@partition_date' /* @partition_end */ FROM t WHERE d = @partition_date_end AND @@row_count > 0";
+        assert_eq!(referenced_params(sql), ["run_id", "partition_date_end"]);
+    }
+}
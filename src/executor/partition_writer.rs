@@ -1,37 +1,165 @@
+use chrono::Utc;
+use std::collections::{HashMap, HashSet};
+use std::sync::Arc;
+use std::time::Instant;
+use tokio::sync::Mutex;
+use tracing::{info, warn};
 use crate::error::{BqDriftError, Result};
-use crate::dsl::{QueryDef, VersionDef};
-use crate::schema::PartitionKey;
+use crate::dsl::{QueryDef, VersionDef, WriteStrategy};
+use crate::drift::{ExecutionArtifact, ExecutionStatus, PartitionState};
+use crate::migration::{MigrationTracker, StateStore};
+use crate::schema::{PartitionKey, SchemaEvolution};
 use crate::invariant::{
     InvariantChecker, InvariantReport, CheckStatus, Severity,
     resolve_invariants_def,
 };
 use super::client::BqClient;
+use super::estimate::PartitionEstimate;
+use super::params::{QueryParams, RunContext};
+use super::strategy::{estimate_sql, execute_write, WriteTarget};
 
 #[derive(Debug, Clone)]
 pub struct PartitionWriteStats {
     pub query_name: String,
     pub version: u32,
     pub partition_key: PartitionKey,
+    pub write_strategy: WriteStrategy,
+    /// Rows affected by the write. A merge counts the rows it replaced as
+    /// well as the rows it inserted.
     pub rows_written: Option<i64>,
     pub bytes_processed: Option<i64>,
+    pub bytes_billed: Option<i64>,
+    pub slot_ms: Option<i64>,
+    /// Id of the job that wrote the partition.
+    pub job_id: Option<String>,
+    /// Time spent in write jobs, excluding invariant checks.
+    pub elapsed_ms: u64,
+    /// Query attempts retried after transient BigQuery errors.
+    pub retries: u32,
     pub invariant_report: Option<InvariantReport>,
+    /// Why the partition's state could not be recorded, when tracking is
+    /// enabled and the write itself succeeded. Drift detection won't see the
+    /// write until it is recorded by a later run.
+    pub tracking_error: Option<String>,
+}
+
+impl PartitionWriteStats {
+    /// One-line summary of the write job, e.g.
+    /// `1204 rows, 52.3 MB processed, 60.0 MB billed, 4.1s slot time, job bquxjob_1a2b in 2.3s`.
+    /// Figures BigQuery didn't report are left out.
+    pub fn job_summary(&self) -> String {
+        let mut parts = Vec::new();
+
+        if let Some(rows) = self.rows_written {
+            parts.push(format!("{} rows", rows));
+        }
+        if let Some(bytes) = self.bytes_processed {
+            parts.push(format!("{} processed", format_bytes(bytes)));
+        }
+        if let Some(bytes) = self.bytes_billed {
+            parts.push(format!("{} billed", format_bytes(bytes)));
+        }
+        if let Some(slot_ms) = self.slot_ms {
+            parts.push(format!("{:.1}s slot time", slot_ms as f64 / 1000.0));
+        }
+        if let Some(job_id) = &self.job_id {
+            parts.push(format!("job {} in {:.1}s", job_id, self.elapsed_ms as f64 / 1000.0));
+        }
+        if self.retries > 0 {
+            parts.push(format!("{} retries", self.retries));
+        }
+
+        parts.join(", ")
+    }
+}
+
+/// Human-readable byte count using binary units, e.g. `52.3 MB`.
+pub fn format_bytes(bytes: i64) -> String {
+    const UNITS: [&str; 5] = ["B", "KB", "MB", "GB", "TB"];
+    let mut value = bytes as f64;
+    let mut unit = 0;
+
+    while value >= 1024.0 && unit < UNITS.len() - 1 {
+        value /= 1024.0;
+        unit += 1;
+    }
+
+    if unit == 0 {
+        format!("{} B", bytes)
+    } else {
+        format!("{:.1} {}", value, UNITS[unit])
+    }
 }
 
 pub struct PartitionWriter {
     client: BqClient,
+    run: RunContext,
+    tracking: Option<StateTracking>,
+    /// Versions of each query whose destination table has been checked by
+    /// `ensure_destination`, behind a lock per query.
+    ensured: std::sync::Mutex<HashMap<String, EnsuredVersions>>,
+}
+
+type EnsuredVersions = Arc<Mutex<HashSet<u32>>>;
+
+/// Everything needed to persist a `PartitionState` after each write.
+struct StateTracking {
+    store: Arc<dyn StateStore>,
+    queries: Vec<QueryDef>,
+    yaml_contents: HashMap<String, String>,
 }
 
 impl PartitionWriter {
     pub fn new(client: BqClient) -> Self {
-        Self { client }
+        Self {
+            client,
+            run: RunContext::new(),
+            tracking: None,
+            ensured: Default::default(),
+        }
     }
 
+    /// Bind `@run_id` and `@run_time` from `run` instead of a fresh context.
+    pub fn with_run_context(mut self, run: RunContext) -> Self {
+        self.run = run;
+        self
+    }
+
+    pub fn run_context(&self) -> &RunContext {
+        &self.run
+    }
+
+    /// Record a `PartitionState` for every successful or failed write.
+    /// `queries` is used to resolve upstream dependencies and `yaml_contents`
+    /// (keyed by query name) supplies the YAML checksum and snapshot.
+    pub fn with_state_store(
+        mut self,
+        store: Arc<dyn StateStore>,
+        queries: Vec<QueryDef>,
+        yaml_contents: HashMap<String, String>,
+    ) -> Self {
+        self.tracking = Some(StateTracking { store, queries, yaml_contents });
+        self
+    }
+
+    /// Same as `with_state_store`, recording into the BigQuery tracking dataset.
+    pub fn with_tracker(
+        self,
+        tracker: MigrationTracker,
+        queries: Vec<QueryDef>,
+        yaml_contents: HashMap<String, String>,
+    ) -> Self {
+        self.with_state_store(Arc::new(tracker), queries, yaml_contents)
+    }
+
+    /// Write a partition using the query's `destination.write_strategy`.
     pub async fn write_partition(
         &self,
         query_def: &QueryDef,
         partition_key: PartitionKey,
     ) -> Result<PartitionWriteStats> {
-        self.write_partition_with_invariants(query_def, partition_key, true).await
+        let strategy = query_def.destination.write_strategy;
+        self.write_partition_with(query_def, partition_key, strategy, true).await
     }
 
     pub async fn write_partition_skip_invariants(
@@ -39,29 +167,81 @@ impl PartitionWriter {
         query_def: &QueryDef,
         partition_key: PartitionKey,
     ) -> Result<PartitionWriteStats> {
-        self.write_partition_with_invariants(query_def, partition_key, false).await
+        let strategy = query_def.destination.write_strategy;
+        self.write_partition_with(query_def, partition_key, strategy, false).await
+    }
+
+    /// Dry-run the statement `write_partition` would execute and report the
+    /// bytes it would process. Nothing is written or recorded.
+    pub async fn estimate_partition(
+        &self,
+        query_def: &QueryDef,
+        partition_key: PartitionKey,
+    ) -> Result<PartitionEstimate> {
+        let version = query_def
+            .get_version_for_partition(&partition_key)
+            .ok_or_else(|| BqDriftError::Partition(
+                format!("No version found for partition {}", partition_key)
+            ))?;
+
+        let sql = version.get_sql_for_date(chrono::Utc::now().date_naive());
+        let target = self.write_target(query_def);
+        let full_sql = estimate_sql(query_def.destination.write_strategy, &target, sql, &partition_key);
+        let params = QueryParams::for_partition(&partition_key, &query_def.destination.partition, &self.run);
+        let client = self.job_client(query_def, version, &partition_key);
+        let bytes_processed = client.dry_run_query(&full_sql, &params).await?;
+
+        Ok(PartitionEstimate {
+            query_name: query_def.name.clone(),
+            partition_key,
+            bytes_processed,
+        })
+    }
+
+    /// Write a partition with `strategy`, regardless of the query's configured one.
+    pub async fn write_partition_with(
+        &self,
+        query_def: &QueryDef,
+        partition_key: PartitionKey,
+        strategy: WriteStrategy,
+        run_invariants: bool,
+    ) -> Result<PartitionWriteStats> {
+        // Nothing was attempted, so there's no outcome to record.
+        if self.client.is_cancelled() {
+            return Err(BqDriftError::Cancelled("not started, the run was interrupted".to_string()));
+        }
+
+        let started = Instant::now();
+        let result = self.write_with_strategy(query_def, partition_key.clone(), strategy, run_invariants).await;
+        self.record_outcome(query_def, &partition_key, strategy, started, result).await
     }
 
-    async fn write_partition_with_invariants(
+    async fn write_with_strategy(
         &self,
         query_def: &QueryDef,
         partition_key: PartitionKey,
+        strategy: WriteStrategy,
         run_invariants: bool,
     ) -> Result<PartitionWriteStats> {
-        let partition_date = partition_key.to_naive_date();
+        let params = QueryParams::for_partition(&partition_key, &query_def.destination.partition, &self.run);
         let version = query_def
-            .get_version_for_date(partition_date)
+            .get_version_for_partition(&partition_key)
             .ok_or_else(|| BqDriftError::Partition(
                 format!("No version found for partition {}", partition_key)
             ))?;
+        self.ensure_destination(query_def, version).await?;
+        let client = self.job_client(query_def, version, &partition_key);
 
         let mut invariant_report = InvariantReport::default();
+        let target = self.write_target(query_def);
+        let job;
 
         if run_invariants {
             let (before_checks, after_checks) = resolve_invariants_def(&version.invariants);
 
             if !before_checks.is_empty() {
-                let checker = InvariantChecker::new(&self.client, &query_def.destination, partition_date);
+                let checker = InvariantChecker::new(&client, &query_def.destination, &partition_key)
+                    .with_run_context(&self.run);
                 let results = checker.run_checks(&before_checks).await?;
 
                 let has_error = results.iter().any(|r| {
@@ -78,102 +258,113 @@ impl PartitionWriter {
             }
 
             let sql = version.get_sql_for_date(chrono::Utc::now().date_naive());
-            let full_sql = self.build_merge_sql(query_def, version, sql, &partition_key);
-            self.client.execute_query(&full_sql).await?;
+            job = execute_write(&client, strategy, &target, sql, &partition_key, &params).await?;
 
             if !after_checks.is_empty() {
-                let checker = InvariantChecker::new(&self.client, &query_def.destination, partition_date);
+                let checker = InvariantChecker::new(&client, &query_def.destination, &partition_key)
+                    .with_run_context(&self.run);
                 let results = checker.run_checks(&after_checks).await?;
                 invariant_report.after = results;
             }
         } else {
             let sql = version.get_sql_for_date(chrono::Utc::now().date_naive());
-            let full_sql = self.build_merge_sql(query_def, version, sql, &partition_key);
-            self.client.execute_query(&full_sql).await?;
+            job = execute_write(&client, strategy, &target, sql, &partition_key, &params).await?;
         }
 
         Ok(PartitionWriteStats {
             query_name: query_def.name.clone(),
             version: version.version,
             partition_key,
-            rows_written: None,
-            bytes_processed: None,
+            write_strategy: strategy,
+            rows_written: job.rows_affected,
+            bytes_processed: job.bytes_processed,
+            bytes_billed: job.bytes_billed,
+            slot_ms: job.slot_ms,
+            job_id: job.job_id,
+            elapsed_ms: job.elapsed_ms,
+            retries: job.retries,
             invariant_report: if run_invariants { Some(invariant_report) } else { None },
+            tracking_error: None,
         })
     }
 
-    fn build_merge_sql(
-        &self,
-        query_def: &QueryDef,
-        _version: &VersionDef,
-        sql: &str,
-        partition_key: &PartitionKey,
-    ) -> String {
-        let dest_table = format!(
-            "{}.{}",
-            query_def.destination.dataset,
-            query_def.destination.table
-        );
+    /// Make sure the destination table exists and can take rows of
+    /// `version`. A missing table is created from the latest version, with the
+    /// query's partitioning and clustering. The table is then compared with
+    /// `version`, not the latest one, so backfilling a partition of an older
+    /// version only needs that version's columns: new NULLABLE/REPEATED fields
+    /// are added and REQUIRED fields relaxed to NULLABLE with `ALTER TABLE`,
+    /// and any other difference is refused. Each version is checked once per
+    /// writer; writers of other queries don't wait on the check.
+    pub async fn ensure_destination(&self, query_def: &QueryDef, version: &VersionDef) -> Result<()> {
+        let lock = self.ensured.lock().unwrap().entry(query_def.name.clone()).or_default().clone();
+        let mut ensured = lock.lock().await;
+        if ensured.contains(&version.version) {
+            return Ok(());
+        }
 
-        let partition_field = query_def
-            .destination
-            .partition
-            .field
-            .as_deref()
-            .unwrap_or("date");
-
-        let parameterized_sql = sql.replace("@partition_date", &format!("'{}'", partition_key.sql_value()));
-
-        let partition_condition = match partition_key {
-            PartitionKey::Hour(_) => format!(
-                "TIMESTAMP_TRUNC(target.{}, HOUR) = {}",
-                partition_field,
-                partition_key.sql_literal()
-            ),
-            PartitionKey::Day(_) => format!(
-                "target.{} = {}",
-                partition_field,
-                partition_key.sql_literal()
-            ),
-            PartitionKey::Month { .. } => format!(
-                "DATE_TRUNC(target.{}, MONTH) = {}",
-                partition_field,
-                partition_key.sql_literal()
-            ),
-            PartitionKey::Year(_) => format!(
-                "DATE_TRUNC(target.{}, YEAR) = {}",
-                partition_field,
-                partition_key.sql_literal()
-            ),
-            PartitionKey::Range(_) => format!(
-                "target.{} = {}",
-                partition_field,
-                partition_key.sql_literal()
-            ),
+        let latest = query_def.latest_version()
+            .ok_or_else(|| BqDriftError::Schema("No versions defined".into()))?;
+        let dest = &query_def.destination;
+        let client = self.client.clone().for_destination(dest);
+
+        let current = match client.get_table_schema(&dest.dataset, &dest.table).await? {
+            Some(current) => current,
+            None => {
+                info!("Creating {} from v{} of '{}'", dest.table_ref(), latest.version, query_def.name);
+                client.ensure_dataset(&dest.dataset).await?;
+                client.create_table(query_def).await?;
+                latest.schema.clone()
+            }
         };
 
-        format!(
-            r#"
-            MERGE `{dest_table}` AS target
-            USING (
-                {parameterized_sql}
-            ) AS source
-            ON FALSE
-            WHEN NOT MATCHED BY SOURCE AND {partition_condition} THEN DELETE
-            WHEN NOT MATCHED BY TARGET THEN INSERT ROW
-            "#,
-            dest_table = dest_table,
-            parameterized_sql = parameterized_sql,
-            partition_condition = partition_condition,
-        )
+        let evolution = SchemaEvolution::plan(&current, &version.schema);
+        if !evolution.is_compatible() {
+            return Err(BqDriftError::Schema(format!(
+                "{} cannot be migrated to v{} of '{}': {}",
+                dest.table_ref(),
+                version.version,
+                query_def.name,
+                evolution.incompatible.join("; ")
+            )));
+        }
+
+        let table_path = self.write_target(query_def).table_path();
+        for change in &evolution.changes {
+            info!("{}: {}", dest.table_ref(), change);
+            client.execute_query(&change.to_ddl(&table_path)).await?;
+        }
+
+        ensured.insert(version.version);
+        Ok(())
+    }
+
+    /// The client for the query's destination project and location, with the
+    /// version's job settings and partition labels applied.
+    fn job_client(&self, query_def: &QueryDef, version: &VersionDef, partition_key: &PartitionKey) -> BqClient {
+        self.client
+            .clone()
+            .for_destination(&query_def.destination)
+            .with_job_config(version.job.for_partition(&query_def.name, version.version, partition_key))
     }
 
+    fn write_target<'a>(&'a self, query_def: &'a QueryDef) -> WriteTarget<'a> {
+        WriteTarget {
+            project: Some(query_def.destination.project_or(self.client.project_id())),
+            dataset: &query_def.destination.dataset,
+            table: &query_def.destination.table,
+            partition_field: query_def.destination.partition.field.as_deref().unwrap_or("date"),
+        }
+    }
+
+    /// Replace the partition's rows with a DELETE and INSERT committed in a
+    /// single transaction. `job_id` of the stats is the script job's.
     pub async fn write_partition_truncate(
         &self,
         query_def: &QueryDef,
         partition_key: PartitionKey,
     ) -> Result<PartitionWriteStats> {
-        self.write_partition_truncate_with_invariants(query_def, partition_key, true).await
+        self.write_partition_with(query_def, partition_key, WriteStrategy::TruncatePartition, true).await
     }
 
     pub async fn write_partition_truncate_skip_invariants(
@@ -181,105 +372,155 @@ impl PartitionWriter {
         query_def: &QueryDef,
         partition_key: PartitionKey,
     ) -> Result<PartitionWriteStats> {
-        self.write_partition_truncate_with_invariants(query_def, partition_key, false).await
+        self.write_partition_with(query_def, partition_key, WriteStrategy::TruncatePartition, false).await
     }
 
-    async fn write_partition_truncate_with_invariants(
+    /// Persist the outcome of a write when tracking is enabled. A failure to
+    /// record never masks the original write error, and never fails a write
+    /// that succeeded: it is logged and reported in `tracking_error`.
+    async fn record_outcome(
         &self,
         query_def: &QueryDef,
-        partition_key: PartitionKey,
-        run_invariants: bool,
+        partition_key: &PartitionKey,
+        strategy: WriteStrategy,
+        started: Instant,
+        result: Result<PartitionWriteStats>,
     ) -> Result<PartitionWriteStats> {
-        let partition_date = partition_key.to_naive_date();
-        let version = query_def
-            .get_version_for_date(partition_date)
-            .ok_or_else(|| BqDriftError::Partition(
-                format!("No version found for partition {}", partition_key)
-            ))?;
-
-        let mut invariant_report = InvariantReport::default();
-
-        let dest_table = format!(
-            "{}.{}{}",
-            query_def.destination.dataset,
-            query_def.destination.table,
-            partition_key.decorator()
-        );
-
-        if run_invariants {
-            let (before_checks, after_checks) = resolve_invariants_def(&version.invariants);
+        let Some(tracking) = &self.tracking else {
+            return result;
+        };
 
-            if !before_checks.is_empty() {
-                let checker = InvariantChecker::new(&self.client, &query_def.destination, partition_date);
-                let results = checker.run_checks(&before_checks).await?;
+        let execution_time_ms = started.elapsed().as_millis() as i64;
 
-                let has_error = results.iter().any(|r| {
-                    r.status == CheckStatus::Failed && r.severity == Severity::Error
-                });
+        let status = match &result {
+            Ok(_) => ExecutionStatus::Success,
+            Err(BqDriftError::Cancelled(_)) => ExecutionStatus::Cancelled,
+            Err(_) => ExecutionStatus::Failed,
+        };
 
-                invariant_report.before = results;
+        let recorded = tracking
+            .record(query_def, partition_key, strategy, execution_time_ms, status, result.as_ref().ok())
+            .await;
 
-                if has_error {
-                    return Err(BqDriftError::InvariantFailed(
-                        "Before invariant check(s) failed with error severity".to_string()
-                    ));
-                }
+        match (result, recorded) {
+            (Ok(stats), Ok(())) => Ok(stats),
+            (Ok(mut stats), Err(e)) => {
+                warn!("Wrote {} for {} but could not record its state: {}", query_def.name, partition_key, e);
+                stats.tracking_error = Some(e.to_string());
+                Ok(stats)
             }
+            (Err(e), _) => Err(e),
+        }
+    }
+}
 
-            let sql = version.get_sql_for_date(chrono::Utc::now().date_naive());
-            let parameterized_sql = sql.replace("@partition_date", &format!("'{}'", partition_key.sql_value()));
-
-            let insert_sql = format!(
-                r#"
-                INSERT INTO `{dest_table}`
-                {parameterized_sql}
-                "#,
-                dest_table = dest_table,
-                parameterized_sql = parameterized_sql,
-            );
-
-            let delete_sql = format!(
-                "DELETE FROM `{}` WHERE TRUE",
-                dest_table
-            );
+impl StateTracking {
+    async fn record(
+        &self,
+        query_def: &QueryDef,
+        partition_key: &PartitionKey,
+        strategy: WriteStrategy,
+        execution_time_ms: i64,
+        status: ExecutionStatus,
+        stats: Option<&PartitionWriteStats>,
+    ) -> Result<()> {
+        let partition_date = partition_key.to_naive_date();
+        let Some(version) = query_def.get_version_for_partition(partition_key) else {
+            return Ok(());
+        };
 
-            self.client.execute_query(&delete_sql).await?;
-            self.client.execute_query(&insert_sql).await?;
+        let execution_date = Utc::now().date_naive();
+        let yaml_content = self.yaml_contents.get(&query_def.name).map(|s| s.as_str()).unwrap_or("");
+        let artifact = ExecutionArtifact::from_version(version, yaml_content, execution_date);
+        let revision = version.get_revision_for_date(execution_date);
 
-            if !after_checks.is_empty() {
-                let checker = InvariantChecker::new(&self.client, &query_def.destination, partition_date);
-                let results = checker.run_checks(&after_checks).await?;
-                invariant_report.after = results;
-            }
-        } else {
-            let sql = version.get_sql_for_date(chrono::Utc::now().date_naive());
-            let parameterized_sql = sql.replace("@partition_date", &format!("'{}'", partition_key.sql_value()));
-
-            let insert_sql = format!(
-                r#"
-                INSERT INTO `{dest_table}`
-                {parameterized_sql}
-                "#,
-                dest_table = dest_table,
-                parameterized_sql = parameterized_sql,
-            );
-
-            let delete_sql = format!(
-                "DELETE FROM `{}` WHERE TRUE",
-                dest_table
-            );
-
-            self.client.execute_query(&delete_sql).await?;
-            self.client.execute_query(&insert_sql).await?;
-        }
+        let dependencies = revision.map(|r| &r.dependencies).unwrap_or(&version.dependencies);
+        let upstream_names: Vec<String> = query_def
+            .upstream_queries(dependencies, &self.queries)
+            .into_iter()
+            .map(|q| q.name.clone())
+            .collect();
+        let upstream_states = self.store.latest_executions(&upstream_names, partition_key).await?;
 
-        Ok(PartitionWriteStats {
+        let state = PartitionState {
             query_name: query_def.name.clone(),
+            partition_key: partition_key.clone(),
+            partition_date,
             version: version.version,
-            partition_key,
+            sql_revision: revision.map(|r| r.revision),
+            effective_from: revision.map(|r| r.effective_from).unwrap_or(version.effective_from),
+            sql_checksum: artifact.sql_checksum,
+            schema_checksum: artifact.schema_checksum,
+            yaml_checksum: artifact.yaml_checksum,
+            executed_sql_b64: Some(artifact.sql_compressed),
+            executed_yaml_b64: Some(artifact.yaml_compressed),
+            upstream_states,
+            executed_at: Utc::now(),
+            execution_time_ms: Some(execution_time_ms),
+            rows_written: stats.and_then(|s| s.rows_written),
+            bytes_processed: stats.and_then(|s| s.bytes_processed),
+            bytes_billed: stats.and_then(|s| s.bytes_billed),
+            slot_ms: stats.and_then(|s| s.slot_ms),
+            job_id: stats.and_then(|s| s.job_id.clone()),
+            write_strategy: Some(strategy),
+            status,
+        };
+
+        self.store.record_state(&state).await
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::NaiveDate;
+
+    fn stats() -> PartitionWriteStats {
+        PartitionWriteStats {
+            query_name: "daily_stats".to_string(),
+            version: 1,
+            partition_key: PartitionKey::Day(NaiveDate::from_ymd_opt(2024, 6, 15).unwrap()),
+            write_strategy: WriteStrategy::Merge,
             rows_written: None,
             bytes_processed: None,
-            invariant_report: if run_invariants { Some(invariant_report) } else { None },
-        })
+            bytes_billed: None,
+            slot_ms: None,
+            job_id: None,
+            elapsed_ms: 0,
+            retries: 0,
+            invariant_report: None,
+            tracking_error: None,
+        }
+    }
+
+    #[test]
+    fn test_format_bytes() {
+        assert_eq!(format_bytes(512), "512 B");
+        assert_eq!(format_bytes(10 * 1024 * 1024), "10.0 MB");
+        assert_eq!(format_bytes(1536 * 1024 * 1024), "1.5 GB");
+    }
+
+    #[test]
+    fn test_job_summary() {
+        let stats = PartitionWriteStats {
+            rows_written: Some(1204),
+            bytes_processed: Some(10 * 1024 * 1024),
+            bytes_billed: Some(10 * 1024 * 1024),
+            slot_ms: Some(4100),
+            job_id: Some("bquxjob_1a2b".to_string()),
+            elapsed_ms: 2300,
+            retries: 1,
+            ..stats()
+        };
+
+        assert_eq!(
+            stats.job_summary(),
+            "1204 rows, 10.0 MB processed, 10.0 MB billed, 4.1s slot time, job bquxjob_1a2b in 2.3s, 1 retries"
+        );
+    }
+
+    #[test]
+    fn test_job_summary_without_statistics() {
+        assert_eq!(stats().job_summary(), "");
     }
 }
@@ -0,0 +1,165 @@
+use std::collections::HashSet;
+use std::collections::hash_map::RandomState;
+use std::hash::{BuildHasher, Hasher};
+use std::time::Duration;
+use crate::error::BigQueryError;
+
+/// Error kinds retried by default. These usually clear up on their own:
+/// rate limits, timeouts, dropped connections and short-lived resource pressure.
+pub const DEFAULT_RETRYABLE_ERRORS: &[&str] = &[
+    "QUOTA_EXCEEDED",
+    "TIMEOUT",
+    "CONNECTION_FAILED",
+    "RESOURCES_EXCEEDED",
+];
+
+/// How `BqClient` retries failed BigQuery calls.
+///
+/// Attempt `n` (1-based) that fails with a retryable error waits
+/// `initial_backoff * multiplier^(n-1)`, capped at `max_backoff`, before the
+/// next attempt. With jitter enabled the wait is drawn from the upper half of
+/// that interval so concurrent writers don't retry in lockstep.
+#[derive(Debug, Clone)]
+pub struct RetryPolicy {
+    pub max_attempts: u32,
+    pub initial_backoff: Duration,
+    pub max_backoff: Duration,
+    pub multiplier: f64,
+    pub jitter: bool,
+    retryable: HashSet<&'static str>,
+}
+
+impl Default for RetryPolicy {
+    fn default() -> Self {
+        Self {
+            max_attempts: 4,
+            initial_backoff: Duration::from_secs(1),
+            max_backoff: Duration::from_secs(30),
+            multiplier: 2.0,
+            jitter: true,
+            retryable: DEFAULT_RETRYABLE_ERRORS.iter().copied().collect(),
+        }
+    }
+}
+
+impl RetryPolicy {
+    /// Fail on the first error.
+    pub fn none() -> Self {
+        Self::default().with_max_attempts(1)
+    }
+
+    pub fn with_max_attempts(mut self, max_attempts: u32) -> Self {
+        self.max_attempts = max_attempts.max(1);
+        self
+    }
+
+    pub fn with_backoff(mut self, initial: Duration, max: Duration) -> Self {
+        self.initial_backoff = initial;
+        self.max_backoff = max;
+        self
+    }
+
+    pub fn with_multiplier(mut self, multiplier: f64) -> Self {
+        self.multiplier = multiplier;
+        self
+    }
+
+    pub fn with_jitter(mut self, jitter: bool) -> Self {
+        self.jitter = jitter;
+        self
+    }
+
+    /// Turn retries on or off for one error kind, named by `BigQueryError::error_code`.
+    pub fn with_retryable(mut self, error_code: &'static str, retry: bool) -> Self {
+        if retry {
+            self.retryable.insert(error_code);
+        } else {
+            self.retryable.remove(error_code);
+        }
+        self
+    }
+
+    pub fn is_retryable(&self, error: &BigQueryError) -> bool {
+        self.retryable.contains(error.error_code())
+    }
+
+    /// Delay before the attempt following failed attempt `attempt` (1-based).
+    pub fn backoff(&self, attempt: u32) -> Duration {
+        let exponent = attempt.saturating_sub(1).min(32) as i32;
+        let base = self.initial_backoff.as_secs_f64() * self.multiplier.powi(exponent);
+        let capped = base.min(self.max_backoff.as_secs_f64()).max(0.0);
+
+        let delay = if self.jitter {
+            capped / 2.0 + capped / 2.0 * random_fraction()
+        } else {
+            capped
+        };
+
+        Duration::from_secs_f64(delay)
+    }
+}
+
+/// A value in `[0, 1)` from the standard library's randomly seeded hasher.
+fn random_fraction() -> f64 {
+    let bits = RandomState::new().build_hasher().finish();
+    (bits >> 11) as f64 / (1u64 << 53) as f64
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_backoff_grows_and_caps() {
+        let policy = RetryPolicy::default()
+            .with_backoff(Duration::from_millis(100), Duration::from_millis(500))
+            .with_jitter(false);
+
+        assert_eq!(policy.backoff(1), Duration::from_millis(100));
+        assert_eq!(policy.backoff(2), Duration::from_millis(200));
+        assert_eq!(policy.backoff(3), Duration::from_millis(400));
+        assert_eq!(policy.backoff(4), Duration::from_millis(500));
+    }
+
+    #[test]
+    fn test_backoff_jitter_stays_in_upper_half() {
+        let policy = RetryPolicy::default()
+            .with_backoff(Duration::from_secs(2), Duration::from_secs(2));
+
+        for _ in 0..20 {
+            let delay = policy.backoff(1);
+            assert!(delay >= Duration::from_secs(1));
+            assert!(delay <= Duration::from_secs(2));
+        }
+    }
+
+    #[test]
+    fn test_default_retryable_errors() {
+        let policy = RetryPolicy::default();
+
+        assert!(policy.is_retryable(&BigQueryError::QuotaExceeded {
+            quota_type: "rateLimitExceeded".into(),
+            message: "m".into(),
+        }));
+        assert!(policy.is_retryable(&BigQueryError::ConnectionFailed { reason: "reset".into() }));
+        assert!(!policy.is_retryable(&BigQueryError::InvalidQuery {
+            sql_preview: "".into(),
+            message: "Syntax error".into(),
+            location: None,
+        }));
+    }
+
+    #[test]
+    fn test_with_retryable_overrides_error_kind() {
+        let policy = RetryPolicy::default().with_retryable("RESOURCES_EXCEEDED", false);
+        assert!(!policy.is_retryable(&BigQueryError::ResourcesExceeded {
+            message: "m".into(),
+            suggestion: "s".into(),
+        }));
+    }
+
+    #[test]
+    fn test_none_makes_single_attempt() {
+        assert_eq!(RetryPolicy::none().max_attempts, 1);
+    }
+}
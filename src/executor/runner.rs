@@ -1,14 +1,45 @@
 use chrono::{NaiveDate, Utc};
-use crate::error::Result;
-use crate::dsl::QueryDef;
+use futures::stream::{self, FuturesUnordered, StreamExt};
+use std::collections::HashMap;
+use std::fmt;
+use std::future::Future;
+use std::sync::Arc;
+use std::sync::atomic::{AtomicBool, Ordering};
+use crate::error::{BqDriftError, Result};
+use crate::dsl::{QueryDef, QueryGraph};
+use crate::drift::PartitionDrift;
+use crate::migration::{MigrationTracker, StateStore};
 use crate::schema::PartitionKey;
 use super::client::BqClient;
 use super::partition_writer::{PartitionWriter, PartitionWriteStats};
+use super::estimate::CostEstimate;
 
-#[derive(Debug)]
+#[derive(Debug, Default)]
 pub struct RunReport {
     pub stats: Vec<PartitionWriteStats>,
     pub failures: Vec<RunFailure>,
+    /// Partitions not attempted: a query they read from failed, or the
+    /// backfill stopped at `max_failures` first.
+    pub skipped: Vec<RunSkip>,
+    /// Partitions whose job was cancelled by an interrupt or a job timeout,
+    /// or that never started because the run was interrupted.
+    pub cancelled: Vec<RunFailure>,
+}
+
+impl RunReport {
+    /// Sort a write error into `failures` or `cancelled`.
+    fn push_error(&mut self, query_name: &str, partition_key: PartitionKey, error: BqDriftError) {
+        let entry = RunFailure {
+            query_name: query_name.to_string(),
+            partition_key,
+            error: error.to_string(),
+        };
+
+        match error {
+            BqDriftError::Cancelled(_) => self.cancelled.push(entry),
+            _ => self.failures.push(entry),
+        }
+    }
 }
 
 #[derive(Debug)]
@@ -18,6 +49,69 @@ pub struct RunFailure {
     pub error: String,
 }
 
+#[derive(Debug)]
+pub struct RunSkip {
+    pub query_name: String,
+    pub partition_key: PartitionKey,
+    pub reason: SkipReason,
+}
+
+/// Why a partition was not attempted.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum SkipReason {
+    /// The named upstream query failed.
+    UpstreamFailed(String),
+    /// The backfill had already failed this many partitions.
+    MaxFailures(usize),
+}
+
+impl fmt::Display for SkipReason {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            SkipReason::UpstreamFailed(upstream) => write!(f, "upstream '{}' failed", upstream),
+            SkipReason::MaxFailures(max) => write!(f, "backfill stopped after {} failures", max),
+        }
+    }
+}
+
+/// The partitions a cascading backfill writes for one query.
+#[derive(Debug, Clone, PartialEq)]
+pub struct BackfillStep {
+    pub query_name: String,
+    pub partitions: Vec<PartitionKey>,
+}
+
+/// How a backfill schedules its partitions.
+#[derive(Debug, Clone)]
+pub struct BackfillOptions {
+    /// Partitions written at the same time. Values below 1 are treated as 1.
+    pub concurrency: usize,
+    /// Stop starting new partitions once this many have failed. Partitions
+    /// already running are allowed to finish; the rest are reported as skipped.
+    pub max_failures: Option<usize>,
+}
+
+impl Default for BackfillOptions {
+    fn default() -> Self {
+        Self {
+            concurrency: 1,
+            max_failures: None,
+        }
+    }
+}
+
+impl BackfillOptions {
+    pub fn with_concurrency(mut self, concurrency: usize) -> Self {
+        self.concurrency = concurrency;
+        self
+    }
+
+    pub fn with_max_failures(mut self, max_failures: Option<usize>) -> Self {
+        self.max_failures = max_failures;
+        self
+    }
+}
+
 pub struct Runner {
     writer: PartitionWriter,
     queries: Vec<QueryDef>,
@@ -31,6 +125,17 @@ impl Runner {
         }
     }
 
+    /// Record a `PartitionState` in `store` after every partition write.
+    pub fn with_state_store(mut self, store: Arc<dyn StateStore>, yaml_contents: HashMap<String, String>) -> Self {
+        self.writer = self.writer.with_state_store(store, self.queries.clone(), yaml_contents);
+        self
+    }
+
+    /// Record a `PartitionState` in the tracking dataset after every partition write.
+    pub fn with_tracker(self, tracker: MigrationTracker, yaml_contents: HashMap<String, String>) -> Self {
+        self.with_state_store(Arc::new(tracker), yaml_contents)
+    }
+
     pub async fn run_today(&self) -> Result<RunReport> {
         let today = Utc::now().date_naive();
         self.run_for_date(today).await
@@ -40,22 +145,56 @@ impl Runner {
         self.run_for_partition(PartitionKey::Day(date)).await
     }
 
+    /// Write `partition_key` for every query in dependency order. A query
+    /// starts as soon as all the queries it reads from have been written, so
+    /// independent branches run in parallel. When a query fails, everything
+    /// downstream of it is skipped. Dependencies come from the versions that
+    /// write this partition. Fails up front on a dependency cycle.
     pub async fn run_for_partition(&self, partition_key: PartitionKey) -> Result<RunReport> {
-        let mut stats = Vec::new();
-        let mut failures = Vec::new();
+        let graph = QueryGraph::for_partitions(&self.queries, std::slice::from_ref(&partition_key));
+        graph.topological_order()?;
 
-        for query in &self.queries {
-            match self.writer.write_partition(query, partition_key.clone()).await {
-                Ok(s) => stats.push(s),
-                Err(e) => failures.push(RunFailure {
-                    query_name: query.name.clone(),
-                    partition_key: partition_key.clone(),
-                    error: e.to_string(),
-                }),
+        let mut report = RunReport::default();
+        let mut waiting_on: Vec<usize> = (0..graph.len()).map(|i| graph.upstream(i).len()).collect();
+        let mut skipped = vec![false; graph.len()];
+
+        let mut running: FuturesUnordered<_> = (0..graph.len())
+            .filter(|&i| waiting_on[i] == 0)
+            .map(|i| self.write_node(i, partition_key.clone()))
+            .collect();
+
+        while let Some((node, result)) = running.next().await {
+            match result {
+                Ok(s) => {
+                    report.stats.push(s);
+                    for &next in graph.downstream(node) {
+                        waiting_on[next] -= 1;
+                        if waiting_on[next] == 0 && !skipped[next] {
+                            running.push(self.write_node(next, partition_key.clone()));
+                        }
+                    }
+                }
+                Err(e) => {
+                    report.push_error(graph.name(node), partition_key.clone(), e);
+                    for next in graph.descendants(node) {
+                        if !skipped[next] {
+                            skipped[next] = true;
+                            report.skipped.push(RunSkip {
+                                query_name: graph.name(next).to_string(),
+                                partition_key: partition_key.clone(),
+                                reason: SkipReason::UpstreamFailed(graph.name(node).to_string()),
+                            });
+                        }
+                    }
+                }
             }
         }
 
-        Ok(RunReport { stats, failures })
+        Ok(report)
+    }
+
+    async fn write_node(&self, node: usize, partition_key: PartitionKey) -> (usize, Result<PartitionWriteStats>) {
+        (node, self.writer.write_partition(&self.queries[node], partition_key).await)
     }
 
     pub async fn run_query(&self, query_name: &str, date: NaiveDate) -> Result<PartitionWriteStats> {
@@ -94,36 +233,468 @@ impl Runner {
         to: PartitionKey,
         interval: Option<i64>,
     ) -> Result<RunReport> {
-        let query = self.queries
-            .iter()
-            .find(|q| q.name == query_name)
-            .ok_or_else(|| crate::error::BqDriftError::DslParse(
-                format!("Query '{}' not found", query_name)
-            ))?;
+        self.backfill_partitions_with(query_name, from, to, interval, &BackfillOptions::default()).await
+    }
+
+    /// Backfill with up to `options.concurrency` partitions in flight. Every
+    /// partition gets its own entry in the report, ordered by partition key.
+    pub async fn backfill_partitions_with(
+        &self,
+        query_name: &str,
+        from: PartitionKey,
+        to: PartitionKey,
+        interval: Option<i64>,
+        options: &BackfillOptions,
+    ) -> Result<RunReport> {
+        let query = self.find_query(query_name)?;
+        let keys = partition_keys(from, &to, interval);
+        let stopped = AtomicBool::new(false);
+        let mut failed = 0;
+
+        Ok(self.backfill_keys(query, keys, options, &stopped, &mut failed).await)
+    }
+
+    /// Backfill `query_name` and then every query downstream of it over the
+    /// same range. A query's partitions start once its upstream queries have
+    /// finished the range; a partition is skipped when an upstream partition
+    /// it overlaps failed. `options` applies across the whole cascade.
+    pub async fn backfill_cascade(
+        &self,
+        query_name: &str,
+        from: PartitionKey,
+        to: PartitionKey,
+        interval: Option<i64>,
+        options: &BackfillOptions,
+    ) -> Result<RunReport> {
+        let plan = cascade_plan(&self.queries, query_name, &from, &to, interval)?;
+        let graph = QueryGraph::new(&self.queries);
+
+        let mut report = RunReport::default();
+        let stopped = AtomicBool::new(false);
+        let mut failed = 0;
+        // Partitions not written, with the query whose failure caused it.
+        let mut broken: Vec<(usize, PartitionKey, String)> = Vec::new();
+
+        for step in plan {
+            let node = graph.index_of(&step.query_name).ok_or_else(|| {
+                crate::error::BqDriftError::DslParse(format!("Query '{}' not found", step.query_name))
+            })?;
+            let query = &self.queries[node];
+            let mut keys = Vec::new();
+
+            for key in step.partitions {
+                let cause = broken
+                    .iter()
+                    .find(|(u, k, _)| graph.upstream(node).contains(u) && overlaps(k, &key))
+                    .map(|(_, _, cause)| cause.clone());
+
+                match cause {
+                    Some(cause) => {
+                        broken.push((node, key.clone(), cause.clone()));
+                        report.skipped.push(RunSkip {
+                            query_name: query.name.clone(),
+                            partition_key: key,
+                            reason: SkipReason::UpstreamFailed(cause),
+                        });
+                    }
+                    None => keys.push(key),
+                }
+            }
+
+            let written = self.backfill_keys(query, keys, options, &stopped, &mut failed).await;
+            broken.extend(
+                written.failures.iter()
+                    .chain(&written.cancelled)
+                    .map(|f| (node, f.partition_key.clone(), query.name.clone()))
+            );
+            report.stats.extend(written.stats);
+            report.failures.extend(written.failures);
+            report.skipped.extend(written.skipped);
+            report.cancelled.extend(written.cancelled);
+        }
+
+        Ok(report)
+    }
+
+    /// Write `keys` of `query` as `schedule_backfill` schedules them. Results are
+    /// ordered by partition key.
+    async fn backfill_keys(
+        &self,
+        query: &QueryDef,
+        keys: Vec<PartitionKey>,
+        options: &BackfillOptions,
+        stopped: &AtomicBool,
+        failed: &mut usize,
+    ) -> RunReport {
+        let results = schedule_backfill(keys, options, stopped, failed, move |key| {
+            self.writer.write_partition(query, key)
+        }).await;
+
+        let mut report = RunReport::default();
+
+        for (key, result) in results {
+            match result {
+                Some(Ok(s)) => report.stats.push(s),
+                Some(Err(e)) => report.push_error(&query.name, key, e),
+                None => report.skipped.push(RunSkip {
+                    query_name: query.name.clone(),
+                    partition_key: key,
+                    reason: SkipReason::MaxFailures(options.max_failures.unwrap_or(*failed)),
+                }),
+            }
+        }
+
+        report
+    }
+
+    /// Re-run drifted partitions. Upstream queries are rebuilt before the
+    /// queries that read from them; partitions of one query run in key order.
+    /// Partitions with no applicable version are skipped.
+    pub async fn sync_partitions(
+        &self,
+        drifted: &[&PartitionDrift],
+        skip_invariants: bool,
+    ) -> Result<RunReport> {
+        let mut report = RunReport::default();
+
+        for (query, keys) in self.sync_plan(drifted)? {
+            for key in keys {
+                let result = if skip_invariants {
+                    self.writer.write_partition_skip_invariants(query, key.clone()).await
+                } else {
+                    self.writer.write_partition(query, key.clone()).await
+                };
+
+                match result {
+                    Ok(s) => report.stats.push(s),
+                    Err(e) => report.push_error(&query.name, key, e),
+                }
+            }
+        }
+
+        Ok(report)
+    }
+
+    /// Drifted partitions grouped by query, in the order `sync_partitions`
+    /// runs them. Fails if the dependencies of the versions writing the
+    /// drifted partitions form a cycle.
+    fn sync_plan(&self, drifted: &[&PartitionDrift]) -> Result<Vec<(&QueryDef, Vec<PartitionKey>)>> {
+        let mut drifted_keys: Vec<PartitionKey> = drifted.iter().map(|d| d.partition_key.clone()).collect();
+        drifted_keys.sort();
+        drifted_keys.dedup();
+        let order = QueryGraph::for_partitions(&self.queries, &drifted_keys).topological_order()?;
+
+        Ok(order
+            .into_iter()
+            .map(|node| {
+                let query = &self.queries[node];
+                let mut keys: Vec<PartitionKey> = drifted
+                    .iter()
+                    .filter(|d| d.query_name == query.name)
+                    .filter(|d| query.get_version_for_partition(&d.partition_key).is_some())
+                    .map(|d| d.partition_key.clone())
+                    .collect();
+                keys.sort();
+                keys.dedup();
+                (query, keys)
+            })
+            .filter(|(_, keys)| !keys.is_empty())
+            .collect())
+    }
+
+    /// Estimate bytes processed by `run_for_partition` without writing anything.
+    pub async fn estimate_for_partition(&self, partition_key: PartitionKey) -> Result<CostEstimate> {
+        let mut estimate = CostEstimate::default();
+
+        for query in &self.queries {
+            estimate.merge(self.estimate_keys(query, vec![partition_key.clone()], 1).await);
+        }
+
+        Ok(estimate)
+    }
+
+    pub async fn estimate_query_partition(&self, query_name: &str, partition_key: PartitionKey) -> Result<CostEstimate> {
+        let query = self.find_query(query_name)?;
+        Ok(self.estimate_keys(query, vec![partition_key], 1).await)
+    }
+
+    /// Estimate bytes processed by a backfill, dry-running up to
+    /// `options.concurrency` partitions at a time.
+    pub async fn estimate_backfill(
+        &self,
+        query_name: &str,
+        from: PartitionKey,
+        to: PartitionKey,
+        interval: Option<i64>,
+        options: &BackfillOptions,
+    ) -> Result<CostEstimate> {
+        let query = self.find_query(query_name)?;
+        let keys = partition_keys(from, &to, interval);
+        Ok(self.estimate_keys(query, keys, options.concurrency).await)
+    }
+
+    /// Estimate bytes processed by `backfill_cascade`.
+    pub async fn estimate_cascade(
+        &self,
+        query_name: &str,
+        from: PartitionKey,
+        to: PartitionKey,
+        interval: Option<i64>,
+        options: &BackfillOptions,
+    ) -> Result<CostEstimate> {
+        let mut estimate = CostEstimate::default();
+
+        for step in cascade_plan(&self.queries, query_name, &from, &to, interval)? {
+            let query = self.find_query(&step.query_name)?;
+            estimate.merge(self.estimate_keys(query, step.partitions, options.concurrency).await);
+        }
+
+        Ok(estimate)
+    }
 
-        let mut stats = Vec::new();
-        let mut failures = Vec::new();
-        let mut current = from;
+    pub async fn estimate_sync(&self, drifted: &[&PartitionDrift]) -> Result<CostEstimate> {
+        let mut estimate = CostEstimate::default();
 
-        while current <= to {
-            match self.writer.write_partition(query, current.clone()).await {
-                Ok(s) => stats.push(s),
-                Err(e) => failures.push(RunFailure {
-                    query_name: query_name.to_string(),
-                    partition_key: current.clone(),
+        for (query, keys) in self.sync_plan(drifted)? {
+            estimate.merge(self.estimate_keys(query, keys, 1).await);
+        }
+
+        Ok(estimate)
+    }
+
+    async fn estimate_keys(&self, query: &QueryDef, keys: Vec<PartitionKey>, concurrency: usize) -> CostEstimate {
+        let mut results = stream::iter(keys.into_iter().map(|key| async move {
+            let result = self.writer.estimate_partition(query, key.clone()).await;
+            (key, result)
+        }))
+        .buffer_unordered(concurrency.max(1));
+
+        let mut estimate = CostEstimate::default();
+
+        while let Some((key, result)) = results.next().await {
+            match result {
+                Ok(partition) => estimate.partitions.push(partition),
+                Err(e) => estimate.failures.push(RunFailure {
+                    query_name: query.name.clone(),
+                    partition_key: key,
                     error: e.to_string(),
                 }),
             }
-            current = match interval {
-                Some(i) => current.next_by(i),
-                None => current.next(),
-            };
         }
 
-        Ok(RunReport { stats, failures })
+        estimate.partitions.sort_by(|a, b| a.partition_key.cmp(&b.partition_key));
+        estimate.failures.sort_by(|a, b| a.partition_key.cmp(&b.partition_key));
+        estimate
+    }
+
+    fn find_query(&self, query_name: &str) -> Result<&QueryDef> {
+        self.queries
+            .iter()
+            .find(|q| q.name == query_name)
+            .ok_or_else(|| crate::error::BqDriftError::DslParse(
+                format!("Query '{}' not found", query_name)
+            ))
     }
 
     pub fn queries(&self) -> &[QueryDef] {
         &self.queries
     }
 }
+
+/// Every partition key from `from` to `to` inclusive.
+pub(crate) fn partition_keys(from: PartitionKey, to: &PartitionKey, interval: Option<i64>) -> Vec<PartitionKey> {
+    let mut keys = Vec::new();
+    let mut current = from;
+
+    while current <= *to {
+        let next = match interval {
+            Some(i) => current.next_by(i),
+            None => current.next(),
+        };
+        keys.push(current.clone());
+        if next == current {
+            break;
+        }
+        current = next;
+    }
+
+    keys
+}
+
+/// The partitions `Runner::backfill_cascade` writes: `query_name` and every
+/// query downstream of it, in dependency order. Each query gets the range
+/// converted to its own partition type; partitions with no applicable
+/// version are left out. Fails on a dependency cycle, or when a downstream
+/// query's partitioning can't express the range (e.g. a date range
+/// cascading into an integer-range partitioned table).
+pub fn cascade_plan(
+    queries: &[QueryDef],
+    query_name: &str,
+    from: &PartitionKey,
+    to: &PartitionKey,
+    interval: Option<i64>,
+) -> Result<Vec<BackfillStep>> {
+    let graph = QueryGraph::new(queries);
+    let order = graph.topological_order()?;
+    let root = graph.index_of(query_name).ok_or_else(|| crate::error::BqDriftError::DslParse(
+        format!("Query '{}' not found", query_name)
+    ))?;
+
+    let mut members = graph.descendants(root);
+    members.push(root);
+
+    let mut plan = Vec::new();
+    for node in order.into_iter().filter(|n| members.contains(n)) {
+        let query = &queries[node];
+        let partition_type = &query.destination.partition.partition_type;
+        let bound = |key: &PartitionKey, end: bool| {
+            PartitionKey::parse_bound(&key.to_string(), partition_type, end).map_err(|e| {
+                crate::error::BqDriftError::Partition(format!("Cannot cascade to '{}': {}", query.name, e))
+            })
+        };
+
+        let partitions: Vec<PartitionKey> = partition_keys(bound(from, false)?, &bound(to, true)?, interval)
+            .into_iter()
+            .filter(|key| query.get_version_for_partition(key).is_some())
+            .collect();
+
+        if !partitions.is_empty() {
+            plan.push(BackfillStep { query_name: query.name.clone(), partitions });
+        }
+    }
+
+    Ok(plan)
+}
+
+/// Run `write` for `keys` with up to `options.concurrency` in flight,
+/// counting failures into `failed` and setting `stopped` once
+/// `options.max_failures` is reached. Cancelled writes don't count as
+/// failures. Keys not started because the backfill stopped come back with
+/// no result. Results are ordered by partition key.
+pub(crate) async fn schedule_backfill<T, F, Fut>(
+    keys: Vec<PartitionKey>,
+    options: &BackfillOptions,
+    stopped: &AtomicBool,
+    failed: &mut usize,
+    write: F,
+) -> Vec<(PartitionKey, Option<Result<T>>)>
+where
+    F: Fn(PartitionKey) -> Fut,
+    Fut: Future<Output = Result<T>>,
+{
+    let write = &write;
+    let mut results = stream::iter(keys.into_iter().map(move |key| async move {
+        if stopped.load(Ordering::SeqCst) {
+            return (key, None);
+        }
+        let result = write(key.clone()).await;
+        (key, Some(result))
+    }))
+    .buffer_unordered(options.concurrency.max(1));
+
+    let mut outcomes = Vec::new();
+
+    while let Some((key, result)) = results.next().await {
+        if let Some(Err(e)) = &result {
+            if !matches!(e, BqDriftError::Cancelled(_)) {
+                *failed += 1;
+                if options.max_failures.is_some_and(|max| *failed >= max) {
+                    stopped.store(true, Ordering::SeqCst);
+                }
+            }
+        }
+        outcomes.push((key, result));
+    }
+
+    outcomes.sort_by(|a, b| a.0.cmp(&b.0));
+    outcomes
+}
+
+/// Whether `key` covers any of the time `upstream_key` does. The two may be
+/// of different partition types; keys that can't be compared overlap.
+fn overlaps(upstream_key: &PartitionKey, key: &PartitionKey) -> bool {
+    let partition_type = key.partition_type();
+    let bound = |end| PartitionKey::parse_bound(&upstream_key.to_string(), &partition_type, end);
+
+    match (bound(false), bound(true)) {
+        (Ok(first), Ok(last)) => first <= *key && *key <= last,
+        _ => true,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::dsl::test_queries::create_test_query;
+    use crate::schema::PartitionConfig;
+
+    #[test]
+    fn test_partition_keys() {
+        let from = PartitionKey::Day(NaiveDate::from_ymd_opt(2024, 1, 30).unwrap());
+        let to = PartitionKey::Day(NaiveDate::from_ymd_opt(2024, 2, 2).unwrap());
+        let keys = partition_keys(from, &to, None);
+        assert_eq!(keys.len(), 4);
+        assert_eq!(keys.last(), Some(&to));
+
+        let keys = partition_keys(PartitionKey::Range(0), &PartitionKey::Range(25), Some(10));
+        assert_eq!(keys, vec![PartitionKey::Range(0), PartitionKey::Range(10), PartitionKey::Range(20)]);
+    }
+
+    #[test]
+    fn test_backfill_options_default_is_serial() {
+        let options = BackfillOptions::default();
+        assert_eq!(options.concurrency, 1);
+        assert!(options.max_failures.is_none());
+    }
+
+    #[test]
+    fn test_cascade_plan_follows_downstream_queries() {
+        let mut monthly = create_test_query("monthly", &["analytics.daily"]);
+        monthly.destination.partition = PartitionConfig::month("month");
+        let queries = vec![
+            create_test_query("report", &["analytics.weekly"]),
+            create_test_query("weekly", &["analytics.daily"]),
+            create_test_query("daily", &["raw.events"]),
+            create_test_query("unrelated", &["raw.events"]),
+            monthly,
+        ];
+        let from = PartitionKey::Day(NaiveDate::from_ymd_opt(2024, 3, 30).unwrap());
+        let to = PartitionKey::Day(NaiveDate::from_ymd_opt(2024, 4, 2).unwrap());
+
+        let plan = cascade_plan(&queries, "daily", &from, &to, None).unwrap();
+
+        let names: Vec<&str> = plan.iter().map(|s| s.query_name.as_str()).collect();
+        assert_eq!(names, vec!["daily", "weekly", "monthly", "report"]);
+        assert_eq!(plan[0].partitions.len(), 4);
+        assert_eq!(plan[0].partitions, plan[3].partitions);
+        assert_eq!(plan[2].partitions, vec![
+            PartitionKey::Month { year: 2024, month: 3 },
+            PartitionKey::Month { year: 2024, month: 4 },
+        ]);
+    }
+
+    #[test]
+    fn test_overlaps_across_partition_types() {
+        let day = PartitionKey::Day(NaiveDate::from_ymd_opt(2024, 3, 15).unwrap());
+        let march = PartitionKey::Month { year: 2024, month: 3 };
+        let april = PartitionKey::Month { year: 2024, month: 4 };
+
+        assert!(overlaps(&day, &march));
+        assert!(!overlaps(&day, &april));
+        assert!(overlaps(&march, &day));
+        assert!(overlaps(&day, &day));
+    }
+
+    #[test]
+    fn test_cancelled_errors_are_reported_separately() {
+        let key = PartitionKey::Day(NaiveDate::from_ymd_opt(2024, 3, 1).unwrap());
+        let mut report = RunReport::default();
+
+        report.push_error("daily", key.clone(), BqDriftError::Cancelled("job j1 was interrupted".to_string()));
+        report.push_error("daily", key, BqDriftError::Executor("boom".to_string()));
+
+        assert_eq!(report.cancelled.len(), 1);
+        assert_eq!(report.cancelled[0].error, "Cancelled: job j1 was interrupted");
+        assert_eq!(report.failures.len(), 1);
+    }
+}
@@ -5,6 +5,8 @@ use crate::schema::PartitionKey;
 use crate::invariant::{InvariantChecker, InvariantReport, CheckStatus, Severity, resolve_invariants_def};
 use crate::dsl::Destination;
 use super::client::BqClient;
+use super::params::{QueryParams, RunContext};
+use super::strategy::{execute_write, WriteTarget};
 
 const SCRATCH_DATASET: &str = "bqdrift_scratch";
 
@@ -30,11 +32,18 @@ impl ScratchConfig {
 pub struct ScratchWriter {
     client: BqClient,
     config: ScratchConfig,
+    run: RunContext,
 }
 
 impl ScratchWriter {
     pub fn new(client: BqClient, config: ScratchConfig) -> Self {
-        Self { client, config }
+        Self { client, config, run: RunContext::new() }
+    }
+
+    /// Bind `@run_id` and `@run_time` from `run` instead of a fresh context.
+    pub fn with_run_context(mut self, run: RunContext) -> Self {
+        self.run = run;
+        self
     }
 
     pub fn scratch_table_name(query_def: &QueryDef) -> String {
@@ -47,11 +56,25 @@ impl ScratchWriter {
     pub fn scratch_table_fqn(&self, query_def: &QueryDef) -> String {
         format!("{}.{}.{}",
             self.config.project,
-            SCRATCH_DATASET,
+            self.scratch_dataset(query_def),
             Self::scratch_table_name(query_def)
         )
     }
 
+    /// Dataset holding the query's scratch table. A job can only write to
+    /// datasets in the location it runs in, so queries located elsewhere than
+    /// the client get their own `bqdrift_scratch_<location>` dataset.
+    pub fn scratch_dataset(&self, query_def: &QueryDef) -> String {
+        match &query_def.destination.location {
+            Some(location) if !self.client.config().location.as_ref()
+                .is_some_and(|own| own.eq_ignore_ascii_case(location)) =>
+            {
+                format!("{}_{}", SCRATCH_DATASET, location.to_lowercase().replace('-', "_"))
+            }
+            _ => SCRATCH_DATASET.to_string(),
+        }
+    }
+
     fn calculate_expiration(&self, partition_key: &PartitionKey) -> DateTime<Utc> {
         if let Some(hours) = self.config.ttl_hours {
             return Utc::now() + Duration::hours(hours as i64);
@@ -94,6 +117,8 @@ impl ScratchWriter {
         }
     }
 
+    /// Create the default scratch dataset, in the client's location. Datasets
+    /// for other locations are created on first write.
     pub async fn ensure_dataset(&self) -> Result<()> {
         self.client.ensure_dataset(SCRATCH_DATASET).await
     }
@@ -104,20 +129,34 @@ impl ScratchWriter {
         partition_key: PartitionKey,
         run_invariants: bool,
     ) -> Result<ScratchWriteStats> {
-        let partition_date = partition_key.to_naive_date();
+        let params = QueryParams::for_partition(&partition_key, &query_def.destination.partition, &self.run);
         let version = query_def
-            .get_version_for_date(partition_date)
+            .get_version_for_partition(&partition_key)
             .ok_or_else(|| crate::error::BqDriftError::Partition(
                 format!("No version found for partition {}", partition_key)
             ))?;
 
+        let scratch_dataset = self.scratch_dataset(query_def);
         let scratch_table = Self::scratch_table_name(query_def);
         let expiration = self.calculate_expiration(&partition_key);
 
-        self.client.drop_table(SCRATCH_DATASET, &scratch_table).await?;
+        // Scratch tables stay in the scratch project, but the job reads the
+        // query's sources, so it runs in the query's location.
+        let location = query_def.destination.location.clone()
+            .or_else(|| self.client.config().location.clone());
+        let client = self.client
+            .clone()
+            .with_location(location)
+            .with_job_config(version.job.for_partition(&query_def.name, version.version, &partition_key));
+
+        if scratch_dataset != SCRATCH_DATASET {
+            client.ensure_dataset(&scratch_dataset).await?;
+        }
+
+        self.client.drop_table(&scratch_dataset, &scratch_table).await?;
 
         self.client.create_table_with_expiration(
-            SCRATCH_DATASET,
+            &scratch_dataset,
             &scratch_table,
             &version.schema,
             &query_def.destination.partition,
@@ -126,19 +165,31 @@ impl ScratchWriter {
         ).await?;
 
         let scratch_destination = Destination {
-            dataset: SCRATCH_DATASET.to_string(),
+            project: None,
+            dataset: scratch_dataset.clone(),
             table: scratch_table.clone(),
             partition: query_def.destination.partition.clone(),
             cluster: query_def.destination.cluster.clone(),
+            write_strategy: query_def.destination.write_strategy,
+            location: query_def.destination.location.clone(),
         };
 
         let mut invariant_report = InvariantReport::default();
+        let strategy = query_def.destination.write_strategy;
+        let target = WriteTarget {
+            project: Some(&self.config.project),
+            dataset: &scratch_dataset,
+            table: &scratch_table,
+            partition_field: query_def.destination.partition.field.as_deref().unwrap_or("date"),
+        };
+        let job;
 
         if run_invariants {
             let (before_checks, after_checks) = resolve_invariants_def(&version.invariants);
 
             if !before_checks.is_empty() {
-                let checker = InvariantChecker::new(&self.client, &scratch_destination, partition_date);
+                let checker = InvariantChecker::new(&client, &scratch_destination, &partition_key)
+                    .with_run_context(&self.run);
                 let results = checker.run_checks(&before_checks).await?;
 
                 let has_error = results.iter().any(|r| {
@@ -155,18 +206,17 @@ impl ScratchWriter {
             }
 
             let sql = version.get_sql_for_date(chrono::Utc::now().date_naive());
-            let full_sql = self.build_merge_sql(query_def, &scratch_destination, sql, &partition_key);
-            self.client.execute_query(&full_sql).await?;
+            job = execute_write(&client, strategy, &target, sql, &partition_key, &params).await?;
 
             if !after_checks.is_empty() {
-                let checker = InvariantChecker::new(&self.client, &scratch_destination, partition_date);
+                let checker = InvariantChecker::new(&client, &scratch_destination, &partition_key)
+                    .with_run_context(&self.run);
                 let results = checker.run_checks(&after_checks).await?;
                 invariant_report.after = results;
             }
         } else {
             let sql = version.get_sql_for_date(chrono::Utc::now().date_naive());
-            let full_sql = self.build_merge_sql(query_def, &scratch_destination, sql, &partition_key);
-            self.client.execute_query(&full_sql).await?;
+            job = execute_write(&client, strategy, &target, sql, &partition_key, &params).await?;
         }
 
         Ok(ScratchWriteStats {
@@ -175,79 +225,16 @@ impl ScratchWriter {
             partition_key,
             scratch_table: self.scratch_table_fqn(query_def),
             expiration,
-            rows_written: None,
-            bytes_processed: None,
+            rows_written: job.rows_affected,
+            bytes_processed: job.bytes_processed,
+            bytes_billed: job.bytes_billed,
+            slot_ms: job.slot_ms,
+            job_id: job.job_id,
             invariant_report: if run_invariants { Some(invariant_report) } else { None },
         })
     }
 
-    fn build_merge_sql(
-        &self,
-        query_def: &QueryDef,
-        scratch_dest: &Destination,
-        sql: &str,
-        partition_key: &PartitionKey,
-    ) -> String {
-        let dest_table = format!(
-            "{}.{}.{}",
-            self.config.project,
-            scratch_dest.dataset,
-            scratch_dest.table
-        );
-
-        let partition_field = query_def
-            .destination
-            .partition
-            .field
-            .as_deref()
-            .unwrap_or("date");
-
-        let parameterized_sql = sql.replace("@partition_date", &format!("'{}'", partition_key.sql_value()));
-
-        let partition_condition = match partition_key {
-            PartitionKey::Hour(_) => format!(
-                "TIMESTAMP_TRUNC(target.{}, HOUR) = {}",
-                partition_field,
-                partition_key.sql_literal()
-            ),
-            PartitionKey::Day(_) => format!(
-                "target.{} = {}",
-                partition_field,
-                partition_key.sql_literal()
-            ),
-            PartitionKey::Month { .. } => format!(
-                "DATE_TRUNC(target.{}, MONTH) = {}",
-                partition_field,
-                partition_key.sql_literal()
-            ),
-            PartitionKey::Year(_) => format!(
-                "DATE_TRUNC(target.{}, YEAR) = {}",
-                partition_field,
-                partition_key.sql_literal()
-            ),
-            PartitionKey::Range(_) => format!(
-                "target.{} = {}",
-                partition_field,
-                partition_key.sql_literal()
-            ),
-        };
-
-        format!(
-            r#"
-            MERGE `{dest_table}` AS target
-            USING (
-                {parameterized_sql}
-            ) AS source
-            ON FALSE
-            WHEN NOT MATCHED BY SOURCE AND {partition_condition} THEN DELETE
-            WHEN NOT MATCHED BY TARGET THEN INSERT ROW
-            "#,
-            dest_table = dest_table,
-            parameterized_sql = parameterized_sql,
-            partition_condition = partition_condition,
-        )
-    }
-
+    /// Tables in the default scratch dataset.
     pub async fn list_tables(&self) -> Result<Vec<String>> {
         self.client.list_tables(SCRATCH_DATASET).await
     }
@@ -261,7 +248,7 @@ impl ScratchWriter {
         let scratch_table = self.scratch_table_fqn(query_def);
         let production_table = format!(
             "{}.{}.{}",
-            production_client.project_id(),
+            query_def.destination.project_or(production_client.project_id()),
             query_def.destination.dataset,
             query_def.destination.table
         );
@@ -314,7 +301,11 @@ impl ScratchWriter {
             partition_condition = partition_condition,
         );
 
-        production_client.execute_query(&merge_sql).await?;
+        production_client
+            .clone()
+            .for_destination(&query_def.destination)
+            .execute_query_with_stats(&merge_sql, &QueryParams::new())
+            .await?;
 
         Ok(PromoteStats {
             query_name: query_def.name.clone(),
@@ -334,6 +325,9 @@ pub struct ScratchWriteStats {
     pub expiration: DateTime<Utc>,
     pub rows_written: Option<i64>,
     pub bytes_processed: Option<i64>,
+    pub bytes_billed: Option<i64>,
+    pub slot_ms: Option<i64>,
+    pub job_id: Option<String>,
     pub invariant_report: Option<InvariantReport>,
 }
 
@@ -353,10 +347,12 @@ mod tests {
     #[test]
     fn test_scratch_table_name() {
         use crate::schema::{PartitionConfig, PartitionType};
+        use crate::dsl::WriteStrategy;
 
         let query_def = QueryDef {
             name: "daily_stats".to_string(),
             destination: Destination {
+                project: None,
                 dataset: "analytics".to_string(),
                 table: "daily_user_stats".to_string(),
                 partition: PartitionConfig {
@@ -368,6 +364,8 @@ mod tests {
                     granularity: None,
                 },
                 cluster: None,
+                write_strategy: WriteStrategy::Merge,
+                location: None,
             },
             description: None,
             owner: None,
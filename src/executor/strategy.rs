@@ -0,0 +1,222 @@
+use gcp_bigquery_client::model::table_reference::TableReference;
+use crate::dsl::WriteStrategy;
+use crate::error::Result;
+use crate::schema::PartitionKey;
+use super::client::{BqClient, JobStats};
+use super::params::QueryParams;
+
+/// The table a partition write lands in. Without a project, the table is
+/// resolved in the project the job runs in.
+pub(crate) struct WriteTarget<'a> {
+    pub project: Option<&'a str>,
+    pub dataset: &'a str,
+    pub table: &'a str,
+    pub partition_field: &'a str,
+}
+
+impl WriteTarget<'_> {
+    pub fn table_path(&self) -> String {
+        match self.project {
+            Some(project) => format!("{}.{}.{}", project, self.dataset, self.table),
+            None => format!("{}.{}", self.dataset, self.table),
+        }
+    }
+}
+
+/// Write `sql`'s result into one partition of `target` using `strategy`.
+/// `rows_affected` of the returned stats is the number of rows written;
+/// statistics of every job involved are summed.
+pub(crate) async fn execute_write(
+    client: &BqClient,
+    strategy: WriteStrategy,
+    target: &WriteTarget<'_>,
+    sql: &str,
+    partition_key: &PartitionKey,
+    params: &QueryParams,
+) -> Result<JobStats> {
+    match strategy {
+        WriteStrategy::Merge => {
+            let merge_sql = build_merge_sql(target, sql, partition_key);
+            let mut job = client.execute_query_with_stats(&merge_sql, params).await?;
+            // The affected rows of a merge include the rows it deleted.
+            job.rows_affected = job.rows_inserted;
+            Ok(job)
+        }
+        WriteStrategy::TruncatePartition => {
+            let script = build_replace_script(target, sql, partition_key);
+            client.execute_script_with_stats(&script, params).await
+        }
+        WriteStrategy::InsertOverwriteViaJob => {
+            let table = format!("{}{}", target.table, partition_key.decorator());
+            let destination = TableReference::new(target.project.unwrap_or(client.project_id()), target.dataset, &table);
+            client.query_to_table(sql, params, &destination, "WRITE_TRUNCATE").await
+        }
+        WriteStrategy::Append => {
+            client.execute_query_with_stats(&build_insert_sql(target, sql), params).await
+        }
+    }
+}
+
+/// The statement a dry run of `execute_write` should price: the one that
+/// reads the source data.
+pub(crate) fn estimate_sql(
+    strategy: WriteStrategy,
+    target: &WriteTarget<'_>,
+    sql: &str,
+    partition_key: &PartitionKey,
+) -> String {
+    match strategy {
+        WriteStrategy::Merge => build_merge_sql(target, sql, partition_key),
+        WriteStrategy::TruncatePartition => build_insert_sql(target, sql),
+        WriteStrategy::InsertOverwriteViaJob => sql.to_string(),
+        WriteStrategy::Append => build_insert_sql(target, sql),
+    }
+}
+
+pub(crate) fn build_merge_sql(target: &WriteTarget<'_>, sql: &str, partition_key: &PartitionKey) -> String {
+    format!(
+        r#"
+        MERGE `{dest_table}` AS target
+        USING (
+            {sql}
+        ) AS source
+        ON FALSE
+        WHEN NOT MATCHED BY SOURCE AND {partition_condition} THEN DELETE
+        WHEN NOT MATCHED BY TARGET THEN INSERT ROW
+        "#,
+        dest_table = target.table_path(),
+        sql = sql,
+        partition_condition = partition_condition(target, partition_key),
+    )
+}
+
+/// A script replacing the partition's rows in one transaction, so a failed
+/// INSERT never leaves the partition empty. It ends by selecting the number
+/// of rows inserted.
+fn build_replace_script(target: &WriteTarget<'_>, sql: &str, partition_key: &PartitionKey) -> String {
+    format!(
+        r#"
+        DECLARE rows_written INT64;
+
+        BEGIN
+          BEGIN TRANSACTION;
+
+          DELETE FROM `{dest_table}` AS target
+          WHERE {partition_condition};
+
+          INSERT INTO `{dest_table}`
+          {sql}
+          ;
+
+          SET rows_written = @@row_count;
+          COMMIT TRANSACTION;
+        EXCEPTION WHEN ERROR THEN
+          ROLLBACK TRANSACTION;
+          RAISE USING MESSAGE = @@error.message;
+        END;
+
+        SELECT rows_written;
+        "#,
+        dest_table = target.table_path(),
+        partition_condition = partition_condition(target, partition_key),
+        sql = sql.trim_end().trim_end_matches(';'),
+    )
+}
+
+fn build_insert_sql(target: &WriteTarget<'_>, sql: &str) -> String {
+    format!(
+        r#"
+        INSERT INTO `{dest_table}`
+        {sql}
+        "#,
+        dest_table = target.table_path(),
+        sql = sql,
+    )
+}
+
+/// Matches the rows of `target` (aliased `target`) in the partition.
+fn partition_condition(target: &WriteTarget<'_>, partition_key: &PartitionKey) -> String {
+    let partition_field = target.partition_field;
+
+    match partition_key {
+        PartitionKey::Hour(_) => format!(
+            "TIMESTAMP_TRUNC(target.{}, HOUR) = {}",
+            partition_field,
+            partition_key.sql_literal()
+        ),
+        PartitionKey::Day(_) => format!(
+            "target.{} = {}",
+            partition_field,
+            partition_key.sql_literal()
+        ),
+        PartitionKey::Month { .. } => format!(
+            "DATE_TRUNC(target.{}, MONTH) = {}",
+            partition_field,
+            partition_key.sql_literal()
+        ),
+        PartitionKey::Year(_) => format!(
+            "DATE_TRUNC(target.{}, YEAR) = {}",
+            partition_field,
+            partition_key.sql_literal()
+        ),
+        PartitionKey::Range(_) => format!(
+            "target.{} = {}",
+            partition_field,
+            partition_key.sql_literal()
+        ),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::NaiveDate;
+
+    fn target() -> WriteTarget<'static> {
+        WriteTarget {
+            project: Some("proj"),
+            dataset: "analytics",
+            table: "daily_stats",
+            partition_field: "date",
+        }
+    }
+
+    fn day() -> PartitionKey {
+        PartitionKey::Day(NaiveDate::from_ymd_opt(2024, 6, 15).unwrap())
+    }
+
+    #[test]
+    fn test_merge_sql_targets_partition() {
+        let sql = build_merge_sql(&target(), "SELECT 1", &day());
+
+        assert!(sql.contains("MERGE `proj.analytics.daily_stats` AS target"));
+        assert!(sql.contains("WHEN NOT MATCHED BY SOURCE AND target.date = DATE '2024-06-15' THEN DELETE"));
+    }
+
+    #[test]
+    fn test_replace_script_is_transactional() {
+        let script = build_replace_script(&target(), "SELECT 1", &day());
+
+        let begin = script.find("BEGIN TRANSACTION;").unwrap();
+        let delete = script.find("DELETE FROM `proj.analytics.daily_stats` AS target").unwrap();
+        let insert = script.find("INSERT INTO `proj.analytics.daily_stats`").unwrap();
+        let commit = script.find("COMMIT TRANSACTION;").unwrap();
+
+        assert!(begin < delete && delete < insert && insert < commit);
+        assert!(script.contains("WHERE target.date = DATE '2024-06-15';"));
+        assert!(script.contains("EXCEPTION WHEN ERROR THEN\n          ROLLBACK TRANSACTION;"));
+        assert!(script.trim_end().ends_with("SELECT rows_written;"));
+    }
+
+    #[test]
+    fn test_estimate_sql_per_strategy() {
+        let truncate = estimate_sql(WriteStrategy::TruncatePartition, &target(), "SELECT 1", &day());
+        assert!(truncate.contains("INSERT INTO `proj.analytics.daily_stats`"));
+
+        let append = estimate_sql(WriteStrategy::Append, &target(), "SELECT 1", &day());
+        assert!(append.contains("INSERT INTO `proj.analytics.daily_stats`"));
+
+        let overwrite = estimate_sql(WriteStrategy::InsertOverwriteViaJob, &target(), "SELECT 1", &day());
+        assert_eq!(overwrite, "SELECT 1");
+    }
+}
@@ -0,0 +1,110 @@
+use async_trait::async_trait;
+use serde_json::Value;
+use crate::error::{BqDriftError, Result};
+use crate::executor::{BqClient, Executor, QueryParams, QueryResult};
+
+/// Where invariant checks run their aggregate queries.
+///
+/// Every check reduces to a single-row query returning one or two numbers,
+/// so a backend only needs to run SQL with `@partition_date` and the other
+/// built-in parameters bound and read back the first row.
+#[async_trait]
+pub trait CheckBackend: Send + Sync {
+    /// First column of the first row as an integer; 0 when there are no rows.
+    async fn query_row_count(&self, sql: &str, params: &QueryParams) -> Result<i64>;
+
+    /// First column of the first row; `None` when it is NULL or missing.
+    async fn query_single_float(&self, sql: &str, params: &QueryParams) -> Result<Option<f64>>;
+
+    /// First two columns of the first row.
+    async fn query_two_floats(&self, sql: &str, params: &QueryParams) -> Result<(Option<f64>, Option<f64>)>;
+}
+
+#[async_trait]
+impl CheckBackend for BqClient {
+    async fn query_row_count(&self, sql: &str, params: &QueryParams) -> Result<i64> {
+        BqClient::query_row_count(self, sql, params).await
+    }
+
+    async fn query_single_float(&self, sql: &str, params: &QueryParams) -> Result<Option<f64>> {
+        BqClient::query_single_float(self, sql, params).await
+    }
+
+    async fn query_two_floats(&self, sql: &str, params: &QueryParams) -> Result<(Option<f64>, Option<f64>)> {
+        BqClient::query_two_floats(self, sql, params).await
+    }
+}
+
+/// The executor takes plain SQL, so parameters are bound as typed literals.
+#[async_trait]
+impl CheckBackend for Executor {
+    async fn query_row_count(&self, sql: &str, params: &QueryParams) -> Result<i64> {
+        let result = run_executor_query(self, sql, params).await?;
+
+        match first_row_cell(&result, 0) {
+            Some(value) => value_as_i64(value).ok_or_else(|| {
+                BqDriftError::Schema(format!("Could not parse count value: {}", value))
+            }),
+            None => Ok(0),
+        }
+    }
+
+    async fn query_single_float(&self, sql: &str, params: &QueryParams) -> Result<Option<f64>> {
+        let result = run_executor_query(self, sql, params).await?;
+        Ok(first_row_cell(&result, 0).and_then(value_as_f64))
+    }
+
+    async fn query_two_floats(&self, sql: &str, params: &QueryParams) -> Result<(Option<f64>, Option<f64>)> {
+        let result = run_executor_query(self, sql, params).await?;
+        Ok((
+            first_row_cell(&result, 0).and_then(value_as_f64),
+            first_row_cell(&result, 1).and_then(value_as_f64),
+        ))
+    }
+}
+
+async fn run_executor_query(executor: &Executor, sql: &str, params: &QueryParams) -> Result<QueryResult> {
+    executor
+        .query(&params.inline(sql))
+        .await
+        .map_err(|e| BqDriftError::Executor(e.to_string()))
+}
+
+fn first_row_cell(result: &QueryResult, column: usize) -> Option<&Value> {
+    result.rows.first()?.get(column)
+}
+
+/// Executors may return numbers as JSON numbers or, like the BigQuery REST
+/// API, as strings.
+fn value_as_i64(value: &Value) -> Option<i64> {
+    match value {
+        Value::Number(n) => n.as_i64().or_else(|| n.as_f64().map(|f| f as i64)),
+        Value::String(s) => s.parse().ok(),
+        _ => None,
+    }
+}
+
+fn value_as_f64(value: &Value) -> Option<f64> {
+    match value {
+        Value::Number(n) => n.as_f64(),
+        Value::String(s) => s.parse().ok(),
+        _ => None,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    #[test]
+    fn test_numeric_values_from_numbers_and_strings() {
+        assert_eq!(value_as_i64(&json!(42)), Some(42));
+        assert_eq!(value_as_i64(&json!("42")), Some(42));
+        assert_eq!(value_as_i64(&json!(null)), None);
+
+        assert_eq!(value_as_f64(&json!(12.5)), Some(12.5));
+        assert_eq!(value_as_f64(&json!("12.5")), Some(12.5));
+        assert_eq!(value_as_f64(&json!(null)), None);
+    }
+}
@@ -1,9 +1,10 @@
-use chrono::NaiveDate;
 use crate::error::Result;
 use crate::dsl::Destination;
-use crate::executor::BqClient;
+use crate::executor::{QueryParams, RunContext};
+use crate::schema::PartitionKey;
 use super::types::{Severity, InvariantsDef, InvariantDef, InvariantCheck};
 use super::result::CheckResult;
+use super::backend::CheckBackend;
 
 pub struct ResolvedInvariant {
     pub name: String,
@@ -38,24 +39,34 @@ pub enum ResolvedCheck {
 }
 
 pub struct InvariantChecker<'a> {
-    client: &'a BqClient,
+    backend: &'a dyn CheckBackend,
     destination: &'a Destination,
-    partition_date: NaiveDate,
+    partition_key: PartitionKey,
+    params: QueryParams,
 }
 
 impl<'a> InvariantChecker<'a> {
+    /// Run checks through `backend`: a `BqClient`, or a bq-runner `Executor`
+    /// such as the one from `create_mock_executor`.
     pub fn new(
-        client: &'a BqClient,
+        backend: &'a dyn CheckBackend,
         destination: &'a Destination,
-        partition_date: NaiveDate,
+        partition_key: &PartitionKey,
     ) -> Self {
         Self {
-            client,
+            backend,
             destination,
-            partition_date,
+            partition_key: partition_key.clone(),
+            params: QueryParams::for_partition(partition_key, &destination.partition, &RunContext::new()),
         }
     }
 
+    /// Share `@run_id` and `@run_time` with the write being checked.
+    pub fn with_run_context(mut self, run: &RunContext) -> Self {
+        self.params = QueryParams::for_partition(&self.partition_key, &self.destination.partition, run);
+        self
+    }
+
     pub async fn run_checks(&self, invariants: &[ResolvedInvariant]) -> Result<Vec<CheckResult>> {
         let mut results = Vec::new();
 
@@ -85,22 +96,22 @@ impl<'a> InvariantChecker<'a> {
     }
 
     fn destination_table(&self) -> String {
-        format!("`{}.{}`", self.destination.dataset, self.destination.table)
+        format!("`{}`", self.destination.table_ref())
     }
 
     fn default_source_sql(&self) -> String {
         let partition_field = self.destination.partition.field.as_deref().unwrap_or("date");
         format!(
-            "SELECT * FROM {} WHERE {} = '{}'",
+            "SELECT * FROM {} WHERE {} = @partition_date",
             self.destination_table(),
             partition_field,
-            self.partition_date
         )
     }
 
+    /// Expands `{destination}`. `@partition_date` is left for BigQuery to
+    /// bind as a typed query parameter.
     fn resolve_placeholders(&self, sql: &str) -> String {
         sql.replace("{destination}", &self.destination_table())
-           .replace("@partition_date", &format!("'{}'", self.partition_date))
     }
 
     async fn check_row_count(
@@ -116,7 +127,7 @@ impl<'a> InvariantChecker<'a> {
             .unwrap_or_else(|| self.default_source_sql());
 
         let count_sql = format!("SELECT COUNT(*) as cnt FROM ({}) _source", source);
-        let count = self.client.query_row_count(&count_sql).await?;
+        let count = self.backend.query_row_count(&count_sql, &self.params).await?;
 
         let mut violations = Vec::new();
         if let Some(min_val) = min {
@@ -155,7 +166,7 @@ impl<'a> InvariantChecker<'a> {
             column, source
         );
 
-        let null_pct = self.client.query_single_float(&check_sql).await?.unwrap_or(0.0);
+        let null_pct = self.backend.query_single_float(&check_sql, &self.params).await?.unwrap_or(0.0);
 
         if null_pct <= max_percentage {
             Ok(CheckResult::passed(name, severity, format!("Null percentage: {:.2}%", null_pct)))
@@ -186,7 +197,7 @@ impl<'a> InvariantChecker<'a> {
             column, column, source
         );
 
-        let (min_val, max_val) = self.client.query_two_floats(&check_sql).await?;
+        let (min_val, max_val) = self.backend.query_two_floats(&check_sql, &self.params).await?;
 
         let mut violations = Vec::new();
         if let (Some(threshold), Some(actual)) = (min, min_val) {
@@ -226,7 +237,7 @@ impl<'a> InvariantChecker<'a> {
             column, source
         );
 
-        let count = self.client.query_row_count(&check_sql).await?;
+        let count = self.backend.query_row_count(&check_sql, &self.params).await?;
 
         let mut violations = Vec::new();
         if let Some(min_val) = min {
@@ -1,6 +1,7 @@
 mod types;
 mod checker;
 mod result;
+mod backend;
 
 pub use types::{
     InvariantsRef, InvariantsDef, ExtendedInvariants, InvariantsRemove,
@@ -8,3 +9,4 @@ pub use types::{
 };
 pub use checker::{InvariantChecker, ResolvedInvariant, ResolvedCheck, resolve_invariants_def};
 pub use result::{CheckResult, CheckStatus, InvariantReport};
+pub use backend::CheckBackend;
@@ -9,16 +9,16 @@ pub mod diff;
 pub mod repl;
 
 pub use error::{BqDriftError, Result};
-pub use schema::{BqType, Field, FieldMode, Schema, PartitionConfig, PartitionType, PartitionKey, ClusterConfig};
-pub use dsl::{QueryDef, VersionDef, Revision, ResolvedRevision, QueryLoader, QueryValidator, ValidationResult, SqlDependencies};
-pub use executor::{PartitionWriter, Runner, BqClient};
+pub use schema::{BqType, Field, FieldMode, Schema, PartitionConfig, PartitionType, PartitionKey, ClusterConfig, SchemaChange, SchemaEvolution, TableDefinition};
+pub use dsl::{QueryDef, VersionDef, Destination, WriteStrategy, Revision, ResolvedRevision, QueryLoader, QueryValidator, ValidationResult, SqlDependencies, QueryGraph, DependencyGraph, GraphFormat, JobConfig, JobPriority};
+pub use executor::{PartitionWriter, Runner, BqClient, BackfillOptions, BackfillStep, CancelSignal, ClientConfig, Credentials, RetryPolicy, CostEstimate, PartitionEstimate, QueryParams, ParamValue, RunContext};
 pub use executor::{Executor, ExecutorMode, ExecutorRunner, QueryResult, ColumnDef, ColumnInfo, create_mock_executor, create_bigquery_executor};
-pub use migration::MigrationTracker;
-pub use drift::{Checksums, ExecutionArtifact, DriftDetector, DriftReport, DriftState, PartitionState, PartitionDrift, ExecutionStatus, compress_to_base64, decompress_from_base64, ImmutabilityChecker, ImmutabilityReport, ImmutabilityViolation, SourceAuditor, SourceAuditReport, SourceAuditEntry, SourceStatus, AuditTableRow};
+pub use migration::{MigrationTracker, StateStore, LocalStateStore};
+pub use drift::{Checksums, ExecutionArtifact, DriftDetector, DriftReport, DriftState, PartitionState, PartitionDrift, ExecutionStatus, compress_to_base64, decompress_from_base64, ImmutabilityChecker, ImmutabilityReport, ImmutabilityViolation, SourceAuditor, SourceAuditReport, SourceAuditEntry, SourceStatus, AuditTableRow, SchemaChecker, SchemaCheckReport, SchemaCheckEntry, SchemaMismatch, SchemaCheckTableRow};
 pub use diff::{encode_sql, decode_sql, format_sql_diff, has_changes};
 pub use invariant::{
     InvariantsRef, InvariantsDef, InvariantDef, InvariantCheck, Severity,
-    InvariantChecker, CheckResult, CheckStatus, InvariantReport,
+    InvariantChecker, CheckBackend, CheckResult, CheckStatus, InvariantReport,
     resolve_invariants_def,
 };
 pub use repl::{ReplSession, ReplCommand, ReplResult, InteractiveRepl, AsyncJsonRpcServer, ServerConfig, SessionManager, SessionInfo, ServerConfigInfo};
@@ -0,0 +1,290 @@
+use async_trait::async_trait;
+use chrono::{DateTime, NaiveDate, Utc};
+use std::collections::HashMap;
+use std::fs::{self, OpenOptions};
+use std::io::{BufRead, BufReader, Write};
+use std::path::{Path, PathBuf};
+use tracing::warn;
+use crate::error::{BqDriftError, Result};
+use crate::drift::{PartitionState, ExecutionStatus};
+use crate::schema::PartitionKey;
+use super::store::StateStore;
+
+/// Append-only JSON Lines file of partition states, one state per line.
+/// Needs no BigQuery credentials, so drift and immutability checks work offline.
+#[derive(Debug, Clone)]
+pub struct LocalStateStore {
+    path: PathBuf,
+}
+
+impl LocalStateStore {
+    pub const DEFAULT_PATH: &'static str = ".bqdrift/state.jsonl";
+
+    pub fn new(path: impl Into<PathBuf>) -> Self {
+        Self { path: path.into() }
+    }
+
+    pub fn path(&self) -> &Path {
+        &self.path
+    }
+
+    /// Every recorded state in write order. A missing file holds no states.
+    /// An unreadable final line is skipped with a warning: it is a record an
+    /// interrupted write left incomplete.
+    pub fn read_states(&self) -> Result<Vec<PartitionState>> {
+        if !self.path.exists() {
+            return Ok(Vec::new());
+        }
+
+        let file = fs::File::open(&self.path)?;
+        let lines = BufReader::new(file).lines().collect::<std::io::Result<Vec<_>>>()?;
+        let last = lines.iter().rposition(|line| !line.trim().is_empty());
+        let mut states = Vec::new();
+
+        for (idx, line) in lines.iter().enumerate() {
+            if line.trim().is_empty() {
+                continue;
+            }
+            match serde_json::from_str(line) {
+                Ok(state) => states.push(state),
+                Err(e) if Some(idx) == last => {
+                    warn!("{}:{}: skipping incomplete state record: {}", self.path.display(), idx + 1, e);
+                }
+                Err(e) => {
+                    return Err(BqDriftError::Migration(
+                        format!("{}:{}: invalid state record: {}", self.path.display(), idx + 1, e)
+                    ));
+                }
+            }
+        }
+
+        Ok(states)
+    }
+
+    fn ensure_parent_dir(&self) -> Result<()> {
+        match self.path.parent() {
+            Some(parent) if !parent.as_os_str().is_empty() => Ok(fs::create_dir_all(parent)?),
+            _ => Ok(()),
+        }
+    }
+}
+
+impl Default for LocalStateStore {
+    fn default() -> Self {
+        Self::new(Self::DEFAULT_PATH)
+    }
+}
+
+#[async_trait]
+impl StateStore for LocalStateStore {
+    async fn ensure(&self) -> Result<()> {
+        self.ensure_parent_dir()?;
+        OpenOptions::new().create(true).append(true).open(&self.path)?;
+        Ok(())
+    }
+
+    async fn record_state(&self, state: &PartitionState) -> Result<()> {
+        self.ensure_parent_dir()?;
+        let line = serde_json::to_string(state)?;
+
+        // One write per record, so concurrent appends never interleave within a line.
+        let mut file = OpenOptions::new().create(true).append(true).open(&self.path)?;
+        file.write_all(format!("{}\n", line).as_bytes())?;
+        Ok(())
+    }
+
+    async fn latest_executions(
+        &self,
+        query_names: &[String],
+        partition_key: &PartitionKey,
+    ) -> Result<HashMap<String, DateTime<Utc>>> {
+        let mut latest: HashMap<String, DateTime<Utc>> = HashMap::new();
+
+        for state in self.read_states()? {
+            if !state.partition_key.overlaps(partition_key)
+                || state.status != ExecutionStatus::Success
+                || !query_names.contains(&state.query_name)
+            {
+                continue;
+            }
+
+            let entry = latest.entry(state.query_name).or_insert(state.executed_at);
+            if state.executed_at > *entry {
+                *entry = state.executed_at;
+            }
+        }
+
+        Ok(latest)
+    }
+
+    async fn get_latest_states(&self) -> Result<Vec<PartitionState>> {
+        let mut latest: HashMap<(String, String), PartitionState> = HashMap::new();
+
+        for state in self.read_states()? {
+            let key = (state.query_name.clone(), state.partition_key.to_string());
+            match latest.get(&key) {
+                Some(existing) if existing.executed_at > state.executed_at => {}
+                _ => {
+                    latest.insert(key, state);
+                }
+            }
+        }
+
+        let mut states: Vec<PartitionState> = latest.into_values().collect();
+        states.sort_by(|a, b| {
+            a.query_name.cmp(&b.query_name).then_with(|| a.partition_key.cmp(&b.partition_key))
+        });
+        Ok(states)
+    }
+
+    async fn get_latest_states_for_date_range(
+        &self,
+        from: NaiveDate,
+        to: NaiveDate,
+    ) -> Result<Vec<PartitionState>> {
+        let mut states = self.get_latest_states().await?;
+        states.retain(|s| s.partition_key.overlaps_dates(from, to));
+        Ok(states)
+    }
+
+    fn location(&self) -> String {
+        self.path.display().to_string()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::TempDir;
+
+    fn state(query_name: &str, key: PartitionKey, executed_at: DateTime<Utc>, status: ExecutionStatus) -> PartitionState {
+        PartitionState {
+            query_name: query_name.to_string(),
+            partition_date: key.to_naive_date(),
+            partition_key: key,
+            version: 1,
+            sql_revision: None,
+            effective_from: NaiveDate::from_ymd_opt(2024, 1, 1).unwrap(),
+            sql_checksum: "sql".to_string(),
+            schema_checksum: "schema".to_string(),
+            yaml_checksum: "yaml".to_string(),
+            executed_sql_b64: None,
+            executed_yaml_b64: None,
+            upstream_states: HashMap::new(),
+            executed_at,
+            execution_time_ms: None,
+            rows_written: None,
+            bytes_processed: None,
+            bytes_billed: None,
+            slot_ms: None,
+            job_id: None,
+            write_strategy: None,
+            status,
+        }
+    }
+
+    #[tokio::test]
+    async fn test_missing_file_has_no_states() {
+        let dir = TempDir::new().unwrap();
+        let store = LocalStateStore::new(dir.path().join("state.jsonl"));
+
+        assert!(store.get_latest_states().await.unwrap().is_empty());
+    }
+
+    #[tokio::test]
+    async fn test_latest_state_per_partition() {
+        let dir = TempDir::new().unwrap();
+        let store = LocalStateStore::new(dir.path().join(".bqdrift/state.jsonl"));
+        store.ensure().await.unwrap();
+
+        let hour = PartitionKey::parse("2024-06-15T10", &crate::schema::PartitionType::Hour).unwrap();
+        let earlier = Utc::now() - chrono::Duration::hours(1);
+        let later = Utc::now();
+
+        store.record_state(&state("hourly", hour.clone(), later, ExecutionStatus::Failed)).await.unwrap();
+        store.record_state(&state("hourly", hour.clone(), earlier, ExecutionStatus::Success)).await.unwrap();
+
+        let states = store.get_latest_states().await.unwrap();
+        assert_eq!(states.len(), 1);
+        assert_eq!(states[0].partition_key, hour);
+        assert_eq!(states[0].status, ExecutionStatus::Failed);
+        assert_eq!(store.read_states().unwrap().len(), 2);
+    }
+
+    #[tokio::test]
+    async fn test_incomplete_final_record_is_skipped() {
+        let dir = TempDir::new().unwrap();
+        let store = LocalStateStore::new(dir.path().join("state.jsonl"));
+
+        let date = NaiveDate::from_ymd_opt(2024, 6, 15).unwrap();
+        store.record_state(&state("daily", PartitionKey::Day(date), Utc::now(), ExecutionStatus::Success)).await.unwrap();
+        store.record_state(&state("daily", PartitionKey::Day(date), Utc::now(), ExecutionStatus::Success)).await.unwrap();
+
+        let contents = fs::read_to_string(store.path()).unwrap();
+        let truncated = &contents[..contents.len() - 20];
+        fs::write(store.path(), truncated).unwrap();
+        assert_eq!(store.read_states().unwrap().len(), 1);
+
+        let corrupted = format!("{{\"query_name\":\n{}", contents);
+        fs::write(store.path(), corrupted).unwrap();
+        assert!(store.read_states().is_err());
+    }
+
+    #[tokio::test]
+    async fn test_latest_executions_only_counts_successes() {
+        let dir = TempDir::new().unwrap();
+        let store = LocalStateStore::new(dir.path().join("state.jsonl"));
+
+        let date = NaiveDate::from_ymd_opt(2024, 6, 15).unwrap();
+        let ran_at = Utc::now() - chrono::Duration::hours(1);
+
+        store.record_state(&state("upstream", PartitionKey::Day(date), ran_at, ExecutionStatus::Success)).await.unwrap();
+        store.record_state(&state("upstream", PartitionKey::Day(date), Utc::now(), ExecutionStatus::Failed)).await.unwrap();
+        store.record_state(&state("other", PartitionKey::Day(date), Utc::now(), ExecutionStatus::Success)).await.unwrap();
+
+        let latest = store.latest_executions(&["upstream".to_string()], &PartitionKey::Day(date)).await.unwrap();
+        assert_eq!(latest.len(), 1);
+        assert_eq!(latest["upstream"], ran_at);
+    }
+
+    #[tokio::test]
+    async fn test_latest_executions_match_overlapping_partitions() {
+        let dir = TempDir::new().unwrap();
+        let store = LocalStateStore::new(dir.path().join("state.jsonl"));
+
+        let hour = |s: &str| PartitionKey::parse(s, &crate::schema::PartitionType::Hour).unwrap();
+        let ran_at = Utc::now() - chrono::Duration::hours(1);
+
+        store.record_state(&state("upstream", hour("2024-06-15T10"), ran_at, ExecutionStatus::Success)).await.unwrap();
+        store.record_state(&state("upstream", hour("2024-06-15T11"), Utc::now(), ExecutionStatus::Success)).await.unwrap();
+        store.record_state(&state("ranged", PartitionKey::Range(0), Utc::now(), ExecutionStatus::Success)).await.unwrap();
+
+        let names = ["upstream".to_string(), "ranged".to_string()];
+        let latest = store.latest_executions(&names, &hour("2024-06-15T10")).await.unwrap();
+        assert_eq!(latest["upstream"], ran_at);
+
+        let latest = store.latest_executions(&names, &PartitionKey::Range(10)).await.unwrap();
+        assert!(!latest.contains_key("ranged"));
+    }
+
+    #[tokio::test]
+    async fn test_latest_states_for_date_range() {
+        let dir = TempDir::new().unwrap();
+        let store = LocalStateStore::new(dir.path().join("state.jsonl"));
+
+        let date = |s: &str| NaiveDate::parse_from_str(s, "%Y-%m-%d").unwrap();
+        for key in [
+            PartitionKey::Day(date("2024-06-09")),
+            PartitionKey::Day(date("2024-06-10")),
+            PartitionKey::Month { year: 2024, month: 6 },
+            PartitionKey::Range(10),
+        ] {
+            store.record_state(&state("q", key, Utc::now(), ExecutionStatus::Success)).await.unwrap();
+        }
+
+        let states = store.get_latest_states_for_date_range(date("2024-06-10"), date("2024-06-15")).await.unwrap();
+        let mut keys: Vec<_> = states.iter().map(|s| s.partition_key.to_string()).collect();
+        keys.sort();
+        assert_eq!(keys, ["10", "2024-06", "2024-06-10"]);
+    }
+}
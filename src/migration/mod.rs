@@ -1,3 +1,7 @@
 mod tracker;
+mod store;
+mod local_store;
 
-pub use tracker::MigrationTracker;
+pub use tracker::{MigrationTracker, QueryRun, RunStatus};
+pub use store::StateStore;
+pub use local_store::LocalStateStore;
@@ -0,0 +1,77 @@
+use async_trait::async_trait;
+use chrono::{DateTime, NaiveDate, Utc};
+use std::collections::HashMap;
+use crate::error::Result;
+use crate::drift::PartitionState;
+use crate::schema::PartitionKey;
+use super::tracker::MigrationTracker;
+
+/// Backend that partition state is recorded to and read back from.
+///
+/// `run`, `backfill` and `sync` record a state per partition write; `sync`
+/// reads back the latest states of the partitions it covers for drift and
+/// immutability checks, and `audit` reads back every latest state.
+#[async_trait]
+pub trait StateStore: Send + Sync {
+    /// Create whatever storage the backend needs. Safe to call repeatedly.
+    async fn ensure(&self) -> Result<()>;
+
+    async fn record_state(&self, state: &PartitionState) -> Result<()>;
+
+    /// Latest successful execution time of each named query, across its
+    /// partitions that overlap `partition_key` (see `PartitionKey::overlaps`).
+    async fn latest_executions(
+        &self,
+        query_names: &[String],
+        partition_key: &PartitionKey,
+    ) -> Result<HashMap<String, DateTime<Utc>>>;
+
+    /// Most recent state of every tracked partition, across all queries.
+    async fn get_latest_states(&self) -> Result<Vec<PartitionState>>;
+
+    /// Most recent state of every tracked partition that overlaps the days
+    /// `from..=to` (see `PartitionKey::overlaps_dates`).
+    async fn get_latest_states_for_date_range(
+        &self,
+        from: NaiveDate,
+        to: NaiveDate,
+    ) -> Result<Vec<PartitionState>>;
+
+    /// Human-readable location of the store, for status messages.
+    fn location(&self) -> String;
+}
+
+#[async_trait]
+impl StateStore for MigrationTracker {
+    async fn ensure(&self) -> Result<()> {
+        self.ensure_tracking_table().await
+    }
+
+    async fn record_state(&self, state: &PartitionState) -> Result<()> {
+        MigrationTracker::record_state(self, state).await
+    }
+
+    async fn latest_executions(
+        &self,
+        query_names: &[String],
+        partition_key: &PartitionKey,
+    ) -> Result<HashMap<String, DateTime<Utc>>> {
+        MigrationTracker::latest_executions(self, query_names, partition_key).await
+    }
+
+    async fn get_latest_states(&self) -> Result<Vec<PartitionState>> {
+        MigrationTracker::get_latest_states(self).await
+    }
+
+    async fn get_latest_states_for_date_range(
+        &self,
+        from: NaiveDate,
+        to: NaiveDate,
+    ) -> Result<Vec<PartitionState>> {
+        MigrationTracker::get_latest_states_for_date_range(self, from, to).await
+    }
+
+    fn location(&self) -> String {
+        format!("{}._bqdrift_state", self.dataset())
+    }
+}
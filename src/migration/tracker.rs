@@ -1,8 +1,12 @@
 use chrono::{DateTime, NaiveDate, Utc};
-use crate::error::Result;
+use std::collections::HashMap;
+use crate::error::{BqDriftError, Result};
 use crate::executor::BqClient;
+use crate::drift::{PartitionState, ExecutionStatus};
+use crate::schema::{PartitionKey, PartitionType};
 
 const TRACKING_TABLE: &str = "_bqdrift_query_runs";
+const STATE_TABLE: &str = "_bqdrift_state";
 
 #[derive(Debug, Clone)]
 pub struct QueryRun {
@@ -23,6 +27,7 @@ pub enum RunStatus {
     Failed,
 }
 
+#[derive(Clone)]
 pub struct MigrationTracker {
     client: BqClient,
     dataset: String,
@@ -36,7 +41,11 @@ impl MigrationTracker {
         }
     }
 
+    /// Create the tracking dataset, in the client's location, and the
+    /// tracking tables unless they exist.
     pub async fn ensure_tracking_table(&self) -> Result<()> {
+        self.client.ensure_dataset(&self.dataset).await?;
+
         let table_name = format!("{}.{}", self.dataset, TRACKING_TABLE);
 
         let create_sql = format!(
@@ -57,7 +66,200 @@ impl MigrationTracker {
             table_name = table_name
         );
 
-        self.client.execute_query(&create_sql).await
+        self.client.execute_query(&create_sql).await?;
+
+        let state_table = format!("{}.{}", self.dataset, STATE_TABLE);
+
+        let create_state_sql = format!(
+            r#"
+            CREATE TABLE IF NOT EXISTS `{state_table}` (
+                query_name STRING NOT NULL,
+                partition_date DATE NOT NULL,
+                version INT64 NOT NULL,
+                sql_revision INT64,
+                effective_from DATE NOT NULL,
+                sql_checksum STRING NOT NULL,
+                schema_checksum STRING NOT NULL,
+                yaml_checksum STRING NOT NULL,
+                executed_sql_b64 STRING,
+                executed_yaml_b64 STRING,
+                upstream_states JSON,
+                executed_at TIMESTAMP NOT NULL,
+                execution_time_ms INT64,
+                rows_written INT64,
+                bytes_processed INT64,
+                status STRING NOT NULL,
+                partition_key STRING,
+                partition_type STRING,
+                bytes_billed INT64,
+                slot_ms INT64,
+                job_id STRING,
+                write_strategy STRING
+            )
+            PARTITION BY partition_date
+            CLUSTER BY query_name
+            "#,
+            state_table = state_table
+        );
+
+        self.client.execute_query(&create_state_sql).await?;
+
+        // Older state tables lack the partition key, job statistics and write strategy columns.
+        let alter_state_sql = format!(
+            r#"
+            ALTER TABLE `{state_table}`
+                ADD COLUMN IF NOT EXISTS partition_key STRING,
+                ADD COLUMN IF NOT EXISTS partition_type STRING,
+                ADD COLUMN IF NOT EXISTS bytes_billed INT64,
+                ADD COLUMN IF NOT EXISTS slot_ms INT64,
+                ADD COLUMN IF NOT EXISTS job_id STRING,
+                ADD COLUMN IF NOT EXISTS write_strategy STRING
+            "#,
+            state_table = state_table
+        );
+
+        self.client.execute_query(&alter_state_sql).await
+    }
+
+    pub fn dataset(&self) -> &str {
+        &self.dataset
+    }
+
+    pub async fn record_state(&self, state: &PartitionState) -> Result<()> {
+        let table_name = format!("{}.{}", self.dataset, STATE_TABLE);
+        let status_str = match state.status {
+            ExecutionStatus::Success => "SUCCESS",
+            ExecutionStatus::Failed => "FAILED",
+            ExecutionStatus::Cancelled => "CANCELLED",
+        };
+
+        let upstream: HashMap<&String, String> = state
+            .upstream_states
+            .iter()
+            .map(|(name, at)| (name, at.to_rfc3339()))
+            .collect();
+        let upstream_json = serde_json::to_string(&upstream)?;
+
+        let sql = format!(
+            r#"
+            INSERT INTO `{table_name}` (
+                query_name, partition_date, version, sql_revision, effective_from,
+                sql_checksum, schema_checksum, yaml_checksum,
+                executed_sql_b64, executed_yaml_b64, upstream_states,
+                executed_at, execution_time_ms, rows_written, bytes_processed, status,
+                partition_key, partition_type, bytes_billed, slot_ms, job_id, write_strategy
+            ) VALUES (
+                {query_name}, '{partition_date}', {version}, {revision}, '{effective_from}',
+                '{sql_checksum}', '{schema_checksum}', '{yaml_checksum}',
+                {sql_b64}, {yaml_b64}, PARSE_JSON({upstream}),
+                '{executed_at}', {time_ms}, {rows}, {bytes}, '{status}',
+                '{partition_key}', '{partition_type}', {billed}, {slot_ms}, {job_id}, {strategy}
+            )
+            "#,
+            table_name = table_name,
+            query_name = sql_string(&state.query_name),
+            partition_date = state.partition_date,
+            version = state.version,
+            revision = state.sql_revision.map(|r| r.to_string()).unwrap_or("NULL".to_string()),
+            effective_from = state.effective_from,
+            sql_checksum = state.sql_checksum,
+            schema_checksum = state.schema_checksum,
+            yaml_checksum = state.yaml_checksum,
+            sql_b64 = state.executed_sql_b64.as_deref().map(sql_string).unwrap_or("NULL".to_string()),
+            yaml_b64 = state.executed_yaml_b64.as_deref().map(sql_string).unwrap_or("NULL".to_string()),
+            upstream = sql_string(&upstream_json),
+            executed_at = state.executed_at.format("%Y-%m-%d %H:%M:%S%.6f UTC"),
+            time_ms = state.execution_time_ms.map(|t| t.to_string()).unwrap_or("NULL".to_string()),
+            rows = state.rows_written.map(|r| r.to_string()).unwrap_or("NULL".to_string()),
+            bytes = state.bytes_processed.map(|b| b.to_string()).unwrap_or("NULL".to_string()),
+            status = status_str,
+            partition_key = state.partition_key,
+            partition_type = partition_type_name(&state.partition_key.partition_type()),
+            billed = state.bytes_billed.map(|b| b.to_string()).unwrap_or("NULL".to_string()),
+            slot_ms = state.slot_ms.map(|s| s.to_string()).unwrap_or("NULL".to_string()),
+            job_id = state.job_id.as_deref().map(sql_string).unwrap_or("NULL".to_string()),
+            strategy = state.write_strategy.map(|s| sql_string(s.as_str())).unwrap_or("NULL".to_string()),
+        );
+
+        self.client.execute_query(&sql).await
+    }
+
+    /// Latest successful execution time of each named query for a partition.
+    /// Queries that never succeeded for the partition are absent from the map.
+    pub async fn latest_executions(
+        &self,
+        query_names: &[String],
+        partition_key: &PartitionKey,
+    ) -> Result<HashMap<String, DateTime<Utc>>> {
+        if query_names.is_empty() {
+            return Ok(HashMap::new());
+        }
+
+        let table_name = format!("{}.{}", self.dataset, STATE_TABLE);
+        let names = query_names
+            .iter()
+            .map(|n| sql_string(n))
+            .collect::<Vec<_>>()
+            .join(", ");
+
+        // An overlapping partition starts no earlier than the year the key
+        // starts in (YEAR being the coarsest granularity) and before the key
+        // ends. The exact overlap, and RANGE keys, are checked after the read.
+        let date_filter = match partition_key.time_span() {
+            Some((start, end)) => format!(
+                "partition_date BETWEEN DATE_TRUNC('{}', YEAR) AND '{}'",
+                start.date(),
+                (end - chrono::Duration::microseconds(1)).date(),
+            ),
+            None => "TRUE".to_string(),
+        };
+
+        let sql = format!(
+            r#"
+            SELECT query_name, partition_date, partition_key, partition_type, UNIX_MICROS(MAX(executed_at))
+            FROM `{table_name}`
+            WHERE {date_filter}
+              AND status = 'SUCCESS'
+              AND query_name IN ({names})
+            GROUP BY query_name, partition_date, partition_key, partition_type
+            "#,
+            table_name = table_name,
+            date_filter = date_filter,
+            names = names,
+        );
+
+        let rows = self.client.query_rows(&sql).await?;
+
+        let mut latest: HashMap<String, DateTime<Utc>> = HashMap::new();
+        for row in rows {
+            let Some(name) = row.first().cloned().flatten() else { continue };
+            let key = match (row.get(2).cloned().flatten(), row.get(3).cloned().flatten()) {
+                (Some(key), Some(kind)) => {
+                    let partition_type = parse_partition_type(&kind)?;
+                    PartitionKey::parse(&key, &partition_type).map_err(BqDriftError::Migration)?
+                }
+                _ => PartitionKey::Day(parse_date(&row, 1, "partition_date")?),
+            };
+            if !key.overlaps(partition_key) {
+                continue;
+            }
+            let Some(executed_at) = row
+                .get(4)
+                .cloned()
+                .flatten()
+                .and_then(|micros| micros.parse::<i64>().ok())
+                .and_then(DateTime::from_timestamp_micros)
+            else {
+                continue;
+            };
+
+            latest
+                .entry(name)
+                .and_modify(|at| *at = (*at).max(executed_at))
+                .or_insert(executed_at);
+        }
+
+        Ok(latest)
     }
 
     pub async fn record_run(&self, run: &QueryRun) -> Result<()> {
@@ -101,24 +303,21 @@ impl MigrationTracker {
 
         let sql = format!(
             r#"
-            SELECT
-                query_name, query_version, sql_revision, partition_date,
-                executed_at, rows_written, bytes_processed, execution_time_ms, status
+            SELECT {columns}
             FROM `{table_name}`
-            WHERE query_name = '{query_name}'
+            WHERE query_name = {query_name}
               AND partition_date = '{partition_date}'
             ORDER BY executed_at DESC
             LIMIT 1
             "#,
+            columns = RUN_COLUMNS,
             table_name = table_name,
-            query_name = query_name,
+            query_name = sql_string(query_name),
             partition_date = partition_date,
         );
 
-        // Note: In a real implementation, we'd parse the query results
-        // For now, this is a placeholder that executes the query
-        self.client.execute_query(&sql).await?;
-        Ok(None)
+        let rows = self.client.query_rows(&sql).await?;
+        rows.first().map(|row| parse_run_row(row)).transpose()
     }
 
     pub async fn get_runs_for_date_range(
@@ -131,21 +330,277 @@ impl MigrationTracker {
 
         let sql = format!(
             r#"
-            SELECT
-                query_name, query_version, sql_revision, partition_date,
-                executed_at, rows_written, bytes_processed, execution_time_ms, status
+            SELECT {columns}
             FROM `{table_name}`
-            WHERE query_name = '{query_name}'
+            WHERE query_name = {query_name}
               AND partition_date BETWEEN '{from}' AND '{to}'
             ORDER BY partition_date, executed_at DESC
             "#,
+            columns = RUN_COLUMNS,
             table_name = table_name,
-            query_name = query_name,
+            query_name = sql_string(query_name),
             from = from,
             to = to,
         );
 
-        self.client.execute_query(&sql).await?;
-        Ok(Vec::new())
+        let rows = self.client.query_rows(&sql).await?;
+        rows.iter().map(|row| parse_run_row(row)).collect()
+    }
+
+    /// Most recent state of every tracked partition, across all queries.
+    pub async fn get_latest_states(&self) -> Result<Vec<PartitionState>> {
+        self.fetch_latest_states("TRUE").await
+    }
+
+    /// Most recent state of every tracked partition that overlaps the days
+    /// `from..=to` (see `PartitionKey::overlaps_dates`). Month and year
+    /// partitions are stored under their first day, so the scan starts at
+    /// the beginning of `from`'s year and the overlap is checked here.
+    pub async fn get_latest_states_for_date_range(
+        &self,
+        from: NaiveDate,
+        to: NaiveDate,
+    ) -> Result<Vec<PartitionState>> {
+        let filter = format!(
+            "(partition_type = 'RANGE' OR partition_date BETWEEN DATE_TRUNC(DATE '{}', YEAR) AND DATE '{}')",
+            from, to
+        );
+        let mut states = self.fetch_latest_states(&filter).await?;
+        states.retain(|s| s.partition_key.overlaps_dates(from, to));
+        Ok(states)
+    }
+
+    async fn fetch_latest_states(&self, filter: &str) -> Result<Vec<PartitionState>> {
+        let table_name = format!("{}.{}", self.dataset, STATE_TABLE);
+
+        let sql = format!(
+            r#"
+            SELECT {columns}
+            FROM `{table_name}`
+            WHERE {filter}
+            QUALIFY ROW_NUMBER() OVER (
+                PARTITION BY query_name, partition_date, partition_key
+                ORDER BY executed_at DESC
+            ) = 1
+            ORDER BY query_name, partition_date, partition_key
+            "#,
+            columns = STATE_COLUMNS,
+            table_name = table_name,
+            filter = filter,
+        );
+
+        let rows = self.client.query_rows(&sql).await?;
+        rows.iter().map(|row| parse_state_row(row)).collect()
+    }
+}
+
+const RUN_COLUMNS: &str = "query_name, query_version, sql_revision, partition_date, \
+    UNIX_MICROS(executed_at), rows_written, bytes_processed, execution_time_ms, status";
+
+const STATE_COLUMNS: &str = "query_name, partition_date, version, sql_revision, effective_from, \
+    sql_checksum, schema_checksum, yaml_checksum, executed_sql_b64, executed_yaml_b64, \
+    TO_JSON_STRING(upstream_states), UNIX_MICROS(executed_at), execution_time_ms, \
+    rows_written, bytes_processed, status, partition_key, partition_type, \
+    bytes_billed, slot_ms, job_id, write_strategy";
+
+fn parse_run_row(row: &[Option<String>]) -> Result<QueryRun> {
+    Ok(QueryRun {
+        query_name: required(row, 0, "query_name")?.to_string(),
+        query_version: parse_required(row, 1, "query_version")?,
+        sql_revision: parse_optional(row, 2, "sql_revision")?,
+        partition_date: parse_date(row, 3, "partition_date")?,
+        executed_at: parse_timestamp(row, 4, "executed_at")?,
+        rows_written: parse_optional(row, 5, "rows_written")?,
+        bytes_processed: parse_optional(row, 6, "bytes_processed")?,
+        execution_time_ms: parse_optional(row, 7, "execution_time_ms")?,
+        status: match required(row, 8, "status")? {
+            "SUCCESS" => RunStatus::Success,
+            _ => RunStatus::Failed,
+        },
+    })
+}
+
+fn parse_state_row(row: &[Option<String>]) -> Result<PartitionState> {
+    let upstream_states = match row.get(10).cloned().flatten() {
+        Some(json) if json != "null" => {
+            serde_json::from_str::<HashMap<String, DateTime<Utc>>>(&json)?
+        }
+        _ => HashMap::new(),
+    };
+
+    let partition_date = parse_date(row, 1, "partition_date")?;
+    let partition_key = match (row.get(16).cloned().flatten(), row.get(17).cloned().flatten()) {
+        (Some(key), Some(kind)) => {
+            let partition_type = parse_partition_type(&kind)?;
+            PartitionKey::parse(&key, &partition_type).map_err(BqDriftError::Migration)?
+        }
+        _ => PartitionKey::Day(partition_date),
+    };
+
+    Ok(PartitionState {
+        query_name: required(row, 0, "query_name")?.to_string(),
+        partition_key,
+        partition_date,
+        version: parse_required(row, 2, "version")?,
+        sql_revision: parse_optional(row, 3, "sql_revision")?,
+        effective_from: parse_date(row, 4, "effective_from")?,
+        sql_checksum: required(row, 5, "sql_checksum")?.to_string(),
+        schema_checksum: required(row, 6, "schema_checksum")?.to_string(),
+        yaml_checksum: required(row, 7, "yaml_checksum")?.to_string(),
+        executed_sql_b64: row.get(8).cloned().flatten(),
+        executed_yaml_b64: row.get(9).cloned().flatten(),
+        upstream_states,
+        executed_at: parse_timestamp(row, 11, "executed_at")?,
+        execution_time_ms: parse_optional(row, 12, "execution_time_ms")?,
+        rows_written: parse_optional(row, 13, "rows_written")?,
+        bytes_processed: parse_optional(row, 14, "bytes_processed")?,
+        bytes_billed: parse_optional(row, 18, "bytes_billed")?,
+        slot_ms: parse_optional(row, 19, "slot_ms")?,
+        job_id: row.get(20).cloned().flatten(),
+        write_strategy: parse_optional(row, 21, "write_strategy")?,
+        status: match required(row, 15, "status")? {
+            "SUCCESS" => ExecutionStatus::Success,
+            "CANCELLED" => ExecutionStatus::Cancelled,
+            _ => ExecutionStatus::Failed,
+        },
+    })
+}
+
+fn partition_type_name(partition_type: &PartitionType) -> &'static str {
+    match partition_type {
+        PartitionType::Hour => "HOUR",
+        PartitionType::Day => "DAY",
+        PartitionType::Month => "MONTH",
+        PartitionType::Year => "YEAR",
+        PartitionType::Range => "RANGE",
+        PartitionType::IngestionTime => "INGESTIONTIME",
+    }
+}
+
+fn parse_partition_type(value: &str) -> Result<PartitionType> {
+    match value {
+        "HOUR" => Ok(PartitionType::Hour),
+        "DAY" => Ok(PartitionType::Day),
+        "MONTH" => Ok(PartitionType::Month),
+        "YEAR" => Ok(PartitionType::Year),
+        "RANGE" => Ok(PartitionType::Range),
+        "INGESTIONTIME" => Ok(PartitionType::IngestionTime),
+        _ => Err(BqDriftError::Migration(format!("Invalid partition type '{}'", value))),
+    }
+}
+
+fn required<'a>(row: &'a [Option<String>], idx: usize, column: &str) -> Result<&'a str> {
+    row.get(idx)
+        .and_then(|v| v.as_deref())
+        .ok_or_else(|| BqDriftError::Migration(format!("Missing value for column '{}'", column)))
+}
+
+fn parse_required<T: std::str::FromStr>(row: &[Option<String>], idx: usize, column: &str) -> Result<T> {
+    let value = required(row, idx, column)?;
+    value.parse::<T>().map_err(|_| {
+        BqDriftError::Migration(format!("Invalid value '{}' for column '{}'", value, column))
+    })
+}
+
+fn parse_optional<T: std::str::FromStr>(row: &[Option<String>], idx: usize, column: &str) -> Result<Option<T>> {
+    match row.get(idx).and_then(|v| v.as_deref()) {
+        Some(_) => parse_required(row, idx, column).map(Some),
+        None => Ok(None),
+    }
+}
+
+fn parse_date(row: &[Option<String>], idx: usize, column: &str) -> Result<NaiveDate> {
+    let value = required(row, idx, column)?;
+    NaiveDate::parse_from_str(value, "%Y-%m-%d").map_err(|_| {
+        BqDriftError::Migration(format!("Invalid date '{}' for column '{}'", value, column))
+    })
+}
+
+fn parse_timestamp(row: &[Option<String>], idx: usize, column: &str) -> Result<DateTime<Utc>> {
+    let micros: i64 = parse_required(row, idx, column)?;
+    DateTime::from_timestamp_micros(micros).ok_or_else(|| {
+        BqDriftError::Migration(format!("Invalid timestamp '{}' for column '{}'", micros, column))
+    })
+}
+
+fn sql_string(value: &str) -> String {
+    format!("'{}'", value.replace('\\', "\\\\").replace('\'', "\\'"))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::dsl::WriteStrategy;
+
+    fn cells(values: &[Option<&str>]) -> Vec<Option<String>> {
+        values.iter().map(|v| v.map(|s| s.to_string())).collect()
+    }
+
+    #[test]
+    fn test_parse_run_row() {
+        let row = cells(&[
+            Some("daily_stats"), Some("2"), None, Some("2024-06-15"),
+            Some("1718445600000000"), Some("150"), None, Some("1200"), Some("SUCCESS"),
+        ]);
+
+        let run = parse_run_row(&row).unwrap();
+        assert_eq!(run.query_name, "daily_stats");
+        assert_eq!(run.query_version, 2);
+        assert_eq!(run.sql_revision, None);
+        assert_eq!(run.partition_date, NaiveDate::from_ymd_opt(2024, 6, 15).unwrap());
+        assert_eq!(run.executed_at.timestamp(), 1718445600);
+        assert_eq!(run.rows_written, Some(150));
+        assert_eq!(run.bytes_processed, None);
+        assert!(matches!(run.status, RunStatus::Success));
+    }
+
+    #[test]
+    fn test_parse_state_row() {
+        let row = cells(&[
+            Some("daily_stats"), Some("2024-06-15"), Some("2"), Some("1"), Some("2024-06-01"),
+            Some("sql"), Some("schema"), Some("yaml"), Some("H4sI"), None,
+            Some(r#"{"upstream":"2024-06-15T10:00:00Z"}"#), Some("1718445600000000"),
+            Some("1200"), None, None, Some("FAILED"),
+        ]);
+
+        let state = parse_state_row(&row).unwrap();
+        assert_eq!(state.version, 2);
+        assert_eq!(state.sql_revision, Some(1));
+        assert_eq!(state.effective_from, NaiveDate::from_ymd_opt(2024, 6, 1).unwrap());
+        assert_eq!(state.executed_sql_b64.as_deref(), Some("H4sI"));
+        assert!(state.executed_yaml_b64.is_none());
+        assert_eq!(state.upstream_states["upstream"].timestamp(), 1718445600);
+        assert_eq!(state.status, ExecutionStatus::Failed);
+        assert_eq!(state.partition_key, PartitionKey::Day(NaiveDate::from_ymd_opt(2024, 6, 15).unwrap()));
+    }
+
+    #[test]
+    fn test_parse_state_row_partition_key() {
+        let row = cells(&[
+            Some("hourly_stats"), Some("2024-06-15"), Some("1"), None, Some("2024-06-01"),
+            Some("sql"), Some("schema"), Some("yaml"), None, None,
+            None, Some("1718445600000000"), None, None, None, Some("SUCCESS"),
+            Some("2024-06-15T10"), Some("HOUR"), Some("10485760"), Some("5400"), Some("job_abc"),
+            Some("truncate_partition"),
+        ]);
+
+        let state = parse_state_row(&row).unwrap();
+        assert_eq!(state.partition_key.to_string(), "2024-06-15T10");
+        assert_eq!(state.partition_key.partition_type(), PartitionType::Hour);
+        assert_eq!(state.bytes_billed, Some(10485760));
+        assert_eq!(state.slot_ms, Some(5400));
+        assert_eq!(state.job_id.as_deref(), Some("job_abc"));
+        assert_eq!(state.write_strategy, Some(WriteStrategy::TruncatePartition));
+    }
+
+    #[test]
+    fn test_parse_state_row_missing_required() {
+        let row = cells(&[Some("daily_stats"), None]);
+        assert!(matches!(parse_state_row(&row), Err(BqDriftError::Migration(_))));
+    }
+
+    #[test]
+    fn test_sql_string_escapes_quotes() {
+        assert_eq!(sql_string("it's"), r"'it\'s'");
     }
 }
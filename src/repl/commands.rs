@@ -9,8 +9,10 @@ pub enum ReplCommand {
         partition: Option<String>,
         dry_run: bool,
         skip_invariants: bool,
+        estimate: bool,
         scratch: Option<String>,
         scratch_ttl: Option<u32>,
+        tracking_dataset: String,
     },
     Backfill {
         query: String,
@@ -18,6 +20,11 @@ pub enum ReplCommand {
         to: String,
         dry_run: bool,
         skip_invariants: bool,
+        estimate: bool,
+        concurrency: usize,
+        max_failures: Option<usize>,
+        cascade: bool,
+        tracking_dataset: String,
     },
     Check {
         query: String,
@@ -32,11 +39,20 @@ pub enum ReplCommand {
         query: String,
         version: Option<u32>,
     },
+    Graph {
+        query: Option<String>,
+        upstream: bool,
+        downstream: bool,
+        date: Option<String>,
+        format: String,
+    },
     Validate,
     Sync {
         from: Option<String>,
         to: Option<String>,
         dry_run: bool,
+        skip_invariants: bool,
+        estimate: bool,
         tracking_dataset: String,
         allow_source_mutation: bool,
     },
@@ -45,6 +61,7 @@ pub enum ReplCommand {
         modified_only: bool,
         diff: bool,
         output: String,
+        tracking_dataset: String,
     },
     Init {
         dataset: String,
@@ -149,21 +166,36 @@ impl ReplCommand {
                     .and_then(|v| v.parse().ok());
                 Ok(ReplCommand::Show { query, version })
             }
+            "graph" => {
+                let query = find_arg(&parts, "--query", "-q")
+                    .or_else(|| parts.get(1).filter(|s| !s.starts_with('-')).map(|s| s.to_string()));
+                let upstream = has_flag(&parts, "--upstream");
+                let downstream = has_flag(&parts, "--downstream");
+                let date = find_arg(&parts, "--date", "-d");
+                let format = find_arg(&parts, "--format", "-f")
+                    .unwrap_or_else(|| "ascii".to_string());
+                Ok(ReplCommand::Graph { query, upstream, downstream, date, format })
+            }
             "run" => {
                 let query = find_arg(&parts, "--query", "-q");
                 let partition = find_arg(&parts, "--partition", "-p");
                 let dry_run = has_flag(&parts, "--dry-run");
                 let skip_invariants = has_flag(&parts, "--skip-invariants");
+                let estimate = has_flag(&parts, "--estimate");
                 let scratch = find_arg(&parts, "--scratch", "-s");
                 let scratch_ttl = find_arg(&parts, "--scratch-ttl", "")
                     .and_then(|v| v.parse().ok());
+                let tracking_dataset = find_arg(&parts, "--tracking-dataset", "")
+                    .unwrap_or_else(|| "bqdrift".to_string());
                 Ok(ReplCommand::Run {
                     query,
                     partition,
                     dry_run,
                     skip_invariants,
+                    estimate,
                     scratch,
                     scratch_ttl,
+                    tracking_dataset,
                 })
             }
             "backfill" => {
@@ -176,12 +208,26 @@ impl ReplCommand {
                     .ok_or_else(|| crate::error::BqDriftError::Repl("backfill requires --to".to_string()))?;
                 let dry_run = has_flag(&parts, "--dry-run");
                 let skip_invariants = has_flag(&parts, "--skip-invariants");
+                let estimate = has_flag(&parts, "--estimate");
+                let concurrency = find_arg(&parts, "--concurrency", "")
+                    .and_then(|v| v.parse().ok())
+                    .unwrap_or(1);
+                let max_failures = find_arg(&parts, "--max-failures", "")
+                    .and_then(|v| v.parse().ok());
+                let cascade = has_flag(&parts, "--cascade");
+                let tracking_dataset = find_arg(&parts, "--tracking-dataset", "")
+                    .unwrap_or_else(|| "bqdrift".to_string());
                 Ok(ReplCommand::Backfill {
                     query,
                     from,
                     to,
                     dry_run,
                     skip_invariants,
+                    estimate,
+                    concurrency,
+                    max_failures,
+                    cascade,
+                    tracking_dataset,
                 })
             }
             "check" => {
@@ -202,6 +248,8 @@ impl ReplCommand {
                 let from = find_arg(&parts, "--from", "-f");
                 let to = find_arg(&parts, "--to", "-t");
                 let dry_run = has_flag(&parts, "--dry-run");
+                let skip_invariants = has_flag(&parts, "--skip-invariants");
+                let estimate = has_flag(&parts, "--estimate");
                 let tracking_dataset = find_arg(&parts, "--tracking-dataset", "")
                     .unwrap_or_else(|| "bqdrift".to_string());
                 let allow_source_mutation = has_flag(&parts, "--allow-source-mutation");
@@ -209,6 +257,8 @@ impl ReplCommand {
                     from,
                     to,
                     dry_run,
+                    skip_invariants,
+                    estimate,
                     tracking_dataset,
                     allow_source_mutation,
                 })
@@ -219,11 +269,14 @@ impl ReplCommand {
                 let diff = has_flag(&parts, "--diff");
                 let output = find_arg(&parts, "--output", "-o")
                     .unwrap_or_else(|| "table".to_string());
+                let tracking_dataset = find_arg(&parts, "--tracking-dataset", "")
+                    .unwrap_or_else(|| "bqdrift".to_string());
                 Ok(ReplCommand::Audit {
                     query,
                     modified_only,
                     diff,
                     output,
+                    tracking_dataset,
                 })
             }
             "init" => {
@@ -285,6 +338,30 @@ impl ReplCommand {
                     .map(|v| v as u32);
                 Ok(ReplCommand::Show { query, version })
             }
+            "graph" => {
+                let query = params
+                    .and_then(|p| p.get("query"))
+                    .and_then(|v| v.as_str())
+                    .map(|s| s.to_string());
+                let upstream = params
+                    .and_then(|p| p.get("upstream"))
+                    .and_then(|v| v.as_bool())
+                    .unwrap_or(false);
+                let downstream = params
+                    .and_then(|p| p.get("downstream"))
+                    .and_then(|v| v.as_bool())
+                    .unwrap_or(false);
+                let date = params
+                    .and_then(|p| p.get("date"))
+                    .and_then(|v| v.as_str())
+                    .map(|s| s.to_string());
+                let format = params
+                    .and_then(|p| p.get("format"))
+                    .and_then(|v| v.as_str())
+                    .unwrap_or("json")
+                    .to_string();
+                Ok(ReplCommand::Graph { query, upstream, downstream, date, format })
+            }
             "run" => {
                 let query = params
                     .and_then(|p| p.get("query"))
@@ -302,6 +379,10 @@ impl ReplCommand {
                     .and_then(|p| p.get("skip_invariants"))
                     .and_then(|v| v.as_bool())
                     .unwrap_or(false);
+                let estimate = params
+                    .and_then(|p| p.get("estimate"))
+                    .and_then(|v| v.as_bool())
+                    .unwrap_or(false);
                 let scratch = params
                     .and_then(|p| p.get("scratch"))
                     .and_then(|v| v.as_str())
@@ -310,13 +391,20 @@ impl ReplCommand {
                     .and_then(|p| p.get("scratch_ttl"))
                     .and_then(|v| v.as_u64())
                     .map(|v| v as u32);
+                let tracking_dataset = params
+                    .and_then(|p| p.get("tracking_dataset"))
+                    .and_then(|v| v.as_str())
+                    .map(|s| s.to_string())
+                    .unwrap_or_else(|| "bqdrift".to_string());
                 Ok(ReplCommand::Run {
                     query,
                     partition,
                     dry_run,
                     skip_invariants,
+                    estimate,
                     scratch,
                     scratch_ttl,
+                    tracking_dataset,
                 })
             }
             "backfill" => {
@@ -343,12 +431,39 @@ impl ReplCommand {
                     .and_then(|p| p.get("skip_invariants"))
                     .and_then(|v| v.as_bool())
                     .unwrap_or(false);
+                let estimate = params
+                    .and_then(|p| p.get("estimate"))
+                    .and_then(|v| v.as_bool())
+                    .unwrap_or(false);
+                let concurrency = params
+                    .and_then(|p| p.get("concurrency"))
+                    .and_then(|v| v.as_u64())
+                    .map(|v| v as usize)
+                    .unwrap_or(1);
+                let max_failures = params
+                    .and_then(|p| p.get("max_failures"))
+                    .and_then(|v| v.as_u64())
+                    .map(|v| v as usize);
+                let cascade = params
+                    .and_then(|p| p.get("cascade"))
+                    .and_then(|v| v.as_bool())
+                    .unwrap_or(false);
+                let tracking_dataset = params
+                    .and_then(|p| p.get("tracking_dataset"))
+                    .and_then(|v| v.as_str())
+                    .map(|s| s.to_string())
+                    .unwrap_or_else(|| "bqdrift".to_string());
                 Ok(ReplCommand::Backfill {
                     query,
                     from,
                     to,
                     dry_run,
                     skip_invariants,
+                    estimate,
+                    concurrency,
+                    max_failures,
+                    cascade,
+                    tracking_dataset,
                 })
             }
             "check" => {
@@ -389,6 +504,14 @@ impl ReplCommand {
                     .and_then(|p| p.get("dry_run"))
                     .and_then(|v| v.as_bool())
                     .unwrap_or(false);
+                let skip_invariants = params
+                    .and_then(|p| p.get("skip_invariants"))
+                    .and_then(|v| v.as_bool())
+                    .unwrap_or(false);
+                let estimate = params
+                    .and_then(|p| p.get("estimate"))
+                    .and_then(|v| v.as_bool())
+                    .unwrap_or(false);
                 let tracking_dataset = params
                     .and_then(|p| p.get("tracking_dataset"))
                     .and_then(|v| v.as_str())
@@ -402,6 +525,8 @@ impl ReplCommand {
                     from,
                     to,
                     dry_run,
+                    skip_invariants,
+                    estimate,
                     tracking_dataset,
                     allow_source_mutation,
                 })
@@ -424,11 +549,17 @@ impl ReplCommand {
                     .and_then(|v| v.as_str())
                     .map(|s| s.to_string())
                     .unwrap_or_else(|| "table".to_string());
+                let tracking_dataset = params
+                    .and_then(|p| p.get("tracking_dataset"))
+                    .and_then(|v| v.as_str())
+                    .map(|s| s.to_string())
+                    .unwrap_or_else(|| "bqdrift".to_string());
                 Ok(ReplCommand::Audit {
                     query,
                     modified_only,
                     diff,
                     output,
+                    tracking_dataset,
                 })
             }
             "init" => {
@@ -537,6 +668,29 @@ mod tests {
         }
     }
 
+    #[test]
+    fn test_parse_graph() {
+        let cmd = ReplCommand::parse_interactive("graph daily_stats --upstream --format dot").unwrap();
+        if let ReplCommand::Graph { query, upstream, downstream, date, format } = cmd {
+            assert_eq!(query, Some("daily_stats".to_string()));
+            assert!(upstream);
+            assert!(!downstream);
+            assert_eq!(date, None);
+            assert_eq!(format, "dot");
+        } else {
+            panic!("Expected Graph command");
+        }
+
+        let cmd = ReplCommand::parse_interactive("graph --date 2024-06-01").unwrap();
+        if let ReplCommand::Graph { query, date, format, .. } = cmd {
+            assert_eq!(query, None);
+            assert_eq!(date, Some("2024-06-01".to_string()));
+            assert_eq!(format, "ascii");
+        } else {
+            panic!("Expected Graph command");
+        }
+    }
+
     #[test]
     fn test_from_json_rpc_list() {
         let params = serde_json::json!({"detailed": true});
@@ -560,4 +714,74 @@ mod tests {
             panic!("Expected Run command");
         }
     }
+
+    #[test]
+    fn test_parse_backfill_concurrency() {
+        let cmd = ReplCommand::parse_interactive(
+            "backfill my_query --from 2024-01-01 --to 2024-01-31 --concurrency 8 --max-failures 3"
+        ).unwrap();
+        if let ReplCommand::Backfill { concurrency, max_failures, .. } = cmd {
+            assert_eq!(concurrency, 8);
+            assert_eq!(max_failures, Some(3));
+        } else {
+            panic!("Expected Backfill command");
+        }
+
+        let cmd = ReplCommand::parse_interactive("backfill my_query --from 2024-01-01 --to 2024-01-31").unwrap();
+        if let ReplCommand::Backfill { concurrency, max_failures, cascade, .. } = cmd {
+            assert_eq!(concurrency, 1);
+            assert_eq!(max_failures, None);
+            assert!(!cascade);
+        } else {
+            panic!("Expected Backfill command");
+        }
+    }
+
+    #[test]
+    fn test_parse_backfill_cascade() {
+        let cmd = ReplCommand::parse_interactive("backfill my_query --from 2024-03-01 --to 2024-03-31 --cascade --dry-run").unwrap();
+        assert!(matches!(cmd, ReplCommand::Backfill { cascade: true, dry_run: true, .. }));
+
+        let params = serde_json::json!({
+            "query": "my_query",
+            "from": "2024-03-01",
+            "to": "2024-03-31",
+            "cascade": true
+        });
+        let cmd = ReplCommand::from_json_rpc("backfill", Some(&params)).unwrap();
+        assert!(matches!(cmd, ReplCommand::Backfill { cascade: true, .. }));
+    }
+
+    #[test]
+    fn test_from_json_rpc_backfill_concurrency() {
+        let params = serde_json::json!({
+            "query": "my_query",
+            "from": "2024-01-01",
+            "to": "2024-01-31",
+            "concurrency": 4,
+            "max_failures": 2
+        });
+        let cmd = ReplCommand::from_json_rpc("backfill", Some(&params)).unwrap();
+        if let ReplCommand::Backfill { concurrency, max_failures, .. } = cmd {
+            assert_eq!(concurrency, 4);
+            assert_eq!(max_failures, Some(2));
+        } else {
+            panic!("Expected Backfill command");
+        }
+    }
+
+    #[test]
+    fn test_parse_estimate() {
+        let cmd = ReplCommand::parse_interactive("sync --estimate").unwrap();
+        assert!(matches!(cmd, ReplCommand::Sync { estimate: true, .. }));
+
+        let params = serde_json::json!({
+            "query": "my_query",
+            "from": "2024-01-01",
+            "to": "2024-01-31",
+            "estimate": true
+        });
+        let cmd = ReplCommand::from_json_rpc("backfill", Some(&params)).unwrap();
+        assert!(matches!(cmd, ReplCommand::Backfill { estimate: true, .. }));
+    }
 }
@@ -12,7 +12,7 @@ use super::commands::ReplCommand;
 use super::session::ReplSession;
 
 const COMMANDS: &[&str] = &[
-    "list", "show", "validate", "run", "backfill", "check",
+    "list", "show", "graph", "validate", "run", "backfill", "check",
     "sync", "audit", "init", "scratch", "reload", "status", "help", "exit", "quit",
 ];
 
@@ -21,7 +21,8 @@ const FLAGS: &[&str] = &[
     "--skip-invariants", "--scratch", "--scratch-ttl", "--from", "--to",
     "--before", "--after", "--tracking-dataset", "--allow-source-mutation",
     "--modified-only", "--diff", "--output", "--dataset", "--project",
-    "--scratch-project",
+    "--scratch-project", "--upstream", "--downstream", "--date", "--format", "--cascade",
+    "--estimate", "--concurrency", "--max-failures",
 ];
 
 struct ReplHelper {
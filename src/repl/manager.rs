@@ -5,8 +5,9 @@ use std::sync::Arc;
 use chrono::{DateTime, Utc, Duration};
 use tokio::sync::{mpsc, oneshot};
 use super::commands::ReplCommand;
-use super::protocol::{JsonRpcRequest, JsonRpcResponse, SessionInfo, ServerConfigInfo, SESSION_EXPIRED, SESSION_LIMIT};
+use super::protocol::{JsonRpcRequest, JsonRpcResponse, SessionInfo, ServerConfigInfo, INVALID_SESSION_CONFIG, SESSION_EXPIRED, SESSION_LIMIT};
 use super::session::ReplSession;
+use crate::executor::{ClientConfig, Credentials};
 
 pub struct ServerConfig {
     pub default_project: Option<String>,
@@ -15,6 +16,10 @@ pub struct ServerConfig {
     pub default_idle_timeout_secs: u64,
     pub max_idle_timeout_secs: u64,
     pub cleanup_interval_secs: u64,
+    pub default_state_file: Option<PathBuf>,
+    pub client_config: ClientConfig,
+    /// Credentials a session can select by name instead of `client_config`'s.
+    pub credential_profiles: HashMap<String, Credentials>,
 }
 
 impl ServerConfig {
@@ -26,6 +31,9 @@ impl ServerConfig {
             default_idle_timeout_secs: 300,
             max_idle_timeout_secs: 3600,
             cleanup_interval_secs: 60,
+            default_state_file: None,
+            client_config: ClientConfig::default(),
+            credential_profiles: HashMap::new(),
         }
     }
 
@@ -43,6 +51,21 @@ impl ServerConfig {
         self.max_idle_timeout_secs = secs;
         self
     }
+
+    pub fn with_state_file(mut self, path: Option<PathBuf>) -> Self {
+        self.default_state_file = path;
+        self
+    }
+
+    pub fn with_client_config(mut self, config: ClientConfig) -> Self {
+        self.client_config = config;
+        self
+    }
+
+    pub fn with_credential_profiles(mut self, profiles: HashMap<String, Credentials>) -> Self {
+        self.credential_profiles = profiles;
+        self
+    }
 }
 
 #[derive(Debug, Clone, Default)]
@@ -51,6 +74,8 @@ pub struct SessionCreateParams {
     pub project: Option<String>,
     pub queries_path: Option<PathBuf>,
     pub idle_timeout_secs: Option<u64>,
+    /// Name of a credential profile from `ServerConfig::credential_profiles`.
+    pub credentials: Option<String>,
     pub metadata: HashMap<String, String>,
 }
 
@@ -71,6 +96,9 @@ impl SessionCreateParams {
             if let Some(n) = p.get("idle_timeout").and_then(|v| v.as_u64()) {
                 result.idle_timeout_secs = Some(n);
             }
+            if let Some(s) = p.get("credentials").and_then(|v| v.as_str()) {
+                result.credentials = Some(s.to_string());
+            }
             if let Some(obj) = p.get("metadata").and_then(|v| v.as_object()) {
                 for (k, v) in obj {
                     if let Some(s) = v.as_str() {
@@ -98,6 +126,7 @@ pub struct SessionHandle {
     idle_timeout_secs: u64,
     project: Option<String>,
     queries_path: Option<PathBuf>,
+    credentials: Option<String>,
     metadata: HashMap<String, String>,
 }
 
@@ -129,6 +158,7 @@ impl SessionHandle {
             expires_at: self.expires_at().to_rfc3339(),
             project: self.project.clone(),
             queries_path: self.queries_path.as_ref().map(|p| p.to_string_lossy().to_string()),
+            credentials: self.credentials.clone(),
             metadata: self.metadata.clone(),
         }
     }
@@ -227,6 +257,11 @@ impl SessionManager {
             max_idle_timeout_secs: self.config.max_idle_timeout_secs,
             default_project: self.config.default_project.clone(),
             default_queries_path: self.config.default_queries_path.to_string_lossy().to_string(),
+            credential_profiles: {
+                let mut names: Vec<String> = self.config.credential_profiles.keys().cloned().collect();
+                names.sort();
+                names
+            },
         }
     }
 
@@ -269,6 +304,16 @@ impl SessionManager {
             return Ok(self.sessions.get(&session_id).unwrap().info());
         }
 
+        if let Some(name) = &params.credentials {
+            if !self.config.credential_profiles.contains_key(name) {
+                return Err(JsonRpcResponse::error(
+                    None,
+                    INVALID_SESSION_CONFIG,
+                    format!("Unknown credential profile '{}'", name),
+                ));
+            }
+        }
+
         let handle = self.create_session(params);
         let info = handle.info();
         self.sessions.insert(session_id, handle);
@@ -286,7 +331,14 @@ impl SessionManager {
             .map(|t| t.min(self.config.max_idle_timeout_secs))
             .unwrap_or(self.config.default_idle_timeout_secs);
 
-        let session = ReplSession::new(project.clone(), queries_path.clone());
+        let mut client_config = self.config.client_config.clone();
+        if let Some(credentials) = params.credentials.as_ref().and_then(|name| self.config.credential_profiles.get(name)) {
+            client_config = client_config.with_credentials(credentials.clone());
+        }
+
+        let session = ReplSession::new(project.clone(), queries_path.clone())
+            .with_state_file(self.config.default_state_file.clone())
+            .with_client_config(client_config);
 
         let (request_tx, request_rx) = mpsc::channel(32);
         let request_count = Arc::new(AtomicU64::new(0));
@@ -312,6 +364,7 @@ impl SessionManager {
             idle_timeout_secs: idle_timeout,
             project,
             queries_path: params.queries_path,
+            credentials: params.credentials,
             metadata: params.metadata,
         }
     }
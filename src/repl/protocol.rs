@@ -121,6 +121,9 @@ pub struct SessionInfo {
     pub project: Option<String>,
     #[serde(skip_serializing_if = "Option::is_none")]
     pub queries_path: Option<String>,
+    /// Credential profile the session authenticates with.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub credentials: Option<String>,
     #[serde(skip_serializing_if = "HashMap::is_empty")]
     pub metadata: std::collections::HashMap<String, String>,
 }
@@ -133,6 +136,8 @@ pub struct ServerConfigInfo {
     pub max_idle_timeout_secs: u64,
     pub default_project: Option<String>,
     pub default_queries_path: String,
+    /// Names of the credential profiles sessions can select.
+    pub credential_profiles: Vec<String>,
 }
 
 #[cfg(test)]
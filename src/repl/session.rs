@@ -1,9 +1,11 @@
 use std::path::PathBuf;
-use chrono::{Datelike, Timelike, NaiveDate, Utc};
+use std::sync::Arc;
+use chrono::{Datelike, Timelike, Utc};
 use crate::error::{BqDriftError, Result};
-use crate::dsl::{QueryDef, QueryLoader, QueryValidator};
+use crate::dsl::{DependencyGraph, GraphFormat, QueryDef, QueryLoader, QueryValidator};
 use crate::schema::{PartitionKey, PartitionType};
-use crate::executor::BqClient;
+use crate::executor::{BqClient, ClientConfig, CostEstimate, PartitionWriteStats, RunReport, cascade_plan, format_bytes};
+use crate::migration::{LocalStateStore, MigrationTracker, StateStore};
 use crate::invariant::{InvariantChecker, CheckStatus, Severity, resolve_invariants_def};
 use super::commands::{ReplCommand, ReplResult};
 
@@ -13,6 +15,8 @@ pub struct ReplSession {
     loader: QueryLoader,
     cached_queries: Option<Vec<QueryDef>>,
     client: Option<BqClient>,
+    state_file: Option<PathBuf>,
+    client_config: ClientConfig,
 }
 
 impl ReplSession {
@@ -23,9 +27,24 @@ impl ReplSession {
             loader: QueryLoader::new(),
             cached_queries: None,
             client: None,
+            state_file: None,
+            client_config: ClientConfig::default(),
         }
     }
 
+    /// Track partition state in a local JSONL file instead of BigQuery.
+    pub fn with_state_file(mut self, path: Option<PathBuf>) -> Self {
+        self.state_file = path;
+        self
+    }
+
+    /// Settings (such as the retry policy) for every BigQuery client the session opens.
+    pub fn with_client_config(mut self, config: ClientConfig) -> Self {
+        self.client_config = config;
+        self.client = None;
+        self
+    }
+
     pub fn project(&self) -> Option<&str> {
         self.project.as_deref()
     }
@@ -59,7 +78,7 @@ impl ReplSession {
             .ok_or_else(|| BqDriftError::Repl("No project set. Use --project flag or set GCP_PROJECT_ID".to_string()))?;
 
         if self.client.is_none() {
-            let client = BqClient::new(project).await?;
+            let client = BqClient::connect(project, self.client_config.clone()).await?;
             self.client = Some(client);
         }
         Ok(self.client.as_ref().unwrap())
@@ -81,11 +100,17 @@ impl ReplSession {
             ReplCommand::Validate => self.cmd_validate(),
             ReplCommand::List { detailed } => self.cmd_list(detailed),
             ReplCommand::Show { query, version } => self.cmd_show(&query, version),
-            ReplCommand::Run { query, partition, dry_run, skip_invariants, scratch, scratch_ttl } => {
-                self.cmd_run(query, partition, dry_run, skip_invariants, scratch, scratch_ttl).await
+            ReplCommand::Graph { query, upstream, downstream, date, format } => {
+                self.cmd_graph(query, upstream, downstream, date, &format)
+            }
+            ReplCommand::Run { query, partition, dry_run, skip_invariants, estimate, scratch, scratch_ttl, tracking_dataset } => {
+                self.cmd_run(query, partition, dry_run, skip_invariants, estimate, scratch, scratch_ttl, &tracking_dataset).await
             }
-            ReplCommand::Backfill { query, from, to, dry_run, skip_invariants } => {
-                self.cmd_backfill(&query, &from, &to, dry_run, skip_invariants).await
+            ReplCommand::Backfill { query, from, to, dry_run, skip_invariants, estimate, concurrency, max_failures, cascade, tracking_dataset } => {
+                let options = crate::BackfillOptions::default()
+                    .with_concurrency(concurrency)
+                    .with_max_failures(max_failures);
+                self.cmd_backfill(&query, &from, &to, dry_run, skip_invariants, estimate, cascade, &options, &tracking_dataset).await
             }
             ReplCommand::Check { query, partition, before, after } => {
                 self.cmd_check(&query, partition, before, after).await
@@ -93,11 +118,11 @@ impl ReplSession {
             ReplCommand::Init { dataset } => {
                 self.cmd_init(&dataset).await
             }
-            ReplCommand::Sync { from, to, dry_run, tracking_dataset, allow_source_mutation } => {
-                self.cmd_sync(from, to, dry_run, &tracking_dataset, allow_source_mutation).await
+            ReplCommand::Sync { from, to, dry_run, skip_invariants, estimate, tracking_dataset, allow_source_mutation } => {
+                self.cmd_sync(from, to, dry_run, skip_invariants, estimate, &tracking_dataset, allow_source_mutation).await
             }
-            ReplCommand::Audit { query, modified_only, diff, output } => {
-                self.cmd_audit(query, modified_only, diff, &output)
+            ReplCommand::Audit { query, modified_only, diff, output, tracking_dataset } => {
+                self.cmd_audit(query, modified_only, diff, &output, &tracking_dataset).await
             }
             ReplCommand::ScratchList { project } => {
                 self.cmd_scratch_list(&project).await
@@ -112,17 +137,21 @@ impl ReplSession {
         let help = r#"Available commands:
   list [--detailed]                    List all queries
   show <query> [--version N]           Show query details
+  graph [query] [--upstream] [--downstream]
+      [--date DATE] [--format ascii|dot|mermaid|json]
   validate                             Validate all query definitions
   run [--query Q] [--partition P]      Run query (all if no query specified)
-      [--dry-run] [--skip-invariants]
-      [--scratch PROJECT] [--scratch-ttl H]
+      [--dry-run] [--estimate] [--skip-invariants]
+      [--scratch PROJECT] [--scratch-ttl H] [--tracking-dataset D]
   backfill <query> --from DATE --to DATE
-      [--dry-run] [--skip-invariants]
+      [--dry-run] [--estimate] [--skip-invariants] [--tracking-dataset D]
+      [--concurrency N] [--max-failures K] [--cascade]
   check <query> [--partition P] [--before] [--after]
   init [--dataset D]                   Initialize tracking table
-  sync [--from DATE] [--to DATE] [--dry-run]
+  sync [--from P] [--to P] [--dry-run] [--estimate] [--skip-invariants]
       [--tracking-dataset D] [--allow-source-mutation]
   audit [--query Q] [--modified-only] [--diff] [--output FORMAT]
+      [--tracking-dataset D]
   scratch list --project P             List scratch tables
   scratch promote --query Q --partition P --scratch-project P
   reload                               Reload queries from disk
@@ -138,19 +167,26 @@ impl ReplSession {
         let queries_count = self.cached_queries.as_ref().map(|q| q.len()).unwrap_or(0);
         let client_status = if self.client.is_some() { "connected" } else { "not connected" };
 
+        let state_store = self.state_file
+            .as_ref()
+            .map(|p| p.display().to_string())
+            .unwrap_or_else(|| "bigquery".to_string());
+
         let output = format!(
-            "Project: {}\nQueries path: {}\nQueries loaded: {}\nClient: {}",
+            "Project: {}\nQueries path: {}\nQueries loaded: {}\nClient: {}\nState store: {}",
             project_str,
             self.queries_path.display(),
             queries_count,
-            client_status
+            client_status,
+            state_store
         );
 
         let data = serde_json::json!({
             "project": self.project,
             "queries_path": self.queries_path.to_string_lossy(),
             "queries_loaded": queries_count,
-            "client_connected": self.client.is_some()
+            "client_connected": self.client.is_some(),
+            "state_store": state_store
         });
 
         ReplResult::success_with_both(output, data)
@@ -249,7 +285,7 @@ impl ReplSession {
         for query in &queries {
             if detailed {
                 output_lines.push(query.name.clone());
-                output_lines.push(format!("  destination: {}.{}", query.destination.dataset, query.destination.table));
+                output_lines.push(format!("  destination: {}", query.destination.table_ref()));
                 if let Some(desc) = &query.description {
                     output_lines.push(format!("  description: {}", desc));
                 }
@@ -263,16 +299,16 @@ impl ReplSession {
                 output_lines.push(String::new());
             } else {
                 let latest = query.latest_version().map(|v| v.version).unwrap_or(0);
-                output_lines.push(format!("{:<30} v{:<3} {}.{}",
+                output_lines.push(format!("{:<30} v{:<3} {}",
                     query.name,
                     latest,
-                    query.destination.dataset,
-                    query.destination.table
+                    query.destination.table_ref()
                 ));
             }
 
             data_list.push(serde_json::json!({
                 "name": query.name,
+                "project": query.destination.project,
                 "dataset": query.destination.dataset,
                 "table": query.destination.table,
                 "latest_version": query.latest_version().map(|v| v.version),
@@ -301,7 +337,7 @@ impl ReplSession {
 
         let mut output_lines = Vec::new();
         output_lines.push(format!("Name: {}", query.name));
-        output_lines.push(format!("Destination: {}.{}", query.destination.dataset, query.destination.table));
+        output_lines.push(format!("Destination: {}", query.destination.table_ref()));
 
         if let Some(desc) = &query.description {
             output_lines.push(format!("Description: {}", desc));
@@ -337,8 +373,10 @@ impl ReplSession {
         let data = serde_json::json!({
             "name": query.name,
             "destination": {
+                "project": query.destination.project,
                 "dataset": query.destination.dataset,
-                "table": query.destination.table
+                "table": query.destination.table,
+                "location": query.destination.location
             },
             "versions": query.versions.iter().map(|v| serde_json::json!({
                 "version": v.version,
@@ -351,14 +389,55 @@ impl ReplSession {
         ReplResult::success_with_both(output_lines.join("\n"), data)
     }
 
+    fn cmd_graph(
+        &mut self,
+        query: Option<String>,
+        upstream: bool,
+        downstream: bool,
+        date: Option<String>,
+        format: &str,
+    ) -> ReplResult {
+        let queries = match self.ensure_queries() {
+            Ok(q) => q.clone(),
+            Err(e) => return ReplResult::failure(e.to_string()),
+        };
+
+        let format: GraphFormat = match format.parse() {
+            Ok(f) => f,
+            Err(e) => return ReplResult::failure(e),
+        };
+
+        let date = match date.map(|d| chrono::NaiveDate::parse_from_str(&d, "%Y-%m-%d")).transpose() {
+            Ok(d) => d,
+            Err(e) => return ReplResult::failure(format!("Invalid date: {}", e)),
+        };
+
+        let mut graph = DependencyGraph::build(&queries, date);
+        if let Some(name) = query {
+            // Neither flag means both directions.
+            let both = !upstream && !downstream;
+            graph = match graph.focus(&name, upstream || both, downstream || both) {
+                Some(g) => g,
+                None => return ReplResult::failure(format!("Query '{}' not found", name)),
+            };
+        } else if upstream || downstream {
+            return ReplResult::failure("--upstream and --downstream require a query".to_string());
+        }
+
+        let data = serde_json::to_value(&graph).unwrap_or_default();
+        ReplResult::success_with_both(graph.render(format), data)
+    }
+
     async fn cmd_run(
         &mut self,
         query_name: Option<String>,
         partition: Option<String>,
         dry_run: bool,
         skip_invariants: bool,
+        estimate: bool,
         scratch: Option<String>,
         scratch_ttl: Option<u32>,
+        tracking_dataset: &str,
     ) -> ReplResult {
         let queries = match self.ensure_queries() {
             Ok(q) => q.clone(),
@@ -374,33 +453,50 @@ impl ReplSession {
         }
 
         let client = match self.ensure_client().await {
-            Ok(c) => c,
+            Ok(c) => c.clone(),
             Err(e) => return ReplResult::failure(e.to_string()),
         };
 
-        let runner = crate::Runner::new(client.clone(), queries.clone());
+        if estimate {
+            let runner = crate::Runner::new(client, queries.clone());
+            let plan = match &query_name {
+                Some(name) => {
+                    let partition_key = match Self::query_partition(&queries, name, &partition) {
+                        Ok(k) => k,
+                        Err(e) => return ReplResult::failure(e),
+                    };
+                    runner.estimate_query_partition(name, partition_key).await
+                }
+                None => match Self::parse_partition(&partition, &PartitionType::Day) {
+                    Ok(k) => runner.estimate_for_partition(k).await,
+                    Err(e) => return ReplResult::failure(e),
+                },
+            };
+            return self.estimate_result(plan);
+        }
+
+        let runner = match self.tracked_runner(client, queries.clone(), tracking_dataset).await {
+            Ok(r) => r,
+            Err(e) => return ReplResult::failure(e.to_string()),
+        };
 
         match query_name {
             Some(name) => {
-                let query = match queries.iter().find(|q| q.name == name) {
-                    Some(q) => q,
-                    None => return ReplResult::failure(format!("Query '{}' not found", name)),
-                };
-                let partition_type = &query.destination.partition.partition_type;
-                let partition_key = match Self::parse_partition(&partition, partition_type) {
+                let partition_key = match Self::query_partition(&queries, &name, &partition) {
                     Ok(k) => k,
                     Err(e) => return ReplResult::failure(e),
                 };
 
+                if let Some(budget) = self.client_config.maximum_bytes_billed {
+                    if let Err(e) = Self::enforce_budget(runner.estimate_query_partition(&name, partition_key.clone()).await, budget) {
+                        return ReplResult::failure(e);
+                    }
+                }
+
                 match runner.run_query_partition(&name, partition_key.clone()).await {
                     Ok(stats) => {
-                        let output = format!("✓ {} v{} completed for {}", stats.query_name, stats.version, stats.partition_key);
-                        let data = serde_json::json!({
-                            "query": stats.query_name,
-                            "version": stats.version,
-                            "partition": stats.partition_key.to_string()
-                        });
-                        ReplResult::success_with_both(output, data)
+                        let output = Self::format_write_stats(&stats);
+                        ReplResult::success_with_both(output, Self::write_stats_json(&stats))
                     }
                     Err(e) => ReplResult::failure(e.to_string()),
                 }
@@ -411,20 +507,33 @@ impl ReplSession {
                     Err(e) => return ReplResult::failure(e),
                 };
 
+                if let Some(budget) = self.client_config.maximum_bytes_billed {
+                    if let Err(e) = Self::enforce_budget(runner.estimate_for_partition(partition_key.clone()).await, budget) {
+                        return ReplResult::failure(e);
+                    }
+                }
+
                 match runner.run_for_partition(partition_key).await {
                     Ok(report) => {
                         let mut output_lines = Vec::new();
                         for stats in &report.stats {
-                            output_lines.push(format!("✓ {} v{} completed for {}", stats.query_name, stats.version, stats.partition_key));
+                            output_lines.push(Self::format_write_stats(stats));
                         }
                         for failure in &report.failures {
                             output_lines.push(format!("✗ {} ({}): {}", failure.query_name, failure.partition_key, failure.error));
                         }
-                        output_lines.push(format!("\n{} succeeded, {} failed", report.stats.len(), report.failures.len()));
+                        for skip in &report.skipped {
+                            output_lines.push(format!("○ {} ({}): skipped, {}", skip.query_name, skip.partition_key, skip.reason));
+                        }
+                        output_lines.extend(Self::format_cancelled(&report));
+                        output_lines.push(format!("\n{} succeeded, {} failed, {} skipped, {} cancelled", report.stats.len(), report.failures.len(), report.skipped.len(), report.cancelled.len()));
 
                         let data = serde_json::json!({
                             "succeeded": report.stats.len(),
-                            "failed": report.failures.len()
+                            "failed": report.failures.len(),
+                            "cancelled": report.cancelled.len(),
+                            "skipped": report.skipped.iter().map(|s| s.query_name.clone()).collect::<Vec<_>>(),
+                            "partitions": report.stats.iter().map(Self::write_stats_json).collect::<Vec<_>>()
                         });
                         ReplResult::success_with_both(output_lines.join("\n"), data)
                     }
@@ -464,7 +573,7 @@ impl ReplSession {
             };
 
             output_lines.push(format!("Query: {}", query.name));
-            output_lines.push(format!("Destination: {}.{}", query.destination.dataset, query.destination.table));
+            output_lines.push(format!("Destination: {}", query.destination.table_ref()));
             output_lines.push(format!("Partition type: {:?}", partition_type));
 
             let date_for_version = partition_key.to_naive_date();
@@ -526,7 +635,7 @@ impl ReplSession {
             Err(e) => return ReplResult::failure(e),
         };
 
-        let scratch_client = match BqClient::new(&scratch_project).await {
+        let scratch_client = match BqClient::connect(&scratch_project, self.client_config.clone()).await {
             Ok(c) => c,
             Err(e) => return ReplResult::failure(format!("Failed to create scratch client: {}", e)),
         };
@@ -549,6 +658,9 @@ impl ReplSession {
                 output_lines.push(format!("  Destination: {}", stats.scratch_table));
                 output_lines.push(format!("  Partition: {}", stats.partition_key));
                 output_lines.push(format!("  Expires: {}", stats.expiration.format("%Y-%m-%dT%H:%M:%SZ")));
+                if let Some(rows) = stats.rows_written {
+                    output_lines.push(format!("  Rows written: {}", rows));
+                }
                 output_lines.push(format!("\nTo promote to production:"));
                 output_lines.push(format!("  scratch promote --query {} --partition {} --scratch-project {}", stats.query_name, stats.partition_key, scratch_project));
 
@@ -557,7 +669,12 @@ impl ReplSession {
                     "version": stats.version,
                     "partition": stats.partition_key.to_string(),
                     "scratch_table": stats.scratch_table,
-                    "expiration": stats.expiration.to_rfc3339()
+                    "expiration": stats.expiration.to_rfc3339(),
+                    "rows_written": stats.rows_written,
+                    "bytes_processed": stats.bytes_processed,
+                    "bytes_billed": stats.bytes_billed,
+                    "slot_ms": stats.slot_ms,
+                    "job_id": stats.job_id
                 });
                 ReplResult::success_with_both(output_lines.join("\n"), data)
             }
@@ -572,6 +689,10 @@ impl ReplSession {
         to: &str,
         dry_run: bool,
         skip_invariants: bool,
+        estimate: bool,
+        cascade: bool,
+        options: &crate::BackfillOptions,
+        tracking_dataset: &str,
     ) -> ReplResult {
         let queries = match self.ensure_queries() {
             Ok(q) => q.clone(),
@@ -593,7 +714,42 @@ impl ReplSession {
             Err(e) => return ReplResult::failure(format!("Invalid to partition: {}", e)),
         };
 
+        let steps = if cascade {
+            match cascade_plan(&queries, query_name, &from_key, &to_key, None) {
+                Ok(steps) => Some(steps),
+                Err(e) => return ReplResult::failure(e.to_string()),
+            }
+        } else {
+            None
+        };
+
+        // The cascade plan leads the output whether or not it is executed.
+        let mut plan_lines = Vec::new();
+        let mut plan_json = serde_json::Value::Null;
+        if let Some(steps) = &steps {
+            for step in steps {
+                let Some(query) = queries.iter().find(|q| q.name == step.query_name) else {
+                    continue;
+                };
+                for key in &step.partitions {
+                    if let Some(version) = query.get_version_for_partition(key) {
+                        plan_lines.push(format!("{} {}: v{} ({})", step.query_name, key, version.version, version.source));
+                    }
+                }
+            }
+
+            plan_json = serde_json::json!(steps.iter().map(|step| serde_json::json!({
+                "query": step.query_name,
+                "partitions": step.partitions.iter().map(|k| k.to_string()).collect::<Vec<_>>()
+            })).collect::<Vec<_>>());
+        }
+
         if dry_run {
+            if steps.is_some() {
+                let data = serde_json::json!({ "plan": plan_json });
+                return ReplResult::success_with_both(plan_lines.join("\n"), data);
+            }
+
             let mut output_lines = Vec::new();
             let mut current = from_key.clone();
             while current <= to_key {
@@ -611,26 +767,75 @@ impl ReplSession {
         let _ = skip_invariants;
 
         let client = match self.ensure_client().await {
-            Ok(c) => c,
+            Ok(c) => c.clone(),
+            Err(e) => return ReplResult::failure(e.to_string()),
+        };
+
+        if estimate {
+            let runner = crate::Runner::new(client, queries);
+            let plan = if cascade {
+                runner.estimate_cascade(query_name, from_key, to_key, None, options).await
+            } else {
+                runner.estimate_backfill(query_name, from_key, to_key, None, options).await
+            };
+            return self.estimate_result(plan);
+        }
+
+        let runner = match self.tracked_runner(client, queries, tracking_dataset).await {
+            Ok(r) => r,
             Err(e) => return ReplResult::failure(e.to_string()),
         };
 
-        let runner = crate::Runner::new(client.clone(), queries);
+        if let Some(budget) = self.client_config.maximum_bytes_billed {
+            let plan = if cascade {
+                runner.estimate_cascade(query_name, from_key.clone(), to_key.clone(), None, options).await
+            } else {
+                runner.estimate_backfill(query_name, from_key.clone(), to_key.clone(), None, options).await
+            };
+            if let Err(e) = Self::enforce_budget(plan, budget) {
+                return ReplResult::failure(e);
+            }
+        }
 
-        match runner.backfill_partitions(query_name, from_key, to_key, None).await {
+        let result = if cascade {
+            runner.backfill_cascade(query_name, from_key, to_key, None, options).await
+        } else {
+            runner.backfill_partitions_with(query_name, from_key, to_key, None, options).await
+        };
+
+        match result {
             Ok(report) => {
-                let mut output_lines = Vec::new();
+                let mut output_lines = plan_lines;
+                if !output_lines.is_empty() {
+                    output_lines.push(String::new());
+                }
                 for stats in &report.stats {
-                    output_lines.push(format!("✓ {} v{} completed for {}", stats.query_name, stats.version, stats.partition_key));
+                    output_lines.push(Self::format_write_stats(stats));
                 }
                 for failure in &report.failures {
-                    output_lines.push(format!("✗ {}: {}", failure.partition_key, failure.error));
+                    if cascade {
+                        output_lines.push(format!("✗ {} ({}): {}", failure.query_name, failure.partition_key, failure.error));
+                    } else {
+                        output_lines.push(format!("✗ {}: {}", failure.partition_key, failure.error));
+                    }
+                }
+                for skip in &report.skipped {
+                    if cascade {
+                        output_lines.push(format!("○ {} ({}): skipped, {}", skip.query_name, skip.partition_key, skip.reason));
+                    } else {
+                        output_lines.push(format!("○ {}: skipped, {}", skip.partition_key, skip.reason));
+                    }
                 }
-                output_lines.push(format!("\n{} succeeded, {} failed", report.stats.len(), report.failures.len()));
+                output_lines.extend(Self::format_cancelled(&report));
+                output_lines.push(format!("\n{} succeeded, {} failed, {} skipped, {} cancelled", report.stats.len(), report.failures.len(), report.skipped.len(), report.cancelled.len()));
 
                 let data = serde_json::json!({
                     "succeeded": report.stats.len(),
-                    "failed": report.failures.len()
+                    "failed": report.failures.len(),
+                    "cancelled": report.cancelled.len(),
+                    "skipped": report.skipped.len(),
+                    "plan": plan_json,
+                    "partitions": report.stats.iter().map(Self::write_stats_json).collect::<Vec<_>>()
                 });
                 ReplResult::success_with_both(output_lines.join("\n"), data)
             }
@@ -675,7 +880,7 @@ impl ReplSession {
             Err(e) => return ReplResult::failure(e.to_string()),
         };
 
-        let checker = InvariantChecker::new(client, &query.destination, date_for_version);
+        let checker = InvariantChecker::new(client, &query.destination, &partition_key);
 
         let mut output_lines = Vec::new();
         let mut total_passed = 0;
@@ -750,7 +955,53 @@ impl ReplSession {
         }
     }
 
+    async fn tracked_runner(
+        &self,
+        client: BqClient,
+        queries: Vec<QueryDef>,
+        tracking_dataset: &str,
+    ) -> Result<crate::Runner> {
+        let yaml_contents = self.loader.load_yaml_contents(&self.queries_path)?;
+        let store: Arc<dyn StateStore> = match &self.state_file {
+            Some(path) => Arc::new(LocalStateStore::new(path)),
+            None => Arc::new(MigrationTracker::new(client.clone(), tracking_dataset)),
+        };
+        store.ensure().await?;
+
+        Ok(crate::Runner::new(client, queries).with_state_store(store, yaml_contents))
+    }
+
+    async fn state_store(&mut self, tracking_dataset: &str) -> Result<Arc<dyn StateStore>> {
+        if let Some(path) = &self.state_file {
+            return Ok(Arc::new(LocalStateStore::new(path)));
+        }
+        let client = self.ensure_client().await?.clone();
+        Ok(Arc::new(MigrationTracker::new(client, tracking_dataset)))
+    }
+
+    /// Latest stored states, limited to those overlapping `range` when its
+    /// bounds are times.
+    async fn load_stored_states(
+        &mut self,
+        tracking_dataset: &str,
+        range: Option<(&str, &str)>,
+    ) -> Result<Vec<crate::PartitionState>> {
+        let store = self.state_store(tracking_dataset).await?;
+        match range.and_then(|(from, to)| PartitionKey::date_bounds(from, to)) {
+            Some((from, to)) => store.get_latest_states_for_date_range(from, to).await,
+            None => store.get_latest_states().await,
+        }
+    }
+
     async fn cmd_init(&mut self, dataset: &str) -> ReplResult {
+        if let Some(path) = &self.state_file {
+            let store = LocalStateStore::new(path);
+            return match store.ensure().await {
+                Ok(_) => ReplResult::success_with_output(format!("✓ State file ready: {}", store.location())),
+                Err(e) => ReplResult::failure(e.to_string()),
+            };
+        }
+
         let client = match self.ensure_client().await {
             Ok(c) => c,
             Err(e) => return ReplResult::failure(e.to_string()),
@@ -760,7 +1011,7 @@ impl ReplSession {
 
         match tracker.ensure_tracking_table().await {
             Ok(_) => {
-                let output = format!("✓ Tracking table created: {}._bqdrift_query_runs", dataset);
+                let output = format!("✓ Tracking tables created: {}._bqdrift_query_runs, {}._bqdrift_state", dataset, dataset);
                 ReplResult::success_with_output(output)
             }
             Err(e) => ReplResult::failure(e.to_string()),
@@ -772,8 +1023,10 @@ impl ReplSession {
         from: Option<String>,
         to: Option<String>,
         dry_run: bool,
-        _tracking_dataset: &str,
-        _allow_source_mutation: bool,
+        skip_invariants: bool,
+        estimate: bool,
+        tracking_dataset: &str,
+        allow_source_mutation: bool,
     ) -> ReplResult {
         let queries = match self.ensure_queries() {
             Ok(q) => q.clone(),
@@ -786,24 +1039,42 @@ impl ReplSession {
         };
 
         let today = Utc::now().date_naive();
-        let from_date = match from {
-            Some(s) => match NaiveDate::parse_from_str(&s, "%Y-%m-%d") {
-                Ok(d) => d,
-                Err(_) => return ReplResult::failure(format!("Invalid from date: {}", s)),
-            },
-            None => today - chrono::Duration::days(30),
-        };
-        let to_date = match to {
-            Some(s) => match NaiveDate::parse_from_str(&s, "%Y-%m-%d") {
-                Ok(d) => d,
-                Err(_) => return ReplResult::failure(format!("Invalid to date: {}", s)),
-            },
-            None => today,
+        let from = from.unwrap_or_else(|| (today - chrono::Duration::days(30)).to_string());
+        let to = to.unwrap_or_else(|| today.to_string());
+
+        let stored_states = if self.project.is_some() || self.state_file.is_some() {
+            match self.load_stored_states(tracking_dataset, Some((from.as_str(), to.as_str()))).await {
+                Ok(states) => states,
+                Err(e) => return ReplResult::failure(e.to_string()),
+            }
+        } else {
+            vec![]
         };
 
-        let stored_states = vec![];
-        let detector = crate::DriftDetector::new(queries, yaml_contents);
-        let report = match detector.detect(&stored_states, from_date, to_date) {
+        if !allow_source_mutation && !stored_states.is_empty() {
+            let immutability_report = crate::ImmutabilityChecker::new(&queries).check(&stored_states);
+
+            if !immutability_report.is_clean() {
+                let violations: Vec<_> = immutability_report.violations.iter()
+                    .map(|v| serde_json::json!({
+                        "query": v.query_name,
+                        "version": v.version,
+                        "revision": v.revision,
+                        "source": v.source,
+                        "affected_partitions": v.affected_partitions.len(),
+                    }))
+                    .collect();
+                return ReplResult {
+                    success: false,
+                    output: None,
+                    data: Some(serde_json::json!({ "violations": violations })),
+                    error: Some("Source immutability violated. Use --allow-source-mutation to override.".to_string()),
+                };
+            }
+        }
+
+        let detector = crate::DriftDetector::new(queries.clone(), yaml_contents);
+        let report = match detector.detect_range(&stored_states, &from, &to) {
             Ok(r) => r,
             Err(e) => return ReplResult::failure(e.to_string()),
         };
@@ -825,23 +1096,68 @@ impl ReplSession {
 
         if dry_run {
             output_lines.push(format!("\nRun without --dry-run to execute {} drifted partitions", drifted.len()));
-        } else {
-            output_lines.push("\nSync execution not yet implemented.".to_string());
+
+            let data = serde_json::json!({
+                "drifted_count": drifted.len(),
+                "dry_run": dry_run
+            });
+            return ReplResult::success_with_both(output_lines.join("\n"), data);
         }
 
-        let data = serde_json::json!({
-            "drifted_count": drifted.len(),
-            "dry_run": dry_run
-        });
-        ReplResult::success_with_both(output_lines.join("\n"), data)
+        let client = match self.ensure_client().await {
+            Ok(c) => c.clone(),
+            Err(e) => return ReplResult::failure(e.to_string()),
+        };
+
+        if estimate {
+            let runner = crate::Runner::new(client, queries);
+            return self.estimate_result(runner.estimate_sync(&drifted).await);
+        }
+
+        let runner = match self.tracked_runner(client, queries, tracking_dataset).await {
+            Ok(r) => r,
+            Err(e) => return ReplResult::failure(e.to_string()),
+        };
+
+        if let Some(budget) = self.client_config.maximum_bytes_billed {
+            if let Err(e) = Self::enforce_budget(runner.estimate_sync(&drifted).await, budget) {
+                return ReplResult::failure(e);
+            }
+        }
+
+        match runner.sync_partitions(&drifted, skip_invariants).await {
+            Ok(sync_report) => {
+                output_lines.push(String::new());
+                for stats in &sync_report.stats {
+                    output_lines.push(Self::format_write_stats(stats));
+                }
+                for failure in &sync_report.failures {
+                    output_lines.push(format!("✗ {} ({}): {}", failure.query_name, failure.partition_key, failure.error));
+                }
+                output_lines.extend(Self::format_cancelled(&sync_report));
+                output_lines.push(format!("\n{} succeeded, {} failed, {} cancelled", sync_report.stats.len(), sync_report.failures.len(), sync_report.cancelled.len()));
+
+                let data = serde_json::json!({
+                    "drifted_count": drifted.len(),
+                    "dry_run": dry_run,
+                    "succeeded": sync_report.stats.len(),
+                    "failed": sync_report.failures.len(),
+                    "cancelled": sync_report.cancelled.len(),
+                    "partitions": sync_report.stats.iter().map(Self::write_stats_json).collect::<Vec<_>>()
+                });
+                ReplResult::success_with_both(output_lines.join("\n"), data)
+            }
+            Err(e) => ReplResult::failure(e.to_string()),
+        }
     }
 
-    fn cmd_audit(
+    async fn cmd_audit(
         &mut self,
         query_filter: Option<String>,
         modified_only: bool,
         _show_diff: bool,
         output: &str,
+        tracking_dataset: &str,
     ) -> ReplResult {
         let queries = match self.ensure_queries() {
             Ok(q) => q.clone(),
@@ -860,7 +1176,11 @@ impl ReplSession {
             return ReplResult::success_with_output("No queries found".to_string());
         }
 
-        let stored_states = vec![];
+        let stored_states = match self.load_stored_states(tracking_dataset, None).await {
+            Ok(states) => states,
+            Err(e) => return ReplResult::failure(e.to_string()),
+        };
+
         let auditor = crate::SourceAuditor::new(&queries_to_audit);
         let report = auditor.audit(&stored_states);
 
@@ -903,7 +1223,7 @@ impl ReplSession {
     async fn cmd_scratch_list(&mut self, project: &str) -> ReplResult {
         use crate::executor::{ScratchConfig, ScratchWriter};
 
-        let client = match BqClient::new(project).await {
+        let client = match BqClient::connect(project, self.client_config.clone()).await {
             Ok(c) => c,
             Err(e) => return ReplResult::failure(format!("Failed to connect: {}", e)),
         };
@@ -954,12 +1274,12 @@ impl ReplSession {
             None => return ReplResult::failure("Production project not set".to_string()),
         };
 
-        let scratch_client = match BqClient::new(scratch_project).await {
+        let scratch_client = match BqClient::connect(scratch_project, self.client_config.clone()).await {
             Ok(c) => c,
             Err(e) => return ReplResult::failure(format!("Failed to connect to scratch: {}", e)),
         };
 
-        let production_client = match BqClient::new(&production_project).await {
+        let production_client = match BqClient::connect(&production_project, self.client_config.clone()).await {
             Ok(c) => c,
             Err(e) => return ReplResult::failure(format!("Failed to connect to production: {}", e)),
         };
@@ -985,6 +1305,99 @@ impl ReplSession {
         }
     }
 
+    fn query_partition(queries: &[QueryDef], name: &str, partition: &Option<String>) -> std::result::Result<PartitionKey, String> {
+        let query = queries.iter()
+            .find(|q| q.name == name)
+            .ok_or_else(|| format!("Query '{}' not found", name))?;
+        Self::parse_partition(partition, &query.destination.partition.partition_type)
+    }
+
+    fn format_cancelled(report: &RunReport) -> Vec<String> {
+        report.cancelled
+            .iter()
+            .map(|c| format!("⊘ {} ({}): {}", c.query_name, c.partition_key, c.error))
+            .collect()
+    }
+
+    fn estimate_result(&self, plan: Result<CostEstimate>) -> ReplResult {
+        let plan = match plan {
+            Ok(p) => p,
+            Err(e) => return ReplResult::failure(e.to_string()),
+        };
+        let budget = self.client_config.maximum_bytes_billed;
+
+        let mut output_lines = Vec::new();
+        for partition in &plan.partitions {
+            output_lines.push(format!("  {} {}: {}", partition.query_name, partition.partition_key, format_bytes(partition.bytes_processed)));
+        }
+        for failure in &plan.failures {
+            output_lines.push(format!("✗ {} ({}): {}", failure.query_name, failure.partition_key, failure.error));
+        }
+        output_lines.push(format!("\nEstimated {} processed across {} partitions", format_bytes(plan.total_bytes()), plan.partitions.len()));
+        if let Some(budget) = budget {
+            output_lines.push(format!("Budget: {}", format_bytes(budget)));
+        }
+
+        let data = serde_json::json!({
+            "total_bytes": plan.total_bytes(),
+            "budget": budget,
+            "queries": plan.query_totals().into_iter().map(|(query, bytes)| serde_json::json!({
+                "query": query,
+                "bytes_processed": bytes
+            })).collect::<Vec<_>>(),
+            "partitions": plan.partitions.iter().map(|p| serde_json::json!({
+                "query": p.query_name,
+                "partition": p.partition_key.to_string(),
+                "bytes_processed": p.bytes_processed
+            })).collect::<Vec<_>>(),
+            "failed": plan.failures.len()
+        });
+
+        match plan.check_budget(budget) {
+            Ok(()) => ReplResult::success_with_both(output_lines.join("\n"), data),
+            Err(e) => ReplResult {
+                success: false,
+                output: Some(output_lines.join("\n")),
+                data: Some(data),
+                error: Some(e.to_string()),
+            },
+        }
+    }
+
+    /// Refuse to start writing when the plan's dry-run estimate is over budget.
+    fn enforce_budget(plan: Result<CostEstimate>, budget: i64) -> std::result::Result<(), String> {
+        plan.and_then(|p| p.check_budget(Some(budget)))
+            .map_err(|e| e.to_string())
+    }
+
+    fn format_write_stats(stats: &PartitionWriteStats) -> String {
+        let mut line = format!("✓ {} v{} completed for {}", stats.query_name, stats.version, stats.partition_key);
+        let summary = stats.job_summary();
+        if !summary.is_empty() {
+            line.push_str(&format!("\n  {}", summary));
+        }
+        if let Some(error) = &stats.tracking_error {
+            line.push_str(&format!("\n  ⚠ state not recorded: {}", error));
+        }
+        line
+    }
+
+    fn write_stats_json(stats: &PartitionWriteStats) -> serde_json::Value {
+        serde_json::json!({
+            "query": stats.query_name,
+            "version": stats.version,
+            "partition": stats.partition_key.to_string(),
+            "rows_written": stats.rows_written,
+            "bytes_processed": stats.bytes_processed,
+            "bytes_billed": stats.bytes_billed,
+            "slot_ms": stats.slot_ms,
+            "job_id": stats.job_id,
+            "elapsed_ms": stats.elapsed_ms,
+            "retries": stats.retries,
+            "tracking_error": stats.tracking_error
+        })
+    }
+
     fn parse_partition(partition: &Option<String>, partition_type: &PartitionType) -> std::result::Result<PartitionKey, String> {
         match partition {
             Some(p) => PartitionKey::parse(p, partition_type)
@@ -0,0 +1,291 @@
+use std::fmt;
+use super::field::{BqType, Field, FieldMode};
+use super::table::Schema;
+
+/// An additive change BigQuery can apply to a table in place with DDL.
+#[derive(Debug, Clone, PartialEq)]
+pub enum SchemaChange {
+    /// A new NULLABLE or REPEATED column.
+    AddColumn(Field),
+    /// A REQUIRED column relaxed to NULLABLE.
+    DropNotNull(String),
+}
+
+impl SchemaChange {
+    /// The `ALTER TABLE` statement applying this change to `table_path`
+    /// (`project.dataset.table`).
+    pub fn to_ddl(&self, table_path: &str) -> String {
+        match self {
+            SchemaChange::AddColumn(field) => format!(
+                "ALTER TABLE `{}` ADD COLUMN IF NOT EXISTS {} {}",
+                table_path,
+                field.name,
+                column_type(field)
+            ),
+            SchemaChange::DropNotNull(name) => format!(
+                "ALTER TABLE `{}` ALTER COLUMN {} DROP NOT NULL",
+                table_path, name
+            ),
+        }
+    }
+}
+
+impl fmt::Display for SchemaChange {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            SchemaChange::AddColumn(field) => write!(f, "add column {} {}", field.name, column_type(field)),
+            SchemaChange::DropNotNull(name) => write!(f, "relax column {} to NULLABLE", name),
+        }
+    }
+}
+
+/// What it takes to bring an existing table's schema to a query's schema.
+/// `incompatible` lists the differences DDL can't apply without rewriting
+/// the table; when it is non-empty `changes` must not be applied.
+#[derive(Debug, Clone, Default)]
+pub struct SchemaEvolution {
+    pub changes: Vec<SchemaChange>,
+    pub incompatible: Vec<String>,
+}
+
+impl SchemaEvolution {
+    /// Compare the live `current` schema against the `target` schema.
+    pub fn plan(current: &Schema, target: &Schema) -> Self {
+        let mut evolution = Self::default();
+        evolution.compare(&current.fields, &target.fields, None);
+        evolution
+    }
+
+    pub fn is_compatible(&self) -> bool {
+        self.incompatible.is_empty()
+    }
+
+    /// Whether the table already matches the target schema.
+    pub fn is_up_to_date(&self) -> bool {
+        self.changes.is_empty() && self.incompatible.is_empty()
+    }
+
+    fn compare(&mut self, current: &[Field], target: &[Field], parent: Option<&str>) {
+        for field in target {
+            let path = field_path(parent, &field.name);
+
+            let Some(existing) = current.iter().find(|f| f.name == field.name) else {
+                if field.mode == FieldMode::Required {
+                    self.incompatible.push(format!("new field '{}' is REQUIRED; only NULLABLE or REPEATED fields can be added", path));
+                } else if parent.is_some() {
+                    self.incompatible.push(format!("new nested field '{}' must be added manually", path));
+                } else {
+                    self.changes.push(SchemaChange::AddColumn(field.clone()));
+                }
+                continue;
+            };
+
+            if existing.field_type != field.field_type {
+                self.incompatible.push(format!(
+                    "field '{}' type changed from {:?} to {:?}",
+                    path, existing.field_type, field.field_type
+                ));
+                continue;
+            }
+
+            match (&existing.mode, &field.mode) {
+                (a, b) if a == b => {}
+                (FieldMode::Required, FieldMode::Nullable) if parent.is_none() => {
+                    self.changes.push(SchemaChange::DropNotNull(field.name.clone()));
+                }
+                (FieldMode::Required, FieldMode::Nullable) => {
+                    self.incompatible.push(format!("nested field '{}' must be relaxed to NULLABLE manually", path));
+                }
+                (a, b) => {
+                    self.incompatible.push(format!("field '{}' mode changed from {:?} to {:?}", path, a, b));
+                }
+            }
+
+            if field.field_type == BqType::Record {
+                self.compare(
+                    existing.fields.as_deref().unwrap_or_default(),
+                    field.fields.as_deref().unwrap_or_default(),
+                    Some(&path),
+                );
+            }
+        }
+
+        for field in current {
+            if !target.iter().any(|f| f.name == field.name) {
+                self.incompatible.push(format!(
+                    "field '{}' exists in the table but not in the schema",
+                    field_path(parent, &field.name)
+                ));
+            }
+        }
+    }
+}
+
+fn field_path(parent: Option<&str>, name: &str) -> String {
+    match parent {
+        Some(parent) => format!("{}.{}", parent, name),
+        None => name.to_string(),
+    }
+}
+
+/// The GoogleSQL column type of `field`, e.g. `ARRAY<STRUCT<id INT64>>`.
+pub(crate) fn column_type(field: &Field) -> String {
+    let base = match field.field_type {
+        BqType::String => "STRING".to_string(),
+        BqType::Bytes => "BYTES".to_string(),
+        BqType::Int64 => "INT64".to_string(),
+        BqType::Float64 => "FLOAT64".to_string(),
+        BqType::Numeric => "NUMERIC".to_string(),
+        BqType::Bignumeric => "BIGNUMERIC".to_string(),
+        BqType::Bool => "BOOL".to_string(),
+        BqType::Date => "DATE".to_string(),
+        BqType::Datetime => "DATETIME".to_string(),
+        BqType::Time => "TIME".to_string(),
+        BqType::Timestamp => "TIMESTAMP".to_string(),
+        BqType::Geography => "GEOGRAPHY".to_string(),
+        BqType::Json => "JSON".to_string(),
+        BqType::Record => {
+            let nested: Vec<String> = field
+                .fields
+                .as_deref()
+                .unwrap_or_default()
+                .iter()
+                .map(|f| {
+                    let not_null = if f.mode == FieldMode::Required { " NOT NULL" } else { "" };
+                    format!("{} {}{}", f.name, column_type(f), not_null)
+                })
+                .collect();
+            format!("STRUCT<{}>", nested.join(", "))
+        }
+    };
+
+    match field.mode {
+        FieldMode::Repeated => format!("ARRAY<{}>", base),
+        _ => base,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn schema(fields: Vec<Field>) -> Schema {
+        Schema::from_fields(fields)
+    }
+
+    #[test]
+    fn test_identical_schema_is_up_to_date() {
+        let s = schema(vec![
+            Field::new("date", BqType::Date).required(),
+            Field::new("count", BqType::Int64),
+        ]);
+
+        assert!(SchemaEvolution::plan(&s, &s).is_up_to_date());
+    }
+
+    #[test]
+    fn test_added_nullable_field() {
+        let current = schema(vec![Field::new("date", BqType::Date)]);
+        let target = schema(vec![
+            Field::new("date", BqType::Date),
+            Field::new("tags", BqType::String).repeated(),
+            Field::new("region", BqType::String),
+        ]);
+
+        let evolution = SchemaEvolution::plan(&current, &target);
+
+        assert!(evolution.is_compatible());
+        assert_eq!(evolution.changes.len(), 2);
+        assert_eq!(
+            evolution.changes[0].to_ddl("p.d.t"),
+            "ALTER TABLE `p.d.t` ADD COLUMN IF NOT EXISTS tags ARRAY<STRING>"
+        );
+        assert_eq!(
+            evolution.changes[1].to_ddl("p.d.t"),
+            "ALTER TABLE `p.d.t` ADD COLUMN IF NOT EXISTS region STRING"
+        );
+    }
+
+    #[test]
+    fn test_relaxed_required_field() {
+        let current = schema(vec![Field::new("id", BqType::Int64).required()]);
+        let target = schema(vec![Field::new("id", BqType::Int64)]);
+
+        let evolution = SchemaEvolution::plan(&current, &target);
+
+        assert!(evolution.is_compatible());
+        assert_eq!(evolution.changes, vec![SchemaChange::DropNotNull("id".to_string())]);
+        assert_eq!(
+            evolution.changes[0].to_ddl("p.d.t"),
+            "ALTER TABLE `p.d.t` ALTER COLUMN id DROP NOT NULL"
+        );
+    }
+
+    #[test]
+    fn test_add_struct_column_ddl() {
+        let field = Field::new("geo", BqType::Record).with_fields(vec![
+            Field::new("lat", BqType::Float64).required(),
+            Field::new("lng", BqType::Float64),
+        ]);
+
+        assert_eq!(
+            SchemaChange::AddColumn(field).to_ddl("p.d.t"),
+            "ALTER TABLE `p.d.t` ADD COLUMN IF NOT EXISTS geo STRUCT<lat FLOAT64 NOT NULL, lng FLOAT64>"
+        );
+    }
+
+    #[test]
+    fn test_create_table_ddl() {
+        let target = schema(vec![
+            Field::new("date", BqType::Date).required(),
+            Field::new("tags", BqType::String).repeated(),
+        ]);
+
+        assert_eq!(
+            target.to_create_ddl("d.t"),
+            "CREATE TABLE IF NOT EXISTS `d.t` (date DATE NOT NULL, tags ARRAY<STRING>)"
+        );
+    }
+
+    #[test]
+    fn test_incompatible_changes() {
+        let current = schema(vec![
+            Field::new("id", BqType::Int64),
+            Field::new("name", BqType::String),
+            Field::new("legacy", BqType::String),
+        ]);
+        let target = schema(vec![
+            Field::new("id", BqType::String),
+            Field::new("name", BqType::String).required(),
+            Field::new("new_required", BqType::Int64).required(),
+        ]);
+
+        let evolution = SchemaEvolution::plan(&current, &target);
+
+        assert!(!evolution.is_compatible());
+        assert!(evolution.changes.is_empty());
+        assert_eq!(evolution.incompatible.len(), 4);
+        assert!(evolution.incompatible[0].contains("'id' type changed from Int64 to String"));
+        assert!(evolution.incompatible[1].contains("'name' mode changed from Nullable to Required"));
+        assert!(evolution.incompatible[2].contains("'new_required' is REQUIRED"));
+        assert!(evolution.incompatible[3].contains("'legacy' exists in the table"));
+    }
+
+    #[test]
+    fn test_nested_additions_are_incompatible() {
+        let current = schema(vec![
+            Field::new("geo", BqType::Record).with_fields(vec![Field::new("lat", BqType::Float64)]),
+        ]);
+        let target = schema(vec![
+            Field::new("geo", BqType::Record).with_fields(vec![
+                Field::new("lat", BqType::Float64),
+                Field::new("lng", BqType::Float64),
+            ]),
+        ]);
+
+        let evolution = SchemaEvolution::plan(&current, &target);
+
+        assert!(!evolution.is_compatible());
+        assert!(evolution.incompatible[0].contains("'geo.lng'"));
+    }
+}
@@ -2,8 +2,11 @@ mod field;
 mod table;
 mod partition;
 mod cluster;
+mod evolution;
 
 pub use field::{BqType, Field, FieldMode};
-pub use table::Schema;
+pub use table::{Schema, TableDefinition};
 pub use partition::{PartitionConfig, PartitionType, PartitionKey};
 pub use cluster::ClusterConfig;
+pub use evolution::{SchemaChange, SchemaEvolution};
+pub(crate) use evolution::column_type;
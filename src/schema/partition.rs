@@ -1,5 +1,5 @@
 use serde::{Deserialize, Serialize};
-use chrono::{NaiveDate, NaiveDateTime};
+use chrono::{Datelike, NaiveDate, NaiveDateTime, Timelike};
 use std::fmt;
 
 #[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Default)]
@@ -14,8 +14,12 @@ pub enum PartitionType {
     IngestionTime,
 }
 
+/// Serialized externally tagged, e.g. `{"Day":"2024-06-15"}`, so YEAR and
+/// RANGE keys stay apart. Keys written untagged by earlier releases, e.g.
+/// `"2024-06-15"` or `2024`, still deserialize; an untagged number is read
+/// as a year, as it was then.
 #[derive(Debug, Clone, PartialEq, Eq, Hash, Serialize, Deserialize)]
-#[serde(untagged)]
+#[serde(from = "PartitionKeyRepr")]
 pub enum PartitionKey {
     Hour(NaiveDateTime),
     Day(NaiveDate),
@@ -24,6 +28,43 @@ pub enum PartitionKey {
     Range(i64),
 }
 
+#[derive(Deserialize)]
+#[serde(untagged)]
+enum PartitionKeyRepr {
+    Tagged(TaggedPartitionKey),
+    Hour(NaiveDateTime),
+    Day(NaiveDate),
+    Month { year: i32, month: u32 },
+    Number(i64),
+}
+
+#[derive(Deserialize)]
+enum TaggedPartitionKey {
+    Hour(NaiveDateTime),
+    Day(NaiveDate),
+    Month { year: i32, month: u32 },
+    Year(i32),
+    Range(i64),
+}
+
+impl From<PartitionKeyRepr> for PartitionKey {
+    fn from(repr: PartitionKeyRepr) -> Self {
+        match repr {
+            PartitionKeyRepr::Tagged(TaggedPartitionKey::Hour(dt)) | PartitionKeyRepr::Hour(dt) => PartitionKey::Hour(dt),
+            PartitionKeyRepr::Tagged(TaggedPartitionKey::Day(d)) | PartitionKeyRepr::Day(d) => PartitionKey::Day(d),
+            PartitionKeyRepr::Tagged(TaggedPartitionKey::Month { year, month }) | PartitionKeyRepr::Month { year, month } => {
+                PartitionKey::Month { year, month }
+            }
+            PartitionKeyRepr::Tagged(TaggedPartitionKey::Year(y)) => PartitionKey::Year(y),
+            PartitionKeyRepr::Tagged(TaggedPartitionKey::Range(n)) => PartitionKey::Range(n),
+            PartitionKeyRepr::Number(n) => match i32::try_from(n) {
+                Ok(y) => PartitionKey::Year(y),
+                Err(_) => PartitionKey::Range(n),
+            },
+        }
+    }
+}
+
 impl PartitionKey {
     pub fn parse(s: &str, partition_type: &PartitionType) -> Result<Self, String> {
         match partition_type {
@@ -70,6 +111,64 @@ impl PartitionKey {
         }
     }
 
+    /// Parses a range bound written in any partition format (`YYYY-MM-DDTHH`,
+    /// `YYYY-MM-DD`, `YYYY-MM`, `YYYY`, or an integer for RANGE) and converts it
+    /// to `partition_type`. A bound coarser than the target type is widened to
+    /// the first partition it covers, or the last one when `end` is set.
+    pub fn parse_bound(s: &str, partition_type: &PartitionType, end: bool) -> Result<Self, String> {
+        if *partition_type == PartitionType::Range {
+            return Self::parse(s, partition_type);
+        }
+
+        let (first, last) = Self::bound_span(s)?;
+        let at = if end { last } else { first };
+
+        Ok(match partition_type {
+            PartitionType::Hour => PartitionKey::Hour(at),
+            PartitionType::Day | PartitionType::IngestionTime => PartitionKey::Day(at.date()),
+            PartitionType::Month => PartitionKey::Month { year: at.year(), month: at.month() },
+            PartitionType::Year | PartitionType::Range => PartitionKey::Year(at.year()),
+        })
+    }
+
+    /// The days spanned by bounds `from..=to` given in any time-based
+    /// format, e.g. a sync's `--from`/`--to`. None when either is not a time.
+    pub fn date_bounds(from: &str, to: &str) -> Option<(NaiveDate, NaiveDate)> {
+        let (first, _) = Self::bound_span(from).ok()?;
+        let (_, last) = Self::bound_span(to).ok()?;
+        Some((first.date(), last.date()))
+    }
+
+    /// First and last hour covered by a partition string of any time-based format.
+    fn bound_span(s: &str) -> Result<(NaiveDateTime, NaiveDateTime), String> {
+        let span = |first: NaiveDate, last: NaiveDate| {
+            (first.and_hms_opt(0, 0, 0).unwrap(), last.and_hms_opt(23, 0, 0).unwrap())
+        };
+
+        if let Ok(PartitionKey::Hour(dt)) = Self::parse(s, &PartitionType::Hour) {
+            let hour = dt.date().and_hms_opt(dt.hour(), 0, 0).unwrap();
+            return Ok((hour, hour));
+        }
+        if let Ok(PartitionKey::Day(d)) = Self::parse(s, &PartitionType::Day) {
+            return Ok(span(d, d));
+        }
+        if let Ok(key @ PartitionKey::Month { .. }) = Self::parse(s, &PartitionType::Month) {
+            let first = key.to_naive_date();
+            let last = key.next().to_naive_date().pred_opt().unwrap_or(first);
+            return Ok(span(first, last));
+        }
+        if let Ok(PartitionKey::Year(y)) = Self::parse(s, &PartitionType::Year) {
+            if let (Some(first), Some(last)) = (NaiveDate::from_ymd_opt(y, 1, 1), NaiveDate::from_ymd_opt(y, 12, 31)) {
+                return Ok(span(first, last));
+            }
+        }
+
+        Err(format!(
+            "Invalid partition bound: '{}'. Expected YYYY-MM-DDTHH, YYYY-MM-DD, YYYY-MM or YYYY",
+            s
+        ))
+    }
+
     pub fn decorator(&self) -> String {
         match self {
             PartitionKey::Hour(dt) => format!("${}", dt.format("%Y%m%d%H")),
@@ -141,6 +240,41 @@ impl PartitionKey {
         }
     }
 
+    /// The hours a time-based partition covers, end exclusive. RANGE keys
+    /// cover no time.
+    pub fn time_span(&self) -> Option<(NaiveDateTime, NaiveDateTime)> {
+        let midnight = |d: NaiveDate| d.and_hms_opt(0, 0, 0).unwrap();
+        match self {
+            PartitionKey::Hour(dt) => Some((*dt, *dt + chrono::Duration::hours(1))),
+            PartitionKey::Range(_) => None,
+            other => Some((midnight(other.to_naive_date()), midnight(other.next().to_naive_date()))),
+        }
+    }
+
+    /// Whether the two partitions can hold rows for the same time, e.g. an
+    /// hour and the day containing it. RANGE keys overlap only the same
+    /// bucket; a RANGE key and a time-based key have nothing to compare, so
+    /// they are taken to overlap.
+    pub fn overlaps(&self, other: &PartitionKey) -> bool {
+        match (self.time_span(), other.time_span()) {
+            (Some((start, end)), Some((other_start, other_end))) => start < other_end && other_start < end,
+            (None, None) => self == other,
+            _ => true,
+        }
+    }
+
+    /// Whether the partition holds rows for any of the days `from..=to`.
+    /// RANGE keys carry no time, so they are always taken to.
+    pub fn overlaps_dates(&self, from: NaiveDate, to: NaiveDate) -> bool {
+        match self.time_span() {
+            Some((start, end)) => {
+                let midnight = |d: NaiveDate| d.and_hms_opt(0, 0, 0).unwrap();
+                start < midnight(to.succ_opt().unwrap_or(to)) && midnight(from) < end
+            }
+            None => true,
+        }
+    }
+
     pub fn partition_type(&self) -> PartitionType {
         match self {
             PartitionKey::Hour(_) => PartitionType::Hour,
@@ -446,4 +580,98 @@ mod tests {
         let year = PartitionKey::Year(2024);
         assert_eq!(year.to_naive_date(), NaiveDate::from_ymd_opt(2024, 1, 1).unwrap());
     }
+
+    #[test]
+    fn test_parse_bound_same_granularity() {
+        let key = PartitionKey::parse_bound("2024-03-15", &PartitionType::Day, false).unwrap();
+        assert_eq!(key, PartitionKey::Day(NaiveDate::from_ymd_opt(2024, 3, 15).unwrap()));
+    }
+
+    #[test]
+    fn test_parse_bound_widens_to_finer_type() {
+        let from = PartitionKey::parse_bound("2024-02", &PartitionType::Day, false).unwrap();
+        let to = PartitionKey::parse_bound("2024-02", &PartitionType::Day, true).unwrap();
+        assert_eq!(from, PartitionKey::Day(NaiveDate::from_ymd_opt(2024, 2, 1).unwrap()));
+        assert_eq!(to, PartitionKey::Day(NaiveDate::from_ymd_opt(2024, 2, 29).unwrap()));
+
+        let to = PartitionKey::parse_bound("2024-06-15", &PartitionType::Hour, true).unwrap();
+        assert_eq!(to.to_string(), "2024-06-15T23");
+    }
+
+    #[test]
+    fn test_parse_bound_truncates_to_coarser_type() {
+        let key = PartitionKey::parse_bound("2024-06-15T10", &PartitionType::Month, false).unwrap();
+        assert_eq!(key, PartitionKey::Month { year: 2024, month: 6 });
+
+        let key = PartitionKey::parse_bound("2024-06-15", &PartitionType::Year, true).unwrap();
+        assert_eq!(key, PartitionKey::Year(2024));
+    }
+
+    #[test]
+    fn test_parse_bound_range() {
+        let key = PartitionKey::parse_bound("500", &PartitionType::Range, false).unwrap();
+        assert_eq!(key, PartitionKey::Range(500));
+        assert!(PartitionKey::parse_bound("2024-06-15", &PartitionType::Range, false).is_err());
+    }
+
+    #[test]
+    fn test_parse_bound_invalid() {
+        assert!(PartitionKey::parse_bound("yesterday", &PartitionType::Day, false).is_err());
+    }
+
+    #[test]
+    fn test_overlaps() {
+        let hour = |s: &str| PartitionKey::parse(s, &PartitionType::Hour).unwrap();
+        let day = PartitionKey::parse("2024-06-15", &PartitionType::Day).unwrap();
+
+        assert!(hour("2024-06-15T10").overlaps(&hour("2024-06-15T10")));
+        assert!(!hour("2024-06-15T10").overlaps(&hour("2024-06-15T11")));
+        assert!(hour("2024-06-15T23").overlaps(&day));
+        assert!(!hour("2024-06-16T00").overlaps(&day));
+        assert!(day.overlaps(&PartitionKey::Month { year: 2024, month: 6 }));
+        assert!(!PartitionKey::Range(10).overlaps(&PartitionKey::Range(20)));
+        assert!(PartitionKey::Range(10).overlaps(&day));
+    }
+
+    #[test]
+    fn test_overlaps_dates() {
+        let date = |s: &str| NaiveDate::parse_from_str(s, "%Y-%m-%d").unwrap();
+        let (from, to) = (date("2024-06-10"), date("2024-06-15"));
+
+        assert!(PartitionKey::Day(date("2024-06-15")).overlaps_dates(from, to));
+        assert!(!PartitionKey::Day(date("2024-06-16")).overlaps_dates(from, to));
+        assert!(PartitionKey::parse("2024-06-10T00", &PartitionType::Hour).unwrap().overlaps_dates(from, to));
+        assert!(PartitionKey::Month { year: 2024, month: 6 }.overlaps_dates(from, to));
+        assert!(!PartitionKey::Month { year: 2024, month: 5 }.overlaps_dates(from, to));
+        assert!(PartitionKey::Year(2024).overlaps_dates(from, to));
+        assert!(PartitionKey::Range(10).overlaps_dates(from, to));
+    }
+
+    #[test]
+    fn test_date_bounds() {
+        let date = |s: &str| NaiveDate::parse_from_str(s, "%Y-%m-%d").unwrap();
+
+        assert_eq!(PartitionKey::date_bounds("2024-06-10", "2024-06-15"), Some((date("2024-06-10"), date("2024-06-15"))));
+        assert_eq!(PartitionKey::date_bounds("2024-02", "2024-06-15T10"), Some((date("2024-02-01"), date("2024-06-15"))));
+        assert_eq!(PartitionKey::date_bounds("2024-06-10", "soon"), None);
+    }
+
+    #[test]
+    fn test_partition_key_serde_round_trip() {
+        for key in [PartitionKey::Year(2024), PartitionKey::Range(2024)] {
+            let json = serde_json::to_string(&key).unwrap();
+            assert_eq!(serde_json::from_str::<PartitionKey>(&json).unwrap(), key);
+        }
+    }
+
+    #[test]
+    fn test_partition_key_reads_untagged_keys() {
+        let read = |json: &str| serde_json::from_str::<PartitionKey>(json).unwrap();
+
+        assert_eq!(read(r#""2024-06-15T10:00:00""#), PartitionKey::parse("2024-06-15T10", &PartitionType::Hour).unwrap());
+        assert_eq!(read(r#""2024-06-15""#), PartitionKey::Day(NaiveDate::from_ymd_opt(2024, 6, 15).unwrap()));
+        assert_eq!(read(r#"{"year":2024,"month":6}"#), PartitionKey::Month { year: 2024, month: 6 });
+        assert_eq!(read("2024"), PartitionKey::Year(2024));
+        assert_eq!(read(r#"{"Range":2024}"#), PartitionKey::Range(2024));
+    }
 }
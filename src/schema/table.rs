@@ -1,5 +1,7 @@
 use serde::{Deserialize, Serialize};
-use super::field::Field;
+use super::evolution::column_type;
+use super::field::{Field, FieldMode};
+use super::partition::PartitionConfig;
 
 #[derive(Debug, Clone, Serialize, Deserialize, Default)]
 pub struct Schema {
@@ -38,4 +40,26 @@ impl Schema {
     pub fn has_field(&self, name: &str) -> bool {
         self.fields.iter().any(|f| f.name == name)
     }
+
+    /// `CREATE TABLE IF NOT EXISTS` statement giving `table_path` these
+    /// columns, without partitioning or clustering.
+    pub fn to_create_ddl(&self, table_path: &str) -> String {
+        let columns: Vec<String> = self.fields
+            .iter()
+            .map(|f| {
+                let not_null = if f.mode == FieldMode::Required { " NOT NULL" } else { "" };
+                format!("{} {}{}", f.name, column_type(f), not_null)
+            })
+            .collect();
+
+        format!("CREATE TABLE IF NOT EXISTS `{}` ({})", table_path, columns.join(", "))
+    }
+}
+
+/// A table's schema, partitioning and clustering as BigQuery reports them.
+#[derive(Debug, Clone, Default)]
+pub struct TableDefinition {
+    pub schema: Schema,
+    pub partition: Option<PartitionConfig>,
+    pub cluster_fields: Vec<String>,
 }
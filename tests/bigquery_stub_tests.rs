@@ -0,0 +1,570 @@
+mod support;
+
+use bqdrift::dsl::QueryLoader;
+use bqdrift::error::BigQueryError;
+use bqdrift::executor::{ClientConfig, QueryParams, RetryPolicy, RunReport, ScratchConfig, ScratchWriter, SkipReason};
+use bqdrift::invariant::resolve_invariants_def;
+use bqdrift::migration::RunStatus;
+use bqdrift::schema::PartitionKey;
+use bqdrift::{
+    BackfillOptions, BqDriftError, CancelSignal, CheckStatus, ExecutionStatus, InvariantChecker, JobConfig,
+    MigrationTracker, PartitionWriter, QueryDef, Runner, WriteStrategy,
+};
+use chrono::NaiveDate;
+use serde_json::json;
+use std::collections::HashMap;
+use std::path::Path;
+use std::time::Duration;
+use support::{BigQueryStub, BYTES_BILLED, BYTES_PROCESSED};
+
+const PROJECT: &str = "stub-project";
+
+fn load_query(file: &str) -> QueryDef {
+    QueryLoader::new()
+        .load_query(Path::new("tests/fixtures/analytics").join(file))
+        .unwrap()
+}
+
+fn day(d: u32) -> PartitionKey {
+    PartitionKey::Day(NaiveDate::from_ymd_opt(2024, 3, d).unwrap())
+}
+
+fn partition_date_param(config: &serde_json::Value) -> Option<String> {
+    config["queryParameters"]
+        .as_array()?
+        .iter()
+        .find(|p| p["name"] == "partition_date")
+        .and_then(|p| p["parameterValue"]["value"].as_str())
+        .map(str::to_string)
+}
+
+#[tokio::test]
+async fn test_partition_writer_creates_destination_and_merges() {
+    let stub = BigQueryStub::start().await;
+    stub.reply_affected_rows("MERGE", 42);
+
+    let query = load_query("simple_query.yaml");
+    let writer = PartitionWriter::new(stub.client(PROJECT).await);
+    let stats = writer.write_partition(&query, day(15)).await.unwrap();
+
+    assert!(stub.has_dataset(PROJECT, "test_dataset"));
+    let table = stub.table(PROJECT, "test_dataset", "simple_table").unwrap();
+    assert_eq!(table["timePartitioning"]["field"], "date");
+    assert_eq!(table["clustering"]["fields"], json!(["region"]));
+
+    assert_eq!(stats.write_strategy, WriteStrategy::Merge);
+    assert_eq!(stats.rows_written, Some(42));
+    assert_eq!(stats.bytes_processed, Some(BYTES_PROCESSED));
+    assert_eq!(stats.bytes_billed, Some(BYTES_BILLED));
+    assert!(stats.job_id.is_some());
+
+    let merge = stub.find_query("MERGE").unwrap();
+    assert!(merge.sql.contains("`stub-project.test_dataset.simple_table`"));
+    assert_eq!(partition_date_param(&merge.config).as_deref(), Some("2024-03-15"));
+    assert_eq!(merge.config["labels"]["bqdrift_query"], "simple_query");
+    assert_eq!(merge.config["labels"]["bqdrift_partition"], "2024-03-15");
+}
+
+#[tokio::test]
+async fn test_partition_writer_adds_new_columns_to_existing_table() {
+    let stub = BigQueryStub::start().await;
+    stub.put_table(PROJECT, "test_dataset", "simple_table", json!([
+        {"name": "date", "type": "DATE", "mode": "NULLABLE"},
+        {"name": "region", "type": "STRING", "mode": "NULLABLE"},
+    ]));
+
+    let query = load_query("simple_query.yaml");
+    let writer = PartitionWriter::new(stub.client(PROJECT).await);
+    writer.write_partition_skip_invariants(&query, day(1)).await.unwrap();
+
+    let statements = stub.statements();
+    assert!(statements[0].contains("ALTER TABLE `stub-project.test_dataset.simple_table`"));
+    assert!(statements[0].contains("count"));
+    assert!(statements[1].contains("MERGE"));
+}
+
+#[tokio::test]
+async fn test_partition_writer_checks_destination_against_written_version() {
+    let stub = BigQueryStub::start().await;
+    stub.put_table(PROJECT, "test_dataset", "versioned_table", json!([
+        {"name": "date", "type": "DATE", "mode": "NULLABLE"},
+        {"name": "user_id", "type": "STRING", "mode": "NULLABLE"},
+        {"name": "events", "type": "INTEGER", "mode": "NULLABLE"},
+    ]));
+
+    let query = load_query("versioned_query.yaml");
+    let writer = PartitionWriter::new(stub.client(PROJECT).await);
+    writer.write_partition_skip_invariants(&query, day(5)).await.unwrap();
+    assert!(!stub.statements().iter().any(|sql| sql.contains("ALTER TABLE")));
+
+    // v4 turns `events` into FLOAT64, which the table can't take.
+    let september = PartitionKey::Day(NaiveDate::from_ymd_opt(2024, 9, 2).unwrap());
+    let err = writer.write_partition_skip_invariants(&query, september).await.unwrap_err();
+    assert!(err.to_string().contains("cannot be migrated to v4"), "unexpected error: {}", err);
+}
+
+#[tokio::test]
+async fn test_merge_reports_inserted_rows_only() {
+    let stub = BigQueryStub::start().await;
+    stub.reply_dml_stats("MERGE", 100, 100);
+
+    let query = load_query("simple_query.yaml");
+    let writer = PartitionWriter::new(stub.client(PROJECT).await);
+    let stats = writer.write_partition_skip_invariants(&query, day(3)).await.unwrap();
+
+    assert_eq!(stats.rows_written, Some(100));
+}
+
+#[tokio::test]
+async fn test_truncate_partition_reads_rows_written_from_script() {
+    let stub = BigQueryStub::start().await;
+    stub.reply_rows("SELECT rows_written", vec![vec![json!("17")]]);
+
+    let query = load_query("simple_query.yaml");
+    let writer = PartitionWriter::new(stub.client(PROJECT).await);
+    let stats = writer.write_partition_truncate_skip_invariants(&query, day(2)).await.unwrap();
+
+    assert_eq!(stats.write_strategy, WriteStrategy::TruncatePartition);
+    assert_eq!(stats.rows_written, Some(17));
+    assert!(stub.find_query("BEGIN TRANSACTION").is_some());
+}
+
+#[tokio::test]
+async fn test_insert_overwrite_writes_to_partition_decorator() {
+    let stub = BigQueryStub::start().await;
+
+    let query = load_query("simple_query.yaml");
+    let writer = PartitionWriter::new(stub.client(PROJECT).await);
+    writer
+        .write_partition_with(&query, day(3), WriteStrategy::InsertOverwriteViaJob, false)
+        .await
+        .unwrap();
+
+    let job = stub.find_query("FROM raw.events").unwrap();
+    let destination = &job.config["query"]["destinationTable"];
+    assert_eq!(destination["tableId"], "simple_table$20240303");
+    assert_eq!(job.config["query"]["writeDisposition"], "WRITE_TRUNCATE");
+}
+
+#[tokio::test]
+async fn test_failed_job_surfaces_bigquery_error() {
+    let stub = BigQueryStub::start().await;
+    stub.reply_error("MERGE", "invalidQuery", "Unrecognized name: regio at [3:5]");
+
+    let query = load_query("simple_query.yaml");
+    let writer = PartitionWriter::new(stub.client(PROJECT).await);
+    let err = writer.write_partition_skip_invariants(&query, day(4)).await.unwrap_err();
+
+    assert!(
+        matches!(err, BqDriftError::BigQuery(BigQueryError::InvalidQuery { .. })),
+        "unexpected error: {:?}",
+        err
+    );
+    assert!(err.to_string().contains("Unrecognized name"));
+}
+
+#[tokio::test]
+async fn test_job_failing_with_rate_limit_is_resubmitted() {
+    let stub = BigQueryStub::start().await;
+    stub.reply_affected_rows("MERGE", 42);
+    stub.fail_jobs("MERGE", "rateLimitExceeded", "Exceeded rate limits: too many table update operations", 1);
+
+    let retry = RetryPolicy::default().with_backoff(Duration::from_millis(10), Duration::from_millis(10));
+    let client = stub.client_with(PROJECT, ClientConfig::default().with_retry(retry)).await;
+    let query = load_query("simple_query.yaml");
+    let stats = PartitionWriter::new(client).write_partition_skip_invariants(&query, day(5)).await.unwrap();
+
+    let merges = stub.statements().into_iter().filter(|sql| sql.contains("MERGE")).count();
+    assert_eq!(merges, 2);
+    assert_eq!(stats.rows_written, Some(42));
+    assert_eq!(stats.retries, 1);
+}
+
+#[tokio::test]
+async fn test_job_failing_with_invalid_query_is_not_resubmitted() {
+    let stub = BigQueryStub::start().await;
+    stub.fail_jobs("MERGE", "invalidQuery", "Unrecognized name: regio at [3:5]", 1);
+
+    let query = load_query("simple_query.yaml");
+    let writer = PartitionWriter::new(stub.client(PROJECT).await);
+    let err = writer.write_partition_skip_invariants(&query, day(5)).await.unwrap_err();
+
+    assert!(matches!(err, BqDriftError::BigQuery(BigQueryError::InvalidQuery { .. })), "unexpected error: {:?}", err);
+    assert_eq!(stub.statements().iter().filter(|sql| sql.contains("MERGE")).count(), 1);
+}
+
+#[tokio::test]
+async fn test_invariant_checker_runs_checks_against_destination() {
+    let stub = BigQueryStub::start().await;
+    stub.reply_rows("COUNT(*) as cnt", vec![vec![json!("250")]]);
+    stub.reply_rows("COUNTIF(region IS NULL)", vec![vec![json!("1.5")]]);
+    stub.reply_rows("MIN(count)", vec![vec![json!("-1"), json!("40")]]);
+    stub.reply_rows("COUNT(DISTINCT region)", vec![vec![json!("12")]]);
+
+    let query = load_query("query_with_invariants.yaml");
+    let version = query.get_version_for_partition(&day(5)).unwrap();
+    let (_, after) = resolve_invariants_def(&version.invariants);
+
+    let client = stub.client(PROJECT).await;
+    let checker = InvariantChecker::new(&client, &query.destination, &day(5));
+    let results = checker.run_checks(&after).await.unwrap();
+
+    let status = |name: &str| results.iter().find(|r| r.name == name).unwrap().status;
+    assert_eq!(status("min_rows"), CheckStatus::Passed);
+    assert_eq!(status("null_check"), CheckStatus::Passed);
+    assert_eq!(status("count_positive"), CheckStatus::Failed);
+    assert_eq!(status("region_cardinality"), CheckStatus::Passed);
+
+    let count = stub.find_query("COUNT(*) as cnt").unwrap();
+    assert!(count.sql.contains("`test_dataset.invariant_test` WHERE date = @partition_date"));
+    assert_eq!(partition_date_param(&count.config).as_deref(), Some("2024-03-05"));
+}
+
+#[tokio::test]
+async fn test_failing_before_invariant_blocks_write() {
+    let stub = BigQueryStub::start().await;
+    stub.reply_rows("SELECT 1 WHERE FALSE", vec![vec![json!("3")]]);
+
+    let query = load_query("query_with_invariants.yaml");
+    let writer = PartitionWriter::new(stub.client(PROJECT).await);
+    let err = writer.write_partition(&query, day(6)).await.unwrap_err();
+
+    assert!(matches!(err, BqDriftError::InvariantFailed(_)), "unexpected error: {:?}", err);
+    assert!(stub.find_query("BEGIN TRANSACTION").is_none());
+}
+
+#[tokio::test]
+async fn test_migration_tracker_creates_tracking_tables() {
+    let stub = BigQueryStub::start().await;
+
+    let tracker = MigrationTracker::new(stub.client(PROJECT).await, "bqdrift_tracking");
+    tracker.ensure_tracking_table().await.unwrap();
+
+    assert!(stub.has_dataset(PROJECT, "bqdrift_tracking"));
+    let statements = stub.statements();
+    assert!(statements.iter().any(|s| s.contains("CREATE TABLE IF NOT EXISTS `bqdrift_tracking._bqdrift_query_runs`")));
+    assert!(statements.iter().any(|s| s.contains("CREATE TABLE IF NOT EXISTS `bqdrift_tracking._bqdrift_state`")));
+    assert!(statements.iter().any(|s| s.contains("ADD COLUMN IF NOT EXISTS write_strategy")));
+}
+
+#[tokio::test]
+async fn test_partition_writer_records_state_with_tracker() {
+    let stub = BigQueryStub::start().await;
+    stub.reply_affected_rows("MERGE", 9);
+
+    let query = load_query("simple_query.yaml");
+    let client = stub.client(PROJECT).await;
+    let tracker = MigrationTracker::new(client.clone(), "bqdrift_tracking");
+    let yaml = HashMap::from([(query.name.clone(), "name: simple_query".to_string())]);
+    let writer = PartitionWriter::new(client).with_tracker(tracker, vec![query.clone()], yaml);
+
+    writer.write_partition(&query, day(7)).await.unwrap();
+
+    let insert = stub.find_query("INSERT INTO `bqdrift_tracking._bqdrift_state`").unwrap();
+    assert!(insert.sql.contains("'simple_query'"));
+    assert!(insert.sql.contains("'SUCCESS'"));
+    assert!(insert.sql.contains("'merge'"));
+}
+
+#[tokio::test]
+async fn test_state_insert_failure_does_not_fail_write() {
+    let stub = BigQueryStub::start().await;
+    stub.reply_affected_rows("MERGE", 9);
+    stub.reply_error("INSERT INTO `bqdrift_tracking._bqdrift_state`", "invalid", "Streaming buffer is full");
+
+    let query = load_query("simple_query.yaml");
+    let client = stub.client(PROJECT).await;
+    let tracker = MigrationTracker::new(client.clone(), "bqdrift_tracking");
+    let yaml = HashMap::from([(query.name.clone(), "name: simple_query".to_string())]);
+    let writer = PartitionWriter::new(client).with_tracker(tracker, vec![query.clone()], yaml);
+
+    let stats = writer.write_partition(&query, day(7)).await.unwrap();
+
+    assert_eq!(stats.rows_written, Some(9));
+    assert!(stats.tracking_error.as_deref().is_some_and(|e| e.contains("Streaming buffer is full")));
+}
+
+#[tokio::test]
+async fn test_migration_tracker_reads_latest_states() {
+    let stub = BigQueryStub::start().await;
+    stub.reply_rows("QUALIFY ROW_NUMBER()", vec![vec![
+        json!("simple_query"), json!("2024-03-08"), json!("1"), json!(null), json!("2024-01-01"),
+        json!("sql_sum"), json!("schema_sum"), json!("yaml_sum"), json!(null), json!(null),
+        json!(null), json!("1709856000000000"), json!("1500"), json!("42"), json!("1048576"),
+        json!("CANCELLED"), json!("2024-03-08"), json!("DAY"), json!("10485760"), json!("1200"),
+        json!("stub_job_1"), json!("merge"),
+    ]]);
+
+    let tracker = MigrationTracker::new(stub.client(PROJECT).await, "bqdrift_tracking");
+    let states = tracker.get_latest_states().await.unwrap();
+
+    assert_eq!(states.len(), 1);
+    let state = &states[0];
+    assert_eq!(state.query_name, "simple_query");
+    assert_eq!(state.partition_key, day(8));
+    assert_eq!(state.status, ExecutionStatus::Cancelled);
+    assert_eq!(state.rows_written, Some(42));
+    assert_eq!(state.job_id.as_deref(), Some("stub_job_1"));
+    assert_eq!(state.write_strategy, Some(WriteStrategy::Merge));
+}
+
+#[tokio::test]
+async fn test_migration_tracker_reads_runs() {
+    let stub = BigQueryStub::start().await;
+    stub.reply_rows("_bqdrift_query_runs", vec![
+        vec![
+            json!("simple_query"), json!("2"), json!(null), json!("2024-03-08"),
+            json!("1709856000000000"), json!("42"), json!("1048576"), json!("1500"), json!("SUCCESS"),
+        ],
+        vec![
+            json!("simple_query"), json!("2"), json!("1"), json!("2024-03-09"),
+            json!("1709942400000000"), json!(null), json!(null), json!("900"), json!("FAILED"),
+        ],
+    ]);
+
+    let tracker = MigrationTracker::new(stub.client(PROJECT).await, "bqdrift_tracking");
+    let from = NaiveDate::from_ymd_opt(2024, 3, 8).unwrap();
+    let to = NaiveDate::from_ymd_opt(2024, 3, 9).unwrap();
+    let runs = tracker.get_runs_for_date_range("simple_query", from, to).await.unwrap();
+
+    assert_eq!(runs.len(), 2);
+    assert_eq!(runs[0].partition_date, from);
+    assert_eq!(runs[0].rows_written, Some(42));
+    assert!(matches!(runs[0].status, RunStatus::Success));
+    assert_eq!(runs[1].sql_revision, Some(1));
+    assert!(matches!(runs[1].status, RunStatus::Failed));
+
+    let last = tracker.get_last_run("simple_query", from).await.unwrap().unwrap();
+    assert_eq!(last.query_version, 2);
+}
+
+#[tokio::test]
+async fn test_scratch_writer_writes_and_promotes() {
+    let stub = BigQueryStub::start().await;
+    stub.reply_affected_rows("MERGE", 5);
+
+    let query = load_query("simple_query.yaml");
+    let scratch = ScratchWriter::new(
+        stub.client("scratch-project").await,
+        ScratchConfig::new("scratch-project".to_string()).with_ttl(6),
+    );
+
+    scratch.ensure_dataset().await.unwrap();
+    let stats = scratch.write_partition(&query, day(9), false).await.unwrap();
+
+    assert!(stub.has_dataset("scratch-project", "bqdrift_scratch"));
+    let table = stub.table("scratch-project", "bqdrift_scratch", "test_dataset__simple_table").unwrap();
+    assert!(table["expirationTime"].is_string());
+    assert_eq!(stats.scratch_table, "scratch-project.bqdrift_scratch.test_dataset__simple_table");
+    assert_eq!(stats.rows_written, Some(5));
+    assert_eq!(scratch.list_tables().await.unwrap(), vec!["test_dataset__simple_table"]);
+
+    let production = stub.client(PROJECT).await;
+    scratch.promote_to_production(&query, &day(9), &production).await.unwrap();
+
+    let promote = stub.find_query("USING `scratch-project.bqdrift_scratch.test_dataset__simple_table`").unwrap();
+    assert!(promote.sql.contains("MERGE `stub-project.test_dataset.simple_table`"));
+}
+
+#[tokio::test]
+async fn test_scratch_promote_surfaces_failed_merge_job() {
+    let stub = BigQueryStub::start().await;
+    stub.fail_jobs("USING `scratch-project.bqdrift_scratch", "invalidQuery", "Column date not found", 1);
+
+    let query = load_query("simple_query.yaml");
+    let scratch = ScratchWriter::new(
+        stub.client("scratch-project").await,
+        ScratchConfig::new("scratch-project".to_string()),
+    );
+
+    let production = stub.client(PROJECT).await;
+    let err = scratch.promote_to_production(&query, &day(9), &production).await.unwrap_err();
+
+    assert!(matches!(err, BqDriftError::BigQuery(BigQueryError::InvalidQuery { .. })), "unexpected error: {:?}", err);
+}
+
+#[tokio::test]
+async fn test_scratch_writer_uses_dataset_in_query_location() {
+    let stub = BigQueryStub::start().await;
+
+    let mut query = load_query("simple_query.yaml");
+    query.destination.location = Some("EU".to_string());
+    let scratch = ScratchWriter::new(
+        stub.client("scratch-project").await,
+        ScratchConfig::new("scratch-project".to_string()),
+    );
+
+    let stats = scratch.write_partition(&query, day(9), false).await.unwrap();
+
+    let dataset = stub.dataset("scratch-project", "bqdrift_scratch_eu").unwrap();
+    assert_eq!(dataset["location"], "EU");
+    assert!(stub.table("scratch-project", "bqdrift_scratch_eu", "test_dataset__simple_table").is_some());
+    assert_eq!(stats.scratch_table, "scratch-project.bqdrift_scratch_eu.test_dataset__simple_table");
+}
+
+#[tokio::test]
+async fn test_reservation_reads_affected_rows_from_row_count() {
+    let stub = BigQueryStub::start().await;
+    stub.reply_rows("SELECT @@row_count", vec![vec![json!("7")]]);
+
+    let client = stub.client(PROJECT).await.with_job_config(JobConfig {
+        reservation: Some("projects/admin/locations/US/reservations/etl".to_string()),
+        ..Default::default()
+    });
+    let stats = client.execute_query_with_stats("MERGE t USING s ON FALSE;", &QueryParams::new()).await.unwrap();
+    client.dry_run_query("SELECT 1 AS dry", &QueryParams::new()).await.unwrap();
+
+    assert_eq!(stats.rows_affected, Some(7));
+    let script = stub.find_query("MERGE t").unwrap();
+    assert!(script.sql.starts_with("SET @@reservation = 'projects/admin/locations/US/reservations/etl';"));
+    assert!(script.sql.ends_with("MERGE t USING s ON FALSE;\nSELECT @@row_count"));
+    assert_eq!(stub.find_query("AS dry").unwrap().sql, "SELECT 1 AS dry");
+}
+
+/// A writer recording state through the tracker, whose MERGE never finishes.
+async fn writer_with_running_merge(stub: &BigQueryStub, config: ClientConfig) -> (PartitionWriter, QueryDef) {
+    stub.hold_jobs("MERGE");
+
+    let query = load_query("simple_query.yaml");
+    let client = stub.client_with(PROJECT, config).await;
+    let tracker = MigrationTracker::new(client.clone(), "bqdrift_tracking");
+    let yaml = HashMap::from([(query.name.clone(), "name: simple_query".to_string())]);
+    let writer = PartitionWriter::new(client).with_tracker(tracker, vec![query.clone()], yaml);
+
+    (writer, query)
+}
+
+fn assert_merge_cancelled(stub: &BigQueryStub, err: BqDriftError) {
+    assert!(matches!(err, BqDriftError::Cancelled(_)), "unexpected error: {:?}", err);
+    assert_eq!(stub.cancelled_jobs().len(), 1);
+
+    let insert = stub.find_query("INSERT INTO `bqdrift_tracking._bqdrift_state`").unwrap();
+    assert!(insert.sql.contains("'CANCELLED'"));
+}
+
+#[tokio::test]
+async fn test_job_timeout_cancels_running_job() {
+    let stub = BigQueryStub::start().await;
+    let config = ClientConfig::default().with_job_timeout(Some(Duration::from_millis(200)));
+    let (writer, query) = writer_with_running_merge(&stub, config).await;
+
+    let err = writer.write_partition_skip_invariants(&query, day(10)).await.unwrap_err();
+
+    assert!(err.to_string().contains("timeout"), "unexpected error: {}", err);
+    assert_merge_cancelled(&stub, err);
+}
+
+#[tokio::test]
+async fn test_cancel_signal_cancels_running_job() {
+    let stub = BigQueryStub::start().await;
+    let cancel = CancelSignal::new();
+    let config = ClientConfig::default().with_cancel_signal(cancel.clone());
+    let (writer, query) = writer_with_running_merge(&stub, config).await;
+
+    tokio::spawn(async move {
+        tokio::time::sleep(Duration::from_millis(200)).await;
+        cancel.cancel();
+    });
+    let err = writer.write_partition_skip_invariants(&query, day(11)).await.unwrap_err();
+
+    assert!(err.to_string().contains("interrupted"), "unexpected error: {}", err);
+    assert_merge_cancelled(&stub, err);
+}
+
+async fn backfill_simple_query(stub: &BigQueryStub, from: u32, to: u32, options: BackfillOptions) -> RunReport {
+    let runner = Runner::new(stub.client(PROJECT).await, vec![load_query("simple_query.yaml")]);
+    runner.backfill_partitions_with("simple_query", day(from), day(to), None, &options).await.unwrap()
+}
+
+#[tokio::test]
+async fn test_backfill_keeps_at_most_concurrency_writes_in_flight() {
+    let stub = BigQueryStub::start().await;
+    stub.slow_jobs("MERGE");
+
+    let report = backfill_simple_query(&stub, 1, 5, BackfillOptions::default().with_concurrency(2)).await;
+
+    assert_eq!(report.stats.len(), 5);
+    assert_eq!(stub.max_running_jobs(), 2);
+}
+
+#[tokio::test]
+async fn test_backfill_stops_after_max_failures() {
+    let stub = BigQueryStub::start().await;
+    stub.reply_error("MERGE", "invalidQuery", "Unrecognized name: regio at [3:5]");
+
+    let options = BackfillOptions::default().with_max_failures(Some(2));
+    let report = backfill_simple_query(&stub, 1, 5, options).await;
+
+    assert!(report.stats.is_empty());
+    assert_eq!(report.failures.len(), 2);
+    assert!(report.failures.iter().all(|f| f.error.contains("Unrecognized name")));
+    let skipped: Vec<_> = report.skipped.iter().map(|s| s.partition_key.clone()).collect();
+    assert_eq!(skipped, [day(3), day(4), day(5)]);
+    assert!(report.skipped.iter().all(|s| s.reason == SkipReason::MaxFailures(2)));
+    assert_eq!(stub.statements().iter().filter(|sql| sql.contains("MERGE")).count(), 2);
+}
+
+#[tokio::test]
+async fn test_backfill_reports_partitions_in_key_order() {
+    let stub = BigQueryStub::start().await;
+    // The first partition finishes last.
+    stub.slow_jobs("2024-03-01");
+
+    let report = backfill_simple_query(&stub, 1, 4, BackfillOptions::default().with_concurrency(4)).await;
+
+    let written: Vec<_> = report.stats.iter().map(|s| s.partition_key.clone()).collect();
+    assert_eq!(written, [day(1), day(2), day(3), day(4)]);
+}
+
+#[tokio::test]
+async fn test_missing_table_is_reported_as_absent() {
+    let stub = BigQueryStub::start().await;
+    let client = stub.client(PROJECT).await;
+
+    assert!(!client.table_exists("test_dataset", "nope").await.unwrap());
+    assert!(client.get_table_schema("test_dataset", "nope").await.unwrap().is_none());
+
+    stub.reply_error("SELECT broken", "invalidQuery", "Syntax error: Unexpected end of script at [1:14]");
+    let err = client.execute_query("SELECT broken").await.unwrap_err();
+    assert!(matches!(err, BqDriftError::BigQuery(BigQueryError::InvalidQuery { .. })), "unexpected error: {:?}", err);
+}
+
+#[tokio::test]
+async fn test_retried_insert_reuses_job_id() {
+    let stub = BigQueryStub::start().await;
+    stub.reply_affected_rows("MERGE", 42);
+    stub.lose_insert_responses(1);
+
+    let retry = RetryPolicy::default().with_backoff(Duration::from_millis(10), Duration::from_millis(10));
+    let client = stub.client_with(PROJECT, ClientConfig::default().with_retry(retry)).await;
+    let query = load_query("simple_query.yaml");
+    let stats = PartitionWriter::new(client).write_partition_skip_invariants(&query, day(5)).await.unwrap();
+
+    let merges = stub.statements().into_iter().filter(|sql| sql.contains("MERGE")).count();
+    assert_eq!(merges, 1);
+    assert_eq!(stats.rows_written, Some(42));
+    assert_eq!(stats.retries, 1);
+}
+
+#[tokio::test]
+async fn test_query_requests_carry_request_id() {
+    let stub = BigQueryStub::start().await;
+    let client = stub.client(PROJECT).await;
+
+    client.query_rows("SELECT 1").await.unwrap();
+
+    let request = stub.find_query("SELECT 1").unwrap();
+    assert!(request.config["requestId"].as_str().is_some_and(|id| !id.is_empty()));
+}
+
+#[tokio::test]
+async fn test_query_rows_waits_for_queries_that_return_early() {
+    let stub = BigQueryStub::start().await;
+    stub.reply_rows("FROM slow_table", vec![vec![json!("a")], vec![json!("b")]]);
+    stub.defer_query_results("FROM slow_table");
+    let client = stub.client(PROJECT).await;
+
+    let rows = client.query_rows("SELECT name FROM slow_table").await.unwrap();
+
+    assert_eq!(rows, vec![vec![Some("a".to_string())], vec![Some("b".to_string())]]);
+}
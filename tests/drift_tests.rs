@@ -1,7 +1,7 @@
 use bqdrift::dsl::QueryLoader;
 use bqdrift::{DriftDetector, DriftState, PartitionState, ExecutionStatus, compress_to_base64, decompress_from_base64, Checksums};
 use bqdrift::ImmutabilityChecker;
-use bqdrift::schema::Schema;
+use bqdrift::schema::{PartitionKey, Schema};
 use bqdrift::diff::{decode_sql, format_sql_diff, has_changes};
 use chrono::{NaiveDate, Utc};
 use std::collections::HashMap;
@@ -33,6 +33,7 @@ fn create_stored_state_with_version(
     let checksums = Checksums::compute(sql_content, schema, yaml_content);
     PartitionState {
         query_name: query_name.to_string(),
+        partition_key: PartitionKey::Day(partition_date),
         partition_date,
         version,
         sql_revision: revision,
@@ -41,11 +42,16 @@ fn create_stored_state_with_version(
         schema_checksum: checksums.schema,
         yaml_checksum: checksums.yaml,
         executed_sql_b64: Some(compress_to_base64(sql_content)),
+        executed_yaml_b64: None,
         upstream_states: HashMap::new(),
         executed_at: Utc::now(),
         execution_time_ms: Some(100),
         rows_written: Some(1000),
         bytes_processed: Some(10000),
+        bytes_billed: None,
+        slot_ms: None,
+        job_id: None,
+        write_strategy: None,
         status: ExecutionStatus::Success,
     }
 }
@@ -1,5 +1,5 @@
-use bqdrift::dsl::QueryLoader;
-use bqdrift::{BqType, Severity};
+use bqdrift::dsl::{JobPriority, QueryLoader};
+use bqdrift::{BqType, Severity, WriteStrategy};
 use bqdrift::invariant::InvariantCheck;
 use chrono::NaiveDate;
 use std::path::Path;
@@ -56,6 +56,22 @@ fn test_load_simple_query_partition() {
     assert_eq!(query.destination.partition.field, Some("date".to_string()));
 }
 
+#[test]
+fn test_write_strategy_defaults_to_merge() {
+    let loader = QueryLoader::new();
+    let query = loader.load_query(fixtures_path().join("analytics/simple_query.yaml")).unwrap();
+
+    assert_eq!(query.destination.write_strategy, WriteStrategy::Merge);
+}
+
+#[test]
+fn test_load_write_strategy() {
+    let loader = QueryLoader::new();
+    let query = loader.load_query(fixtures_path().join("analytics/query_with_invariants.yaml")).unwrap();
+
+    assert_eq!(query.destination.write_strategy, WriteStrategy::TruncatePartition);
+}
+
 #[test]
 fn test_load_simple_query_cluster() {
     let loader = QueryLoader::new();
@@ -114,6 +130,22 @@ fn test_versioned_query_revisions() {
     assert!(rev.sql_content.contains("COALESCE"));
 }
 
+#[test]
+fn test_versioned_query_job_settings() {
+    let loader = QueryLoader::new();
+    let query = loader.load_query(fixtures_path().join("analytics/versioned_query.yaml")).unwrap();
+
+    let v1 = &query.versions[0];
+    assert_eq!(v1.job.labels.get("team").map(String::as_str), Some("growth"));
+    assert_eq!(v1.job.priority, Some(JobPriority::Batch));
+    assert_eq!(v1.job.maximum_bytes_billed, Some(10 * 1024 * 1024 * 1024));
+
+    let v4 = &query.versions[3];
+    assert_eq!(v4.job.labels.get("team").map(String::as_str), Some("growth-platform"));
+    assert_eq!(v4.job.priority, Some(JobPriority::Interactive));
+    assert_eq!(v4.job.maximum_bytes_billed, Some(10 * 1024 * 1024 * 1024));
+}
+
 #[test]
 fn test_get_version_for_date() {
     let loader = QueryLoader::new();
@@ -161,6 +193,45 @@ fn test_get_sql_for_date_with_revision() {
     assert!(sql.contains("COALESCE"));
 }
 
+#[test]
+fn test_get_revision_for_date() {
+    let loader = QueryLoader::new();
+    let query = loader.load_query(fixtures_path().join("analytics/versioned_query.yaml")).unwrap();
+
+    let v2 = &query.versions[1];
+
+    assert!(v2.get_revision_for_date(NaiveDate::from_ymd_opt(2024, 3, 10).unwrap()).is_none());
+
+    let rev = v2.get_revision_for_date(NaiveDate::from_ymd_opt(2024, 3, 20).unwrap());
+    assert_eq!(rev.map(|r| r.revision), Some(1));
+}
+
+#[test]
+fn test_query_produces_table_reference() {
+    let loader = QueryLoader::new();
+    let query = loader.load_query(fixtures_path().join("analytics/simple_query.yaml")).unwrap();
+
+    assert!(query.produces("test_dataset.simple_table"));
+    assert!(query.produces("my-project.test_dataset.simple_table"));
+    assert!(query.produces("`my-project.test_dataset.simple_table`"));
+    assert!(!query.produces("test_dataset.other_table"));
+    assert!(!query.produces("simple_table"));
+}
+
+#[test]
+fn test_cross_project_destination() {
+    let loader = QueryLoader::new();
+    let mut query = loader.load_query(fixtures_path().join("analytics/simple_query.yaml")).unwrap();
+    assert_eq!(query.destination.table_ref(), "test_dataset.simple_table");
+
+    query.destination.project = Some("eu-project".to_string());
+    assert_eq!(query.destination.table_ref(), "eu-project.test_dataset.simple_table");
+    assert_eq!(query.destination.project_or("billing-project"), "eu-project");
+    assert!(query.produces("eu-project.test_dataset.simple_table"));
+    assert!(query.produces("test_dataset.simple_table"));
+    assert!(!query.produces("other-project.test_dataset.simple_table"));
+}
+
 #[test]
 fn test_latest_version() {
     let loader = QueryLoader::new();
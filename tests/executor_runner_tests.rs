@@ -0,0 +1,122 @@
+use bqdrift::dsl::QueryLoader;
+use bqdrift::{
+    create_mock_executor, BqDriftError, CheckStatus, Executor, ExecutorRunner, InvariantCheck, InvariantDef,
+    InvariantsDef, PartitionKey, QueryDef, Severity,
+};
+use chrono::NaiveDate;
+use std::path::Path;
+
+const DAY: &str = "2024-03-15";
+
+async fn executor_with_events() -> Executor {
+    let executor = create_mock_executor().unwrap();
+    for sql in [
+        "CREATE SCHEMA IF NOT EXISTS raw",
+        "CREATE SCHEMA IF NOT EXISTS test_dataset",
+        "CREATE TABLE raw.events (created_at TIMESTAMP, region STRING)",
+        "CREATE TABLE test_dataset.simple_table (date DATE, region STRING, count INT64)",
+        "INSERT INTO raw.events VALUES \
+            (TIMESTAMP '2024-03-15 08:00:00', 'eu'), \
+            (TIMESTAMP '2024-03-15 09:00:00', 'us'), \
+            (TIMESTAMP '2024-03-16 09:00:00', 'us')",
+    ] {
+        executor.execute(sql).await.unwrap();
+    }
+    executor
+}
+
+fn day() -> PartitionKey {
+    PartitionKey::Day(NaiveDate::parse_from_str(DAY, "%Y-%m-%d").unwrap())
+}
+
+fn row_count(name: &str, source: Option<&str>, min: Option<i64>, max: Option<i64>) -> InvariantDef {
+    InvariantDef {
+        name: name.to_string(),
+        description: None,
+        severity: Severity::Error,
+        check: InvariantCheck::RowCount { source: source.map(str::to_string), min, max },
+    }
+}
+
+fn query_with_invariants(invariants: InvariantsDef) -> QueryDef {
+    let mut query = QueryLoader::new()
+        .load_query(Path::new("tests/fixtures/analytics/simple_query.yaml"))
+        .unwrap();
+    query.versions[0].invariants = invariants;
+    query
+}
+
+async fn destination_rows(executor: &Executor, table: &str) -> usize {
+    executor
+        .query(&format!("SELECT * FROM {} WHERE date = DATE '{}'", table, DAY))
+        .await
+        .unwrap()
+        .rows
+        .len()
+}
+
+/// Fails whenever the source has events, so it blocks every write below.
+fn blocking_before_check() -> InvariantsDef {
+    InvariantsDef {
+        before: vec![row_count("no_events_yet", Some("SELECT COUNT(*) FROM raw.events"), None, Some(0))],
+        after: vec![],
+    }
+}
+
+#[tokio::test]
+async fn test_executor_runner_runs_passing_checks() {
+    let executor = executor_with_events().await;
+    let query = query_with_invariants(InvariantsDef {
+        before: vec![],
+        after: vec![row_count("has_rows", None, Some(1), None)],
+    });
+
+    let runner = ExecutorRunner::new(&executor, vec![query]);
+    let stats = runner.run_query_partition("simple_query", day()).await.unwrap();
+
+    let report = stats.invariant_report.unwrap();
+    assert_eq!(report.after.len(), 1);
+    assert_eq!(report.after[0].status, CheckStatus::Passed);
+    assert_eq!(destination_rows(&executor, "test_dataset.simple_table").await, 2);
+}
+
+#[tokio::test]
+async fn test_executor_runner_failing_before_check_blocks_write() {
+    let executor = executor_with_events().await;
+    let runner = ExecutorRunner::new(&executor, vec![query_with_invariants(blocking_before_check())]);
+
+    let err = runner.run_query_partition("simple_query", day()).await.unwrap_err();
+
+    assert!(matches!(err, BqDriftError::InvariantFailed(_)), "unexpected error: {:?}", err);
+    assert_eq!(destination_rows(&executor, "test_dataset.simple_table").await, 0);
+}
+
+#[tokio::test]
+async fn test_executor_runner_skip_invariants() {
+    let executor = executor_with_events().await;
+    let runner = ExecutorRunner::new(&executor, vec![query_with_invariants(blocking_before_check())])
+        .with_skip_invariants(true);
+
+    let stats = runner.run_query_partition("simple_query", day()).await.unwrap();
+
+    assert!(stats.invariant_report.is_none());
+    assert_eq!(destination_rows(&executor, "test_dataset.simple_table").await, 2);
+}
+
+#[tokio::test]
+async fn test_executor_runner_scratch_mode_leaves_destination_untouched() {
+    let executor = executor_with_events().await;
+    executor.execute("CREATE SCHEMA IF NOT EXISTS scratch").await.unwrap();
+    let query = query_with_invariants(InvariantsDef {
+        before: vec![],
+        after: vec![row_count("has_rows", None, Some(1), None)],
+    });
+
+    let runner = ExecutorRunner::new(&executor, vec![query]).with_scratch_dataset("scratch");
+    let stats = runner.run_query_partition("simple_query", day()).await.unwrap();
+
+    assert_eq!(stats.table, "scratch.test_dataset__simple_table");
+    assert_eq!(stats.invariant_report.unwrap().after[0].status, CheckStatus::Passed);
+    assert_eq!(destination_rows(&executor, "scratch.test_dataset__simple_table").await, 2);
+    assert_eq!(destination_rows(&executor, "test_dataset.simple_table").await, 0);
+}
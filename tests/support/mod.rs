@@ -0,0 +1,502 @@
+//! An in-process stand-in for the BigQuery v2 REST API.
+//!
+//! It keeps datasets and tables in memory and answers every query from
+//! canned replies registered by the test, matched by a fragment of the SQL.
+//! Queries with no matching reply succeed with no rows. Nothing is executed,
+//! so tests assert on the statements the client sent.
+
+use axum::extract::{Path, State};
+use axum::http::StatusCode;
+use axum::routing::{get, post};
+use axum::{Json, Router};
+use bqdrift::executor::{BqClient, ClientConfig, Credentials};
+use serde_json::{json, Value};
+use std::collections::{BTreeMap, HashMap};
+use std::sync::{Arc, Mutex};
+
+pub const BYTES_PROCESSED: i64 = 1_048_576;
+pub const BYTES_BILLED: i64 = 10_485_760;
+
+/// One statement the client submitted, through jobs.query or jobs.insert.
+#[derive(Debug, Clone)]
+pub struct RecordedQuery {
+    pub sql: String,
+    /// The `QueryRequest` of jobs.query, or the `configuration` of an
+    /// inserted job.
+    pub config: Value,
+}
+
+#[derive(Debug, Clone, Default)]
+struct Reply {
+    rows: Vec<Vec<Value>>,
+    affected_rows: Option<i64>,
+    /// Rows deleted by the statement, reported alongside the inserted
+    /// `affected_rows` in its DML statistics.
+    deleted_rows: Option<i64>,
+    error: Option<(String, String)>,
+}
+
+#[derive(Default)]
+struct StubState {
+    datasets: BTreeMap<String, Value>,
+    tables: BTreeMap<String, Value>,
+    jobs: HashMap<String, (Value, Reply)>,
+    replies: Vec<(String, Reply)>,
+    queries: Vec<RecordedQuery>,
+    next_job: u64,
+    lost_insert_responses: usize,
+    held: Vec<String>,
+    /// Job-level errors for the next jobs whose SQL contains the fragment,
+    /// with the number of jobs left to fail.
+    failing: Vec<(String, (String, String), usize)>,
+    cancelled: Vec<String>,
+    slow: Vec<String>,
+    deferred: Vec<String>,
+    /// Slow jobs that have not been reported done yet, with the polls left
+    /// before they are.
+    running: HashMap<String, usize>,
+    max_running: usize,
+}
+
+impl StubState {
+    /// The first registered reply whose fragment `sql` contains.
+    fn reply_for(&self, sql: &str) -> Reply {
+        self.replies
+            .iter()
+            .find(|(fragment, _)| sql.contains(fragment.as_str()))
+            .map(|(_, reply)| reply.clone())
+            .unwrap_or_default()
+    }
+
+    fn next_job_id(&mut self) -> String {
+        self.next_job += 1;
+        format!("stub_job_{}", self.next_job)
+    }
+}
+
+type Shared = Arc<Mutex<StubState>>;
+type ApiResult = Result<Json<Value>, (StatusCode, Json<Value>)>;
+
+pub struct BigQueryStub {
+    endpoint: String,
+    state: Shared,
+}
+
+impl BigQueryStub {
+    /// Serve the stand-in on a free local port.
+    pub async fn start() -> Self {
+        let state = Shared::default();
+
+        let app = Router::new()
+            .route("/projects/:project/datasets", post(create_dataset))
+            .route("/projects/:project/datasets/:dataset", get(get_dataset))
+            .route("/projects/:project/datasets/:dataset/tables", get(list_tables).post(create_table))
+            .route("/projects/:project/datasets/:dataset/tables/:table", get(get_table).delete(delete_table))
+            .route("/projects/:project/queries", post(query))
+            .route("/projects/:project/queries/:job_id", get(get_query_results))
+            .route("/projects/:project/jobs", post(insert_job))
+            .route("/projects/:project/jobs/:job_id", get(get_job))
+            .route("/projects/:project/jobs/:job_id/cancel", post(cancel_job))
+            .with_state(state.clone());
+
+        let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let endpoint = format!("http://{}", listener.local_addr().unwrap());
+        tokio::spawn(async move {
+            axum::serve(listener, app).await.unwrap();
+        });
+
+        Self { endpoint, state }
+    }
+
+    /// A client for `project` that talks to this stand-in without credentials.
+    pub async fn client(&self, project: &str) -> BqClient {
+        self.client_with(project, ClientConfig::default()).await
+    }
+
+    /// Like `client`, with `config` for everything but the endpoint and credentials.
+    pub async fn client_with(&self, project: &str, config: ClientConfig) -> BqClient {
+        let config = config
+            .with_endpoint(Some(self.endpoint.clone()))
+            .with_credentials(Credentials::NoAuth);
+
+        BqClient::connect(project, config).await.unwrap()
+    }
+
+    /// Answer queries containing `sql_fragment` with `rows`.
+    pub fn reply_rows(&self, sql_fragment: &str, rows: Vec<Vec<Value>>) {
+        self.add_reply(sql_fragment, Reply { rows, ..Default::default() });
+    }
+
+    /// Report `rows` DML-affected rows for queries containing `sql_fragment`.
+    pub fn reply_affected_rows(&self, sql_fragment: &str, rows: i64) {
+        self.add_reply(sql_fragment, Reply { affected_rows: Some(rows), ..Default::default() });
+    }
+
+    /// Report DML statistics of `inserted` and `deleted` rows for queries
+    /// containing `sql_fragment`, with their sum as the affected rows.
+    pub fn reply_dml_stats(&self, sql_fragment: &str, inserted: i64, deleted: i64) {
+        self.add_reply(sql_fragment, Reply {
+            affected_rows: Some(inserted),
+            deleted_rows: Some(deleted),
+            ..Default::default()
+        });
+    }
+
+    /// Fail queries containing `sql_fragment` with a BigQuery error.
+    pub fn reply_error(&self, sql_fragment: &str, reason: &str, message: &str) {
+        self.add_reply(sql_fragment, Reply {
+            error: Some((reason.to_string(), message.to_string())),
+            ..Default::default()
+        });
+    }
+
+    /// Finish the next `count` inserted jobs whose SQL contains
+    /// `sql_fragment` with a job-level error, then run them normally.
+    pub fn fail_jobs(&self, sql_fragment: &str, reason: &str, message: &str, count: usize) {
+        let error = (reason.to_string(), message.to_string());
+        self.state.lock().unwrap().failing.push((sql_fragment.to_string(), error, count));
+    }
+
+    /// Accept the next `count` inserted jobs but answer each with a timeout,
+    /// as if the response had been lost on the way back.
+    pub fn lose_insert_responses(&self, count: usize) {
+        self.state.lock().unwrap().lost_insert_responses = count;
+    }
+
+    /// Leave inserted jobs whose SQL contains `sql_fragment` running until
+    /// they are cancelled.
+    pub fn hold_jobs(&self, sql_fragment: &str) {
+        self.state.lock().unwrap().held.push(sql_fragment.to_string());
+    }
+
+    /// Report inserted jobs whose configuration (SQL, parameters, labels)
+    /// contains `fragment` as running until they have been polled once.
+    pub fn slow_jobs(&self, fragment: &str) {
+        self.state.lock().unwrap().slow.push(fragment.to_string());
+    }
+
+    /// Answer jobs.query requests whose SQL contains `sql_fragment` with
+    /// `jobComplete: false` and no rows, as BigQuery does when a query
+    /// outlasts the request's timeout. The job finishes after one poll.
+    pub fn defer_query_results(&self, sql_fragment: &str) {
+        self.state.lock().unwrap().deferred.push(sql_fragment.to_string());
+    }
+
+    /// The most slow jobs that were running at the same time.
+    pub fn max_running_jobs(&self) -> usize {
+        self.state.lock().unwrap().max_running
+    }
+
+    /// Ids of the jobs the client asked to cancel, in order.
+    pub fn cancelled_jobs(&self) -> Vec<String> {
+        self.state.lock().unwrap().cancelled.clone()
+    }
+
+    fn add_reply(&self, sql_fragment: &str, reply: Reply) {
+        self.state.lock().unwrap().replies.push((sql_fragment.to_string(), reply));
+    }
+
+    /// Store a table as if it had been created earlier, with `fields` as its
+    /// schema and a day partitioning on `date`.
+    pub fn put_table(&self, project: &str, dataset: &str, table: &str, fields: Value) {
+        let resource = json!({
+            "kind": "bigquery#table",
+            "tableReference": {"projectId": project, "datasetId": dataset, "tableId": table},
+            "schema": {"fields": fields},
+            "timePartitioning": {"type": "DAY", "field": "date"},
+        });
+        self.state.lock().unwrap().tables.insert(table_key(project, dataset, table), resource);
+    }
+
+    pub fn queries(&self) -> Vec<RecordedQuery> {
+        self.state.lock().unwrap().queries.clone()
+    }
+
+    /// SQL of every statement submitted, in order.
+    pub fn statements(&self) -> Vec<String> {
+        self.queries().into_iter().map(|q| q.sql).collect()
+    }
+
+    /// The first statement containing `fragment`.
+    pub fn find_query(&self, fragment: &str) -> Option<RecordedQuery> {
+        self.queries().into_iter().find(|q| q.sql.contains(fragment))
+    }
+
+    pub fn has_dataset(&self, project: &str, dataset: &str) -> bool {
+        self.dataset(project, dataset).is_some()
+    }
+
+    pub fn dataset(&self, project: &str, dataset: &str) -> Option<Value> {
+        self.state.lock().unwrap().datasets.get(&format!("{}.{}", project, dataset)).cloned()
+    }
+
+    pub fn table(&self, project: &str, dataset: &str, table: &str) -> Option<Value> {
+        self.state.lock().unwrap().tables.get(&table_key(project, dataset, table)).cloned()
+    }
+}
+
+fn table_key(project: &str, dataset: &str, table: &str) -> String {
+    format!("{}.{}.{}", project, dataset, table)
+}
+
+fn not_found(message: String) -> (StatusCode, Json<Value>) {
+    error_response(StatusCode::NOT_FOUND, "notFound", &message)
+}
+
+fn error_response(status: StatusCode, reason: &str, message: &str) -> (StatusCode, Json<Value>) {
+    let body = json!({
+        "error": {
+            "code": status.as_u16(),
+            "message": message,
+            "errors": [{"message": message, "domain": "global", "reason": reason}],
+            "status": if status == StatusCode::NOT_FOUND { "NOT_FOUND" } else { "INVALID_ARGUMENT" },
+        }
+    });
+    (status, Json(body))
+}
+
+fn table_rows(rows: &[Vec<Value>]) -> Value {
+    rows.iter()
+        .map(|row| json!({"f": row.iter().map(|v| json!({"v": v})).collect::<Vec<_>>()}))
+        .collect()
+}
+
+fn job_reference(project: &str, job_id: &str, location: &str) -> Value {
+    json!({"projectId": project, "jobId": job_id, "location": location})
+}
+
+async fn create_dataset(State(state): State<Shared>, Path(project): Path<String>, Json(dataset): Json<Value>) -> ApiResult {
+    let id = dataset["datasetReference"]["datasetId"].as_str().unwrap_or_default().to_string();
+    state.lock().unwrap().datasets.insert(format!("{}.{}", project, id), dataset.clone());
+    Ok(Json(dataset))
+}
+
+async fn get_dataset(State(state): State<Shared>, Path((project, dataset)): Path<(String, String)>) -> ApiResult {
+    let key = format!("{}.{}", project, dataset);
+    state
+        .lock()
+        .unwrap()
+        .datasets
+        .get(&key)
+        .cloned()
+        .map(Json)
+        .ok_or_else(|| not_found(format!("Not found: Dataset {}:{}", project, dataset)))
+}
+
+async fn list_tables(State(state): State<Shared>, Path((project, dataset)): Path<(String, String)>) -> ApiResult {
+    let prefix = format!("{}.{}.", project, dataset);
+    let tables: Vec<Value> = state
+        .lock()
+        .unwrap()
+        .tables
+        .iter()
+        .filter(|(key, _)| key.starts_with(&prefix))
+        .map(|(key, table)| json!({
+            "kind": "bigquery#table",
+            "id": key,
+            "tableReference": table["tableReference"],
+            "type": "TABLE",
+        }))
+        .collect();
+
+    Ok(Json(json!({"kind": "bigquery#tableList", "totalItems": tables.len(), "tables": tables})))
+}
+
+async fn create_table(
+    State(state): State<Shared>,
+    Path((project, dataset)): Path<(String, String)>,
+    Json(table): Json<Value>,
+) -> ApiResult {
+    let id = table["tableReference"]["tableId"].as_str().unwrap_or_default().to_string();
+    let key = table_key(&project, &dataset, &id);
+
+    let mut state = state.lock().unwrap();
+    if state.tables.contains_key(&key) {
+        return Err(error_response(StatusCode::CONFLICT, "duplicate", &format!("Already Exists: Table {}", key)));
+    }
+    state.tables.insert(key, table.clone());
+    Ok(Json(table))
+}
+
+async fn get_table(State(state): State<Shared>, Path((project, dataset, table)): Path<(String, String, String)>) -> ApiResult {
+    let key = table_key(&project, &dataset, &table);
+    state
+        .lock()
+        .unwrap()
+        .tables
+        .get(&key)
+        .cloned()
+        .map(Json)
+        .ok_or_else(|| not_found(format!("Not found: Table {}:{}.{}", project, dataset, table)))
+}
+
+async fn delete_table(State(state): State<Shared>, Path((project, dataset, table)): Path<(String, String, String)>) -> StatusCode {
+    match state.lock().unwrap().tables.remove(&table_key(&project, &dataset, &table)) {
+        Some(_) => StatusCode::NO_CONTENT,
+        None => StatusCode::NOT_FOUND,
+    }
+}
+
+/// jobs.query: runs synchronously and returns the rows in the response.
+async fn query(State(state): State<Shared>, Path(project): Path<String>, Json(request): Json<Value>) -> ApiResult {
+    let sql = request["query"].as_str().unwrap_or_default().to_string();
+    let location = request["location"].as_str().unwrap_or("US").to_string();
+
+    let mut state = state.lock().unwrap();
+    state.queries.push(RecordedQuery { sql: sql.clone(), config: request.clone() });
+
+    let reply = state.reply_for(&sql);
+    if let Some((reason, message)) = &reply.error {
+        return Err(error_response(StatusCode::BAD_REQUEST, reason, message));
+    }
+
+    let job_id = state.next_job_id();
+    if state.deferred.iter().any(|fragment| sql.contains(fragment.as_str())) {
+        let resource = json!({
+            "kind": "bigquery#job",
+            "jobReference": job_reference(&project, &job_id, &location),
+            "status": {"state": "DONE"},
+        });
+        state.jobs.insert(job_id.clone(), (resource, reply));
+        state.running.insert(job_id.clone(), 1);
+        return Ok(Json(json!({
+            "kind": "bigquery#queryResponse",
+            "jobReference": job_reference(&project, &job_id, &location),
+            "jobComplete": false,
+        })));
+    }
+
+    Ok(Json(json!({
+        "kind": "bigquery#queryResponse",
+        "jobReference": job_reference(&project, &job_id, &location),
+        "jobComplete": true,
+        "totalRows": reply.rows.len().to_string(),
+        "rows": table_rows(&reply.rows),
+        "totalBytesProcessed": BYTES_PROCESSED.to_string(),
+        "numDmlAffectedRows": reply.affected_rows.map(|n| (n + reply.deleted_rows.unwrap_or(0)).to_string()),
+    })))
+}
+
+/// jobs.insert: the job finishes immediately, failing if its reply is an error.
+/// A job id the client already used is rejected as a duplicate.
+async fn insert_job(State(state): State<Shared>, Path(project): Path<String>, Json(job): Json<Value>) -> ApiResult {
+    let configuration = job["configuration"].clone();
+    let sql = configuration["query"]["query"].as_str().unwrap_or_default().to_string();
+    let location = job["jobReference"]["location"].as_str().unwrap_or("US").to_string();
+
+    let mut state = state.lock().unwrap();
+    let job_id = match job["jobReference"]["jobId"].as_str() {
+        Some(id) if state.jobs.contains_key(id) => {
+            return Err(error_response(StatusCode::CONFLICT, "duplicate", &format!("Already Exists: Job {}:{}.{}", project, location, id)));
+        }
+        Some(id) => id.to_string(),
+        None => state.next_job_id(),
+    };
+    state.queries.push(RecordedQuery { sql: sql.clone(), config: configuration.clone() });
+
+    let mut reply = state.reply_for(&sql);
+    if let Some((_, error, left)) = state
+        .failing
+        .iter_mut()
+        .find(|(fragment, _, left)| *left > 0 && sql.contains(fragment.as_str()))
+    {
+        *left -= 1;
+        reply.error = Some(error.clone());
+    }
+
+    let held = state.held.iter().any(|fragment| sql.contains(fragment.as_str()));
+    let mut status = json!({"state": if held { "RUNNING" } else { "DONE" }});
+    if let Some((reason, message)) = &reply.error {
+        status["errorResult"] = json!({"reason": reason, "message": message});
+        status["errors"] = json!([{"reason": reason, "message": message}]);
+    }
+
+    let resource = json!({
+        "kind": "bigquery#job",
+        "id": format!("{}:{}.{}", project, location, job_id),
+        "jobReference": job_reference(&project, &job_id, &location),
+        "configuration": configuration,
+        "status": status,
+        "statistics": {
+            "totalBytesProcessed": BYTES_PROCESSED.to_string(),
+            "totalSlotMs": "1200",
+            "query": {
+                "totalBytesBilled": BYTES_BILLED.to_string(),
+                "numDmlAffectedRows": reply.affected_rows.map(|n| (n + reply.deleted_rows.unwrap_or(0)).to_string()),
+                "dmlStats": reply.affected_rows.map(|n| json!({
+                    "insertedRowCount": n.to_string(),
+                    "deletedRowCount": reply.deleted_rows.unwrap_or(0).to_string(),
+                })),
+            },
+        },
+    });
+
+    let slow = !held && state.slow.iter().any(|fragment| configuration.to_string().contains(fragment.as_str()));
+    state.jobs.insert(job_id.clone(), (resource.clone(), reply));
+    if state.lost_insert_responses > 0 {
+        state.lost_insert_responses -= 1;
+        return Err(error_response(StatusCode::BAD_REQUEST, "timeout", "Request timed out"));
+    }
+    if slow {
+        state.running.insert(job_id, 1);
+        state.max_running = state.max_running.max(state.running.len());
+        return Ok(Json(still_running(&resource)));
+    }
+    Ok(Json(resource))
+}
+
+fn still_running(resource: &Value) -> Value {
+    let mut resource = resource.clone();
+    resource["status"] = json!({"state": "RUNNING"});
+    resource
+}
+
+async fn get_job(State(state): State<Shared>, Path((_project, job_id)): Path<(String, String)>) -> ApiResult {
+    let mut state = state.lock().unwrap();
+    let resource = state
+        .jobs
+        .get(&job_id)
+        .map(|(resource, _)| resource.clone())
+        .ok_or_else(|| not_found(format!("Not found: Job {}", job_id)))?;
+
+    match state.running.get_mut(&job_id) {
+        Some(polls) if *polls > 0 => {
+            *polls -= 1;
+            Ok(Json(still_running(&resource)))
+        }
+        Some(_) => {
+            state.running.remove(&job_id);
+            Ok(Json(resource))
+        }
+        None => Ok(Json(resource)),
+    }
+}
+
+/// jobs.cancel: the job stops at once, failing with `stopped`.
+async fn cancel_job(State(state): State<Shared>, Path((_project, job_id)): Path<(String, String)>) -> ApiResult {
+    let mut state = state.lock().unwrap();
+    state.cancelled.push(job_id.clone());
+
+    let (resource, _) = state.jobs.get_mut(&job_id).ok_or_else(|| not_found(format!("Not found: Job {}", job_id)))?;
+    if resource["status"]["state"] != "DONE" {
+        resource["status"] = json!({
+            "state": "DONE",
+            "errorResult": {"reason": "stopped", "message": "Job execution was cancelled: User requested cancellation"},
+        });
+    }
+
+    Ok(Json(json!({"kind": "bigquery#jobCancelResponse", "job": resource.clone()})))
+}
+
+async fn get_query_results(State(state): State<Shared>, Path((_project, job_id)): Path<(String, String)>) -> ApiResult {
+    let state = state.lock().unwrap();
+    let (resource, reply) = state.jobs.get(&job_id).ok_or_else(|| not_found(format!("Not found: Job {}", job_id)))?;
+
+    Ok(Json(json!({
+        "kind": "bigquery#getQueryResultsResponse",
+        "jobReference": resource["jobReference"],
+        "jobComplete": true,
+        "totalRows": reply.rows.len().to_string(),
+        "rows": table_rows(&reply.rows),
+    })))
+}